@@ -0,0 +1,71 @@
+//! `cargo bench --bench report_table` times `build_report_table` on 10k synthetic
+//! transactions, half of which fall outside the watched categories.
+
+use std::collections::HashSet;
+use std::hint::black_box;
+
+use chrono::NaiveDate;
+use criterion::{Criterion, criterion_group, criterion_main};
+use crustynab::report::{self, TransactionSide};
+use crustynab::ynab::{Category, Transaction};
+
+const CATEGORIES: usize = 40;
+const TRANSACTIONS: usize = 10_000;
+
+fn categories() -> Vec<Category> {
+    (0..CATEGORIES)
+        .map(|i| Category {
+            id: format!("c{i}"),
+            name: format!("Category {i}"),
+            category_group_name: Some(format!("Group {}", i % 5)),
+            budgeted: 50_000,
+            balance: 20_000,
+            goal_cadence: Some(1),
+            goal_target: Some(60_000),
+            goal_percentage_complete: Some(50),
+            goal_type: None,
+            hidden: false,
+            deleted: false,
+        })
+        .collect()
+}
+
+/// Spread over twice as many categories as are watched, so the watch-list filter has
+/// work to do.
+fn transactions() -> Vec<Transaction> {
+    let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    (0..TRANSACTIONS)
+        .map(|i| Transaction {
+            id: format!("t{i}"),
+            date: start + chrono::Duration::days((i % 365) as i64),
+            amount: -1000,
+            payee_name: Some(format!("Payee {}", i % 50)),
+            category_name: Some(format!("Category {}", i % (2 * CATEGORIES))),
+            subtransactions: vec![],
+        })
+        .collect()
+}
+
+fn build_report_table(c: &mut Criterion) {
+    let categories = categories();
+    let names: HashSet<String> = categories.iter().map(|c| c.name.clone()).collect();
+    let category_frame = report::categories_to_polars(&categories).unwrap();
+    let transaction_frame = report::transactions_to_polars(&transactions()).unwrap();
+
+    c.bench_function("build_report_table 10k transactions", |b| {
+        b.iter(|| {
+            report::build_report_table(
+                category_frame.clone(),
+                transaction_frame.clone(),
+                black_box(&names),
+                TransactionSide::All,
+            )
+            .unwrap()
+            .collect()
+            .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, build_report_table);
+criterion_main!(benches);
@@ -1,8 +1,32 @@
 package budgetConfig
 
 budgetName:          string
+budgetId?:           string
 personalAccessToken: string
-categoryGroupWatchList: {[string]: =~"^#[0-9a-fA-F]{6}$"}
+categoryGroupWatchList: {[string]: =~"^(#([0-9a-fA-F]{3}|[0-9a-fA-F]{6}))?$"}
 resolution_date: null | (string & =~"^\\d{4}-\\d{2}-\\d{2}$")
 showAllRows:     bool
-outputFormat: "polars_print" | "csv_print" | {csv_output: string, visual_output?: _|_} | {visual_output: string, csv_output?: _|_}
+preserveGroupOrder?: bool
+spendingAlerts?: bool
+alertThresholdPercent?: number
+showMomTrend?: bool
+rateLimitPause?: bool
+payeeMapping?: {[string]: string}
+showIncomeBreakdown?: bool
+excludeSystemGroups?: bool
+showSparklines?: bool
+excludeTransfers?: bool
+defaultMonths?: int & >=1
+timezone?: string
+budgetOverrides?: {[string]: number}
+htmlExtraCss?: string
+printMode?: bool
+transactionDetailPages?: bool
+maxRowsPerGroup?: int & >=0
+strictMode?: bool
+checkThresholdPercent?: number
+currencySymbol?: string
+serverKnowledgeCache?: string
+onMissingCategory?: "ignore" | "warn" | "error"
+csvDelimiter?: =~"^[\\x00-\\x7f]$"
+outputFormat: "polars_print" | "csv_print" | "ascii_table" | "stdout_json" | {csv_output: string, visual_output?: _|_, chart_output?: _|_} | {visual_output: string, csv_output?: _|_, chart_output?: _|_} | {chart_output: string, csv_output?: _|_, visual_output?: _|_} | {csv_append: string} | {sql_output: string} | {summary_output: string} | {slack_webhook: string}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+
+use crate::visual_report::format_currency;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpendingAlert {
+    pub category_name: String,
+    pub spent: f64,
+    pub budgeted: f64,
+}
+
+impl SpendingAlert {
+    pub fn percent_of_budget(&self) -> Option<f64> {
+        (self.budgeted > 0.0).then(|| self.spent / self.budgeted * 100.0)
+    }
+}
+
+impl fmt::Display for SpendingAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "⚠ {}: spent {}, budgeted {}",
+            self.category_name,
+            format_currency(self.spent, true),
+            format_currency(self.budgeted, true)
+        )?;
+        match self.percent_of_budget() {
+            Some(percent) => write!(f, " ({percent:.0}%)"),
+            None => write!(f, " (no budget)"),
+        }
+    }
+}
+
+/// Categories whose outflow exceeds `threshold_percent` of their budget, in report order.
+pub fn spending_alerts(
+    report_df: &DataFrame,
+    threshold_percent: f64,
+) -> Result<Vec<SpendingAlert>> {
+    let names = report_df
+        .column("category_name")
+        .context("category_name column")?
+        .str()
+        .context("category_name as str")?;
+    let spent = report_df
+        .column("spent")
+        .context("spent column")?
+        .f64()
+        .context("spent as f64")?;
+    let budgeted = report_df
+        .column("budgeted")
+        .context("budgeted column")?
+        .f64()
+        .context("budgeted as f64")?;
+
+    let alerts = names
+        .iter()
+        .zip(spent.iter())
+        .zip(budgeted.iter())
+        .filter_map(|((name, spent), budgeted)| {
+            let name = name?;
+            let spent = spent?.abs();
+            let budgeted = budgeted?;
+            (spent > budgeted * threshold_percent / 100.0).then(|| SpendingAlert {
+                category_name: name.to_string(),
+                spent,
+                budgeted,
+            })
+        })
+        .collect();
+
+    Ok(alerts)
+}
+
+/// Budget-check threshold when the config's `check_threshold_percent` is unset.
+pub const DEFAULT_CHECK_THRESHOLD_PERCENT: f64 = 100.0;
+
+/// Categories failing `--check`: outflow above `threshold_percent` of a non-zero budget.
+/// Unbudgeted categories never fail.
+pub fn over_budget_categories(
+    report_df: &DataFrame,
+    threshold_percent: f64,
+) -> Result<Vec<SpendingAlert>> {
+    Ok(spending_alerts(report_df, threshold_percent)?
+        .into_iter()
+        .filter(|alert| alert.budgeted != 0.0)
+        .collect())
+}
+
+/// The error `--check` runs return when categories are over budget; the CLI exits with
+/// code 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetCheckFailed {
+    pub over_budget: Vec<SpendingAlert>,
+    pub threshold_percent: f64,
+}
+
+impl fmt::Display for BudgetCheckFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "budget check failed: {} categories spent over {}% of their budget",
+            self.over_budget.len(),
+            self.threshold_percent
+        )
+    }
+}
+
+impl std::error::Error for BudgetCheckFailed {}
@@ -0,0 +1,1095 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+
+use crate::alerts;
+use crate::ascii_report;
+use crate::atomic_file::write_file_atomically;
+use crate::calendar_weeks::{
+    Month, MonthWeek, complete_months_before, month_week_for_date, month_weeks, previous_week,
+    trailing_weeks, week_position_in_month,
+};
+use crate::chart_report;
+use crate::config::{self, OutputFormat, SimpleOutputFormat};
+use crate::csv_report::{CsvOptions, CsvRenderer};
+use crate::currency::CurrencyFormat;
+use crate::json_report;
+use crate::names::sanitize_category_name;
+use crate::report;
+use crate::report_metadata::ReportMetadata;
+use crate::server_knowledge_cache::ServerKnowledgeCache;
+use crate::slack_report;
+use crate::sql_report;
+use crate::summary_report;
+use crate::visual_report::{self, VisualSections, write_visual_report_html};
+use crate::ynab::{self, YnabApi};
+
+/// Weeks of history shown in each visual report sparkline, ending with the report week.
+const SPARKLINE_WEEKS: usize = 4;
+
+/// Per-invocation options supplied on the command line rather than in config.json
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub payee_filter: Option<String>,
+    pub color: bool,
+    /// Print the week's transactions with a cumulative `running_total` (csv_print only)
+    pub running_total: bool,
+    /// Report this many complete calendar months instead of a week; falls back to the
+    /// config's `default_months`
+    pub months: Option<u32>,
+    /// Pivot the report table to one `spent` column per category group (csv_print and
+    /// csv_output only)
+    pub wide: bool,
+    /// Add a week-over-week spending comparison (polars_print and csv_print only)
+    pub compare_last_week: bool,
+    /// Add year-to-date progress against annual goals (polars_print and csv_print only)
+    pub show_goal_progress: bool,
+    /// Use this budget ID instead of looking the budget up by name
+    pub budget_id: Option<String>,
+    /// Print each weekly pipeline stage's row counts and timing to stderr
+    pub verbose: bool,
+    /// Also write the week's filtered transactions to this CSV file
+    pub export_transactions: Option<PathBuf>,
+    /// Print the report's week number and dates to stderr after the report
+    pub print_period: bool,
+    /// Fail with [`alerts::BudgetCheckFailed`] when a category is over its budget
+    pub check: bool,
+}
+
+/// `--verbose` progress lines on stderr, each timed from the previous line (or from
+/// creation) so that it covers the stage it reports. Does nothing when disabled.
+pub struct StageLog {
+    last: Option<Cell<Instant>>,
+}
+
+impl StageLog {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            last: enabled.then(|| Cell::new(Instant::now())),
+        }
+    }
+
+    /// A progress line, e.g. `[+23ms] relevant_transactions: 142 → 37 rows`.
+    pub fn line(elapsed: Duration, stage: &str, detail: &str) -> String {
+        format!("[+{}ms] {stage}: {detail}", elapsed.as_millis())
+    }
+
+    fn log(&self, stage: &str, detail: &str) {
+        if let Some(last) = &self.last {
+            eprintln!("{}", Self::line(last.get().elapsed(), stage, detail));
+            last.set(Instant::now());
+        }
+    }
+
+    /// The number of rows in `frame`, or 0 without collecting it when disabled.
+    fn rows(&self, frame: &LazyFrame) -> Result<usize> {
+        if self.last.is_none() {
+            return Ok(0);
+        }
+        Ok(frame
+            .clone()
+            .collect()
+            .context("collecting frame to count rows")?
+            .height())
+    }
+}
+
+/// Warnings about the transactions a run fetches, printed on stderr once per transaction
+/// however many of the run's fetches (report week, comparison week, sparklines, ...)
+/// include it.
+#[derive(Default)]
+struct TransactionWarnings {
+    empty_splits: RefCell<HashSet<String>>,
+    invalid_amounts: RefCell<HashSet<String>>,
+}
+
+impl TransactionWarnings {
+    fn report(&self, transactions: &[ynab::Transaction]) {
+        let mut reported = self.empty_splits.borrow_mut();
+        let empty_splits: Vec<String> = report::empty_split_transactions(transactions)
+            .into_iter()
+            .filter(|id| reported.insert(id.clone()))
+            .collect();
+        let mut reported = self.invalid_amounts.borrow_mut();
+        let invalid_amounts: Vec<String> = report::invalid_transaction_amounts(transactions)
+            .into_iter()
+            .filter(|(id, _)| reported.insert(id.clone()))
+            .map(|(id, amount_milli)| format!("{id} ({amount_milli})"))
+            .collect();
+        if !empty_splits.is_empty() {
+            eprintln!(
+                "Warning: split transactions without subtransactions reported as {}: {}",
+                report::UNCATEGORIZED_SPLIT_CATEGORY,
+                empty_splits.join(", ")
+            );
+        }
+        if !invalid_amounts.is_empty() {
+            eprintln!(
+                "Warning: transactions with invalid amounts counted as 0: {}",
+                invalid_amounts.join(", ")
+            );
+        }
+    }
+}
+
+/// Fetches the budget through `api` and renders the report `cfg` asks for. Printed
+/// output formats (and the week banner) go to `out`; file formats are written to disk.
+pub fn run(
+    api: &dyn YnabApi,
+    cfg: &config::Config,
+    opts: &RunOptions,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let csv_renderer = CsvRenderer::new(CsvOptions::from_config(cfg)?);
+    let stages = StageLog::new(opts.verbose);
+    let transaction_warnings = TransactionWarnings::default();
+    let (budget_id, categories_to_watch) = watched_categories(api, cfg, opts)?;
+    stages.log(
+        "watched_categories",
+        &format!("{} categories", categories_to_watch.len()),
+    );
+    if let Some(months) = opts.months.or(cfg.default_months) {
+        return run_months(
+            api,
+            cfg,
+            opts,
+            &budget_id,
+            &categories_to_watch,
+            months,
+            out,
+        );
+    }
+
+    let report_week = month_week_for_date(cfg.today()?)?;
+    let report_start = report_week.week_start;
+    let report_end = report_week.week_end;
+
+    let month_categories = report::select_watched_month_categories(
+        api.get_month_categories_batch(&budget_id, report_start)
+            .context("fetching month categories")?,
+        &categories_to_watch,
+    )?;
+    stages.log(
+        "get_month_categories_batch",
+        &format!("{} categories", month_categories.len()),
+    );
+
+    let categories_budgeted = with_budget_overrides(
+        report::with_free_balance(report::categories_to_polars(&month_categories)?),
+        cfg,
+    );
+
+    let transactions = api.get_transactions(&budget_id, report_start)?;
+    stages.log(
+        "get_transactions",
+        &format!("{} transactions", transactions.len()),
+    );
+    let prepared_transactions =
+        prepare_transactions(&transactions, cfg, opts, &transaction_warnings)?;
+    let prepared_rows = stages.rows(&prepared_transactions.0)?;
+    stages.log(
+        "transactions_to_polars",
+        &format!("{} → {prepared_rows} rows", transactions.len()),
+    );
+    let transactions_frame =
+        report::relevant_transactions(prepared_transactions, report_start, report_end);
+    let relevant_rows = stages.rows(&transactions_frame.0)?;
+    stages.log(
+        "relevant_transactions",
+        &format!("{prepared_rows} → {relevant_rows} rows"),
+    );
+    if let Some(path) = &opts.export_transactions {
+        let mut exported = transactions_frame
+            .0
+            .clone()
+            .select([
+                col("date"),
+                col("payee_name"),
+                col("category_name"),
+                col("amount"),
+            ])
+            .collect()
+            .context("collecting transactions to export")?;
+        std::fs::write(path, csv_renderer.render_frame(&mut exported)?)
+            .with_context(|| format!("writing {path:?}"))?;
+    }
+
+    let category_names: HashSet<String> = month_categories
+        .iter()
+        .map(|c| sanitize_category_name(&c.name).to_string())
+        .collect();
+
+    let running_total_table = if opts.running_total {
+        Some(
+            report::annotate_with_running_total(transactions_frame.clone())?
+                .0
+                .select([
+                    col("date"),
+                    col("payee_name"),
+                    col("category_name"),
+                    col("amount"),
+                    col("running_total"),
+                ]),
+        )
+    } else {
+        None
+    };
+
+    let income_table = if cfg.show_income_breakdown {
+        Some(report::build_income_table(
+            categories_budgeted.clone(),
+            transactions_frame.clone(),
+            &category_names,
+        )?)
+    } else {
+        None
+    };
+    let detail_transactions = cfg
+        .transaction_detail_pages
+        .then(|| transactions_frame.clone());
+    let report_table = report::build_report_table(
+        categories_budgeted.clone(),
+        transactions_frame,
+        &category_names,
+        report::TransactionSide::All,
+    )?;
+    let report_rows = stages.rows(&report_table)?;
+    stages.log(
+        "build_report_table",
+        &format!("{relevant_rows} → {report_rows} rows"),
+    );
+
+    let comparison_table = if opts.compare_last_week {
+        let last_week = previous_week(&report_week)?;
+        let last_week_transactions = report::relevant_transactions(
+            prepare_transactions(
+                &api.get_transactions(&budget_id, last_week.week_start)?,
+                cfg,
+                opts,
+                &transaction_warnings,
+            )?,
+            last_week.week_start,
+            last_week.week_end,
+        );
+        Some(report::build_wow_comparison_table(
+            report_table.clone(),
+            report::build_report_table(
+                categories_budgeted.clone(),
+                last_week_transactions,
+                &category_names,
+                report::TransactionSide::All,
+            )?,
+        )?)
+    } else {
+        None
+    };
+
+    let goal_progress_table = if opts.show_goal_progress {
+        let year_start = NaiveDate::from_ymd_opt(report_start.year(), 1, 1)
+            .ok_or_else(|| anyhow::anyhow!("no January 1 in {}", report_start.year()))?;
+        let year_transactions = report::relevant_transactions(
+            prepare_transactions(
+                &api.get_transactions(&budget_id, year_start)?,
+                cfg,
+                opts,
+                &transaction_warnings,
+            )?,
+            year_start,
+            report_end,
+        );
+        Some(report::build_goal_progress_table(
+            categories_budgeted.clone(),
+            report::build_report_table(
+                categories_budgeted,
+                year_transactions,
+                &category_names,
+                report::TransactionSide::All,
+            )?,
+            report_start.month(),
+        )?)
+    } else {
+        None
+    };
+
+    let (week_of_month, weeks_in_month) = week_position_in_month(&report_week);
+    let report_table =
+        report::with_weighted_percent_spent(report_table, week_of_month, weeks_in_month)?;
+
+    #[cfg(debug_assertions)]
+    report::validate_report_table(
+        &report_table
+            .clone()
+            .collect()
+            .context("collecting report table for validation")?,
+        category_names.len(),
+    )?;
+
+    let report_table = if cfg.show_mom_trend {
+        let previous_month = report_start
+            .checked_sub_months(chrono::Months::new(1))
+            .ok_or_else(|| anyhow::anyhow!("no month before {report_start}"))?;
+        let previous_categories = report::select_watched_month_categories(
+            api.get_month_categories_batch(&budget_id, previous_month)
+                .context("fetching previous month categories")?,
+            &categories_to_watch,
+        )?;
+        report::with_budget_change(
+            report_table,
+            report::categories_to_polars(&previous_categories)?,
+        )
+    } else {
+        report_table
+    };
+
+    let report_table = if cfg.preserve_group_order {
+        report::order_groups_by_watch_list(report_table, &cfg.category_group_watch_list)?
+    } else {
+        report_table
+    };
+
+    let report_table_full = report_table.clone();
+    let report_with_meta = report::collect_with_meta(report_table_full.clone(), &report_week)?;
+    if cfg.spending_alerts {
+        for alert in alerts::spending_alerts(&report_with_meta.frame, cfg.alert_threshold_percent)?
+        {
+            if opts.color {
+                eprintln!("\x1b[33m{alert}\x1b[0m");
+            } else {
+                eprintln!("{alert}");
+            }
+        }
+    }
+    let report_table_display = if cfg.show_all_rows {
+        report_table
+    } else {
+        report_table.filter(col("spent").neq(lit(0.0)))
+    };
+
+    let category_group_totals = report::build_category_group_totals_table(
+        report_table_full.clone(),
+        report::GroupTotalsSortOrder::default(),
+    )?;
+    stages.log(
+        "build_category_group_totals_table",
+        &format!(
+            "{} → {} rows",
+            stages.rows(&report_table_full)?,
+            stages.rows(&category_group_totals)?
+        ),
+    );
+    let category_group_totals = if cfg.preserve_group_order {
+        report::order_groups_by_watch_list(category_group_totals, &cfg.category_group_watch_list)?
+    } else {
+        category_group_totals
+    };
+
+    let week_year = report_week.week_start.year();
+    let week_number = report_week.week_number;
+    let start_label = report_start.format("%A %Y-%m-%d");
+    let end_label = report_end.format("%A %Y-%m-%d");
+    // NDJSON output stays one JSON object per line, without the title line
+    if !matches!(
+        cfg.output_format,
+        OutputFormat::Simple(SimpleOutputFormat::StdoutJson)
+    ) {
+        writeln!(
+            out,
+            "Week {week_number} of {week_year}, starting on {start_label} and ending on {end_label}"
+        )?;
+    }
+
+    let visual_week_label = week_label(&report_week);
+    let metadata = ReportMetadata::new(&report_week, &cfg.budget_name, chrono::Utc::now());
+
+    if running_total_table.is_some()
+        && !matches!(
+            cfg.output_format,
+            OutputFormat::Simple(SimpleOutputFormat::CsvPrint)
+        )
+    {
+        eprintln!("Warning: --running-total only applies to the csv_print output format");
+    }
+    let printed_format = matches!(
+        cfg.output_format,
+        OutputFormat::Simple(SimpleOutputFormat::PolarsPrint | SimpleOutputFormat::CsvPrint)
+    );
+    if comparison_table.is_some() && !printed_format {
+        eprintln!(
+            "Warning: --compare-last-week only applies to the polars_print and csv_print output formats"
+        );
+    }
+    if goal_progress_table.is_some() && !printed_format {
+        eprintln!(
+            "Warning: --show-goal-progress only applies to the polars_print and csv_print output formats"
+        );
+    }
+    let wide_applies = matches!(
+        cfg.output_format,
+        OutputFormat::Simple(SimpleOutputFormat::CsvPrint) | OutputFormat::CsvFile { .. }
+    );
+    if opts.wide && !wide_applies {
+        eprintln!("Warning: --wide only applies to the csv_print and csv_output formats");
+    }
+    let report_table_display = if opts.wide && wide_applies {
+        report::report_table_to_wide_format(report_table_display)?
+    } else {
+        report_table_display
+    };
+
+    match &cfg.output_format {
+        OutputFormat::Simple(SimpleOutputFormat::PolarsPrint) => {
+            let df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            let totals = category_group_totals
+                .collect()
+                .context("collecting totals")?;
+            writeln!(out, "{df}")?;
+            writeln!(out, "Category group totals")?;
+            writeln!(out, "{totals}")?;
+            if let Some(income_table) = income_table {
+                let income = income_table.collect().context("collecting income table")?;
+                writeln!(out, "Income")?;
+                writeln!(out, "{income}")?;
+            }
+            if let Some(comparison_table) = comparison_table {
+                let comparison = comparison_table
+                    .collect()
+                    .context("collecting week-over-week comparison")?;
+                writeln!(out, "Week over week")?;
+                writeln!(out, "{comparison}")?;
+            }
+            if let Some(goal_progress_table) = goal_progress_table {
+                let goal_progress = goal_progress_table
+                    .collect()
+                    .context("collecting goal progress")?;
+                writeln!(out, "Goal progress")?;
+                writeln!(out, "{goal_progress}")?;
+            }
+        }
+        OutputFormat::Simple(SimpleOutputFormat::AsciiTable) => {
+            let df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            let totals = category_group_totals
+                .collect()
+                .context("collecting totals")?;
+            ascii_report::print_ascii_table(&df, out)?;
+            writeln!(out, "Category group totals")?;
+            ascii_report::print_ascii_table(&totals, out)?;
+            if let Some(income_table) = income_table {
+                let income = income_table.collect().context("collecting income table")?;
+                writeln!(out, "Income")?;
+                ascii_report::print_ascii_table(&income, out)?;
+            }
+        }
+        OutputFormat::Simple(SimpleOutputFormat::StdoutJson) => {
+            let df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            let totals = category_group_totals
+                .collect()
+                .context("collecting totals")?;
+            json_report::write_ndjson_rows(&df, "report", out)?;
+            json_report::write_ndjson_rows(&totals, "totals", out)?;
+        }
+        OutputFormat::Simple(SimpleOutputFormat::CsvPrint) => {
+            let mut df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            let mut totals = category_group_totals
+                .collect()
+                .context("collecting totals")?;
+            let csv = csv_renderer.render_frame(&mut df)?;
+            let totals_csv = csv_renderer.render_frame(&mut totals)?;
+            write!(out, "{}", metadata.header_lines("# "))?;
+            write!(out, "{csv}")?;
+            writeln!(out, "category_group_totals")?;
+            write!(out, "{totals_csv}")?;
+            if let Some(income_table) = income_table {
+                let mut income = income_table.collect().context("collecting income table")?;
+                writeln!(out, "income")?;
+                write!(out, "{}", csv_renderer.render_frame(&mut income)?)?;
+            }
+            if let Some(comparison_table) = comparison_table {
+                let mut comparison = comparison_table
+                    .collect()
+                    .context("collecting week-over-week comparison")?;
+                writeln!(out, "week_over_week")?;
+                write!(out, "{}", csv_renderer.render_frame(&mut comparison)?)?;
+            }
+            if let Some(goal_progress_table) = goal_progress_table {
+                let mut goal_progress = goal_progress_table
+                    .collect()
+                    .context("collecting goal progress")?;
+                writeln!(out, "goal_progress")?;
+                write!(out, "{}", csv_renderer.render_frame(&mut goal_progress)?)?;
+            }
+            if let Some(running_total_table) = running_total_table {
+                let mut running = running_total_table
+                    .collect()
+                    .context("collecting running total")?;
+                writeln!(out, "transactions")?;
+                write!(out, "{}", csv_renderer.render_frame(&mut running)?)?;
+            }
+        }
+        OutputFormat::CsvFile { csv_output } => {
+            let mut df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            let mut totals = category_group_totals
+                .collect()
+                .context("collecting totals")?;
+            let header = metadata.header_lines("# ");
+            let csv = format!("{header}{}", csv_renderer.render_frame(&mut df)?);
+            let totals_csv = format!("{header}{}", csv_renderer.render_frame(&mut totals)?);
+
+            let stem = csv_output
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("report");
+            let ext = csv_output
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("csv");
+            let totals_path =
+                csv_output.with_file_name(format!("{stem}_category_group_totals.{ext}"));
+
+            std::fs::write(csv_output, &csv).with_context(|| format!("writing {csv_output:?}"))?;
+            std::fs::write(&totals_path, &totals_csv)
+                .with_context(|| format!("writing {totals_path:?}"))?;
+            if let Some(income_table) = income_table {
+                let mut income = income_table.collect().context("collecting income table")?;
+                let income_csv = format!("{header}{}", csv_renderer.render_frame(&mut income)?);
+                let income_path = csv_output.with_file_name(format!("{stem}_income.{ext}"));
+                std::fs::write(&income_path, &income_csv)
+                    .with_context(|| format!("writing {income_path:?}"))?;
+            }
+        }
+        OutputFormat::CsvFileAppend { csv_output } => {
+            let mut df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            csv_renderer.append_section(
+                csv_output,
+                &mut df,
+                &format!("Week {week_number}, {report_start}"),
+            )?;
+        }
+        OutputFormat::VisualFile { visual_output } => {
+            let sparklines = if cfg.show_sparklines {
+                let weeks = trailing_weeks(&report_week, SPARKLINE_WEEKS);
+                let trend_start = weeks.first().map_or(report_start, |w| w.week_start);
+                let trend_transactions = prepare_transactions(
+                    &api.get_transactions(&budget_id, trend_start)?,
+                    cfg,
+                    opts,
+                    &transaction_warnings,
+                )?;
+                Some(visual_report::sparklines_from_trend_table(
+                    report::build_weekly_trend_table(
+                        report::categories_to_polars(&month_categories)?,
+                        trend_transactions,
+                        &category_names,
+                        &weeks,
+                    )?,
+                )?)
+            } else {
+                None
+            };
+            let print_stylesheet_path = cfg
+                .print_mode
+                .then(|| visual_output.with_extension("print.css"));
+            if let Some(path) = &print_stylesheet_path {
+                std::fs::write(path, visual_report::print_stylesheet())
+                    .with_context(|| format!("writing {path:?}"))?;
+            }
+            let print_stylesheet_href = print_stylesheet_path
+                .as_deref()
+                .and_then(Path::file_name)
+                .and_then(|name| name.to_str());
+            let currency_format = display_currency_format(api, cfg, &budget_id)?;
+            let transaction_detail_dir = detail_transactions
+                .map(|transactions| {
+                    write_transaction_detail_pages(
+                        visual_output,
+                        transactions,
+                        &category_names,
+                        &visual_week_label,
+                        &currency_format,
+                    )
+                })
+                .transpose()?;
+            write_file_atomically(visual_output, |writer| {
+                write_visual_report_html(
+                    writer,
+                    report_table_full,
+                    &cfg.category_group_watch_list,
+                    &visual_week_label,
+                    week_year,
+                    cfg.show_all_rows,
+                    VisualSections {
+                        income_table,
+                        sparklines: sparklines.as_ref(),
+                        extra_css: cfg.html_extra_css.as_deref(),
+                        print_stylesheet_href,
+                        transaction_detail_dir: transaction_detail_dir.as_deref(),
+                        currency_format: Some(&currency_format),
+                        max_rows_per_group: cfg.max_rows_per_group,
+                    },
+                )
+            })?;
+        }
+        OutputFormat::SqliteFile { sql_output } => {
+            let df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            let totals = category_group_totals
+                .collect()
+                .context("collecting totals")?;
+            let income = income_table
+                .map(|income| income.collect().context("collecting income table"))
+                .transpose()?;
+            let sql = format!(
+                "{}{}",
+                metadata.header_lines("-- "),
+                sql_report::build_sql_report(&df, &totals, income.as_ref(), report_start)?
+            );
+            std::fs::write(sql_output, &sql).with_context(|| format!("writing {sql_output:?}"))?;
+        }
+        OutputFormat::SlackBlocks { slack_webhook } => {
+            let df = report_table_display
+                .collect()
+                .context("collecting report table")?;
+            let totals = category_group_totals
+                .collect()
+                .context("collecting totals")?;
+            for payload in slack_report::report_to_slack_blocks(&df, &totals, &visual_week_label)? {
+                slack_report::post_slack_blocks(slack_webhook, &payload)?;
+            }
+        }
+        OutputFormat::SummaryFile { summary_output } => {
+            summary_report::write_summary(
+                summary_output,
+                &report_table_full,
+                &visual_week_label,
+                income_table.as_ref(),
+            )?;
+        }
+        OutputFormat::ChartDataFile { chart_output } => {
+            let weeks = month_weeks(Month::from(report_start));
+            let month_start = weeks.first().map_or(report_start, |w| w.week_start);
+            let month_transactions = api.get_transactions(&budget_id, month_start)?;
+            let month_transactions_frame =
+                prepare_transactions(&month_transactions, cfg, opts, &transaction_warnings)?;
+            let weekly_spending = report::build_weekly_group_spending(
+                report::categories_to_polars(&month_categories)?,
+                month_transactions_frame,
+                &category_names,
+                &weeks,
+            )?;
+            let week_labels: Vec<(usize, String)> = weeks
+                .iter()
+                .map(|w| (w.week_number, week_label(w)))
+                .collect();
+            let chart = chart_report::build_chart_data(
+                weekly_spending,
+                &cfg.category_group_watch_list,
+                &week_labels,
+            )?;
+
+            let json = chart_report::chart_data_json(&chart, &metadata)?;
+            let title = report_start.format("%B %Y spending").to_string();
+            let html = chart_report::build_chart_html(&chart, &title)?;
+            let html_path = chart_output.with_extension("html");
+
+            std::fs::write(chart_output, &json)
+                .with_context(|| format!("writing {chart_output:?}"))?;
+            std::fs::write(&html_path, &html).with_context(|| format!("writing {html_path:?}"))?;
+        }
+    }
+
+    if opts.print_period {
+        eprintln!("Report period: {}", report_with_meta.period_label());
+    }
+
+    if opts.check {
+        let threshold_percent = cfg
+            .check_threshold_percent
+            .unwrap_or(alerts::DEFAULT_CHECK_THRESHOLD_PERCENT);
+        let over_budget =
+            alerts::over_budget_categories(&report_with_meta.frame, threshold_percent)?;
+        if !over_budget.is_empty() {
+            for alert in &over_budget {
+                eprintln!("{alert}");
+            }
+            return Err(alerts::BudgetCheckFailed {
+                over_budget,
+                threshold_percent,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// The `--months` variant of [`run`]: one row per watched category and month over the
+/// `month_count` complete calendar months before the resolution date.
+fn run_months(
+    api: &dyn YnabApi,
+    cfg: &config::Config,
+    opts: &RunOptions,
+    budget_id: &str,
+    categories_to_watch: &[ynab::Category],
+    month_count: u32,
+    out: &mut dyn Write,
+) -> Result<()> {
+    if month_count == 0 {
+        anyhow::bail!("the number of months to report must be at least 1");
+    }
+    let csv_renderer = CsvRenderer::new(CsvOptions::from_config(cfg)?);
+    let today = cfg.today()?;
+    let months = complete_months_before(today, month_count as usize);
+    let (first_month, last_month) = match (months.first(), months.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => anyhow::bail!("no complete months before {today}"),
+    };
+
+    let month_categories = months
+        .iter()
+        .map(|month| {
+            let categories = report::select_watched_month_categories(
+                api.get_month_categories_batch(budget_id, month.first_day())
+                    .with_context(|| format!("fetching {month} categories"))?,
+                categories_to_watch,
+            )?;
+            Ok((
+                *month,
+                with_budget_overrides(
+                    report::categories_to_polars_with_metadata(&categories, month.first_day())?,
+                    cfg,
+                ),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let transactions = api.get_transactions(budget_id, first_month.first_day())?;
+    let transactions_frame =
+        prepare_transactions(&transactions, cfg, opts, &TransactionWarnings::default())?;
+
+    let category_names: HashSet<String> = categories_to_watch
+        .iter()
+        .map(|c| sanitize_category_name(&c.name).to_string())
+        .collect();
+    let monthly_table =
+        report::build_monthly_report_table(month_categories, transactions_frame, &category_names)?;
+    let monthly_display = if cfg.show_all_rows {
+        monthly_table.clone()
+    } else {
+        monthly_table.clone().filter(
+            col("spent")
+                .neq(lit(0.0))
+                .any(false)
+                .over([col("category_name")]),
+        )
+    };
+
+    let title = if first_month == last_month {
+        format!("Month {first_month}")
+    } else {
+        format!("Months {first_month} to {last_month}")
+    };
+    writeln!(
+        out,
+        "{title}, starting on {} and ending on {}",
+        first_month.first_day().format("%A %Y-%m-%d"),
+        last_month.last_day().format("%A %Y-%m-%d")
+    )?;
+    if opts.running_total {
+        eprintln!("Warning: --running-total does not apply to monthly reports");
+    }
+    if opts.wide {
+        eprintln!("Warning: --wide does not apply to monthly reports");
+    }
+    if opts.compare_last_week {
+        eprintln!("Warning: --compare-last-week does not apply to monthly reports");
+    }
+    if opts.show_goal_progress {
+        eprintln!("Warning: --show-goal-progress does not apply to monthly reports");
+    }
+    if opts.export_transactions.is_some() {
+        eprintln!("Warning: --export-transactions does not apply to monthly reports");
+    }
+    if opts.print_period {
+        eprintln!("Warning: --print-period does not apply to monthly reports");
+    }
+    if opts.check {
+        eprintln!("Warning: --check does not apply to monthly reports");
+    }
+
+    match &cfg.output_format {
+        OutputFormat::Simple(SimpleOutputFormat::PolarsPrint) => {
+            let df = monthly_display
+                .collect()
+                .context("collecting monthly report")?;
+            writeln!(out, "{df}")?;
+        }
+        OutputFormat::Simple(SimpleOutputFormat::AsciiTable) => {
+            let df = monthly_display
+                .collect()
+                .context("collecting monthly report")?;
+            ascii_report::print_ascii_table(&df, out)?;
+        }
+        OutputFormat::Simple(SimpleOutputFormat::CsvPrint) => {
+            let mut df = monthly_display
+                .collect()
+                .context("collecting monthly report")?;
+            write!(out, "{}", csv_renderer.render_frame(&mut df)?)?;
+        }
+        OutputFormat::CsvFile { csv_output } => {
+            let mut df = monthly_display
+                .collect()
+                .context("collecting monthly report")?;
+            let csv = csv_renderer.render_frame(&mut df)?;
+            std::fs::write(csv_output, &csv).with_context(|| format!("writing {csv_output:?}"))?;
+        }
+        OutputFormat::VisualFile { visual_output } => {
+            let month_labels: Vec<String> = months.iter().map(Month::to_string).collect();
+            let html = visual_report::build_monthly_visual_html(
+                monthly_table,
+                &cfg.category_group_watch_list,
+                &month_labels,
+                &title,
+                cfg.show_all_rows,
+                cfg.html_extra_css.as_deref(),
+            )?;
+            write_file_atomically(visual_output, |writer| {
+                writer.write_all(html.as_bytes())?;
+                Ok(())
+            })?;
+        }
+        _ => anyhow::bail!(
+            "monthly reports support the polars_print, csv_print, ascii_table, csv_output and visual_output formats"
+        ),
+    }
+
+    Ok(())
+}
+
+/// The budget to report on: `--budget-id` as given, without calling `get_budgets`;
+/// otherwise the config's `budgetId`, checked against `budgetName`; otherwise the ID of
+/// the budget named `budgetName`.
+fn resolve_budget_id(api: &dyn YnabApi, cfg: &config::Config, opts: &RunOptions) -> Result<String> {
+    if let Some(budget_id) = &opts.budget_id {
+        return Ok(budget_id.clone());
+    }
+    let budgets = api.get_budgets()?;
+    match &cfg.budget_id {
+        Some(budget_id) => {
+            report::check_budget_id(&budgets, budget_id, &cfg.budget_name)?;
+            Ok(budget_id.clone())
+        }
+        None => report::get_budget_id(&budgets, &cfg.budget_name)
+            .ok_or_else(|| anyhow::anyhow!("no budget found with name {}", cfg.budget_name)),
+    }
+}
+
+/// Resolves `cfg`'s budget and the categories its watch list selects, warning about
+/// unknown groups and an empty selection.
+fn watched_categories(
+    api: &dyn YnabApi,
+    cfg: &config::Config,
+    opts: &RunOptions,
+) -> Result<(String, Vec<ynab::Category>)> {
+    let budget_id = resolve_budget_id(api, cfg, opts)?;
+
+    let category_groups = api.get_category_groups(&budget_id)?;
+    let missing =
+        report::get_missing_category_groups(&category_groups, &cfg.category_group_watch_list);
+    if !missing.is_empty() {
+        let mut names: Vec<&str> = missing.iter().map(String::as_str).collect();
+        names.sort();
+        eprintln!(
+            "Warning: categoryGroupWatchList includes unknown category groups: {}",
+            names.join(", ")
+        );
+    }
+
+    let watched = report::get_categories_to_watch(
+        &category_groups,
+        &cfg.category_group_watch_list,
+        None,
+        cfg.exclude_system_groups,
+        cfg.strict_mode,
+    )?;
+    for warning in &watched.warnings {
+        eprintln!("Warning: {warning}");
+    }
+    let categories_to_watch = watched.categories;
+    if categories_to_watch.is_empty() {
+        eprintln!(
+            "Warning: no categories to report; categoryGroupWatchList is empty or all of its categories are hidden or deleted"
+        );
+    }
+    if let Some(path) = &cfg.server_knowledge_cache {
+        check_vanished_categories(
+            &ServerKnowledgeCache::new(path),
+            &budget_id,
+            &category_groups,
+            &cfg.category_group_watch_list,
+            cfg.on_missing_category,
+        )?;
+    }
+    Ok((budget_id, categories_to_watch))
+}
+
+/// Compares the categories in `category_groups` with those `cache` recorded for
+/// `budget_id` and handles the ones in watched groups that vanished as `behavior` says.
+/// The current categories are recorded first, so a vanished category is reported once
+/// and a changed watch list needs no reset.
+fn check_vanished_categories(
+    cache: &ServerKnowledgeCache,
+    budget_id: &str,
+    category_groups: &[ynab::CategoryGroup],
+    watch_list: &indexmap::IndexMap<String, String>,
+    behavior: config::MissingCategoryBehavior,
+) -> Result<()> {
+    let current = report::known_categories(category_groups);
+    let previous = cache.known_categories(budget_id)?;
+    cache.record_known_categories(budget_id, current.clone())?;
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+    let vanished = report::get_vanished_categories(&previous, &current, watch_list);
+    if vanished.is_empty() {
+        return Ok(());
+    }
+    let message = format!(
+        "watched categories no longer returned by YNAB: {}",
+        vanished.join(", ")
+    );
+    match behavior {
+        config::MissingCategoryBehavior::Ignore => {}
+        config::MissingCategoryBehavior::Warn => eprintln!("Warning: {message}"),
+        config::MissingCategoryBehavior::Error => anyhow::bail!("{message} (onMissingCategory)"),
+    }
+    Ok(())
+}
+
+/// The configured `currencySymbol` with pound-style separators, or else the budget's
+/// own currency format from YNAB.
+fn display_currency_format(
+    api: &dyn YnabApi,
+    cfg: &config::Config,
+    budget_id: &str,
+) -> Result<CurrencyFormat> {
+    match &cfg.currency_symbol {
+        Some(symbol) => Ok(CurrencyFormat {
+            symbol: symbol.clone(),
+            ..CurrencyFormat::gbp()
+        }),
+        None => api.get_budget_currency_format(budget_id),
+    }
+}
+
+/// Writes a `visual_report::build_transaction_detail_html` page for each of
+/// `category_names` into a `<stem>_transactions` directory next to `visual_output`,
+/// returning the directory's name for the report's links.
+fn write_transaction_detail_pages(
+    visual_output: &Path,
+    transactions: report::TransactionFrame,
+    category_names: &HashSet<String>,
+    week_label: &str,
+    currency_format: &CurrencyFormat,
+) -> Result<String> {
+    let stem = visual_output
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| anyhow::anyhow!("output path {visual_output:?} has no file name"))?;
+    let dir_name = format!("{stem}_transactions");
+    let dir = visual_output.with_file_name(&dir_name);
+    std::fs::create_dir_all(&dir).with_context(|| format!("creating {dir:?}"))?;
+    category_names.iter().try_for_each(|category_name| {
+        let path = dir.join(visual_report::transaction_detail_file_name(category_name));
+        let html = visual_report::build_transaction_detail_html_with(
+            category_name,
+            transactions.clone(),
+            week_label,
+            currency_format,
+        )?;
+        std::fs::write(&path, html).with_context(|| format!("writing {path:?}"))
+    })?;
+    Ok(dir_name)
+}
+
+/// `categories` with `cfg.budget_overrides` applied; left as is when there are none, so
+/// the report only gains a `budget_override_applied` column once overrides are set.
+fn with_budget_overrides(
+    categories: report::CategoryFrame,
+    cfg: &config::Config,
+) -> report::CategoryFrame {
+    if cfg.budget_overrides.is_empty() {
+        categories
+    } else {
+        report::apply_budget_override(categories, &cfg.budget_overrides)
+    }
+}
+
+/// `transactions` as a frame, without transfers when `cfg.exclude_transfers`, with the
+/// payee mapping applied and then the `--payee-filter`. Problems with the transactions
+/// go to `warnings`.
+fn prepare_transactions(
+    transactions: &[ynab::Transaction],
+    cfg: &config::Config,
+    opts: &RunOptions,
+    warnings: &TransactionWarnings,
+) -> Result<report::TransactionFrame> {
+    warnings.report(transactions);
+    let frame = report::transactions_to_polars(transactions)?;
+    let frame = if cfg.exclude_transfers {
+        report::filter_transfers(frame)
+    } else {
+        frame
+    };
+    apply_payee_filter(
+        report::apply_payee_mapping(frame, &cfg.payee_mapping)?,
+        opts,
+    )
+}
+
+fn apply_payee_filter(
+    tf: report::TransactionFrame,
+    opts: &RunOptions,
+) -> Result<report::TransactionFrame> {
+    match &opts.payee_filter {
+        Some(pattern) => report::filter_by_payee_regex(tf, pattern),
+        None => Ok(tf),
+    }
+}
+
+pub fn week_label(week: &MonthWeek) -> String {
+    let week_short_start = format_short_date(week.week_start);
+    let week_short_end = format_short_date(week.week_end);
+    format!(
+        "Week {} ({week_short_start} - {week_short_end})",
+        week.week_number
+    )
+}
+
+fn format_short_date(date: chrono::NaiveDate) -> String {
+    let formatted = date.format("%b %d").to_string();
+    if let Some(space_pos) = formatted.rfind(' ') {
+        let (prefix, day_part) = formatted.split_at(space_pos + 1);
+        if let Some(stripped) = day_part.strip_prefix('0') {
+            return format!("{prefix}{stripped}");
+        }
+    }
+    formatted
+}
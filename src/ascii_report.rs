@@ -0,0 +1,135 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+
+/// Label in the first column of the rows that total the rows above them.
+pub const TOTAL_ROW_LABEL: &str = "Total";
+
+/// Column width limits and float precision for [`print_ascii_table_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsciiTableOptions {
+    /// Columns are padded to at least this many characters.
+    pub min_column_width: usize,
+    /// Longer cells are cut to this many characters, ending with `…`.
+    pub max_column_width: usize,
+    /// Decimal places of float cells.
+    pub float_precision: usize,
+}
+
+impl Default for AsciiTableOptions {
+    fn default() -> Self {
+        Self {
+            min_column_width: 1,
+            max_column_width: 32,
+            float_precision: 2,
+        }
+    }
+}
+
+/// Writes `df` as a box-drawn table with the default [`AsciiTableOptions`].
+pub fn print_ascii_table(df: &DataFrame, writer: &mut dyn Write) -> Result<()> {
+    print_ascii_table_with(df, writer, &AsciiTableOptions::default())
+}
+
+/// Writes `df` as a box-drawn table: a header row, numeric columns (and their headers)
+/// right-aligned, nulls blank and a separator before the first row whose first column is
+/// [`TOTAL_ROW_LABEL`].
+pub fn print_ascii_table_with(
+    df: &DataFrame,
+    writer: &mut dyn Write,
+    options: &AsciiTableOptions,
+) -> Result<()> {
+    let columns = df.get_columns();
+    let headers: Vec<String> = columns
+        .iter()
+        .map(|column| truncate(column.name().as_str(), options.max_column_width))
+        .collect();
+    let rows: Vec<Vec<String>> = (0..df.height())
+        .map(|row_idx| {
+            columns
+                .iter()
+                .map(|column| {
+                    let value = column
+                        .get(row_idx)
+                        .with_context(|| format!("reading {} row {row_idx}", column.name()))?;
+                    Ok(truncate(
+                        &cell_text(&value, options.float_precision),
+                        options.max_column_width,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<_>>()?;
+    let right_aligned: Vec<bool> = columns
+        .iter()
+        .map(|column| column.dtype().is_primitive_numeric())
+        .collect();
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(idx, header)| {
+            rows.iter()
+                .map(|row| row[idx].chars().count())
+                .chain([header.chars().count(), options.min_column_width])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    writeln!(writer, "{}", border(&widths, '┌', '┬', '┐'))?;
+    writeln!(writer, "{}", row_line(&headers, &widths, &right_aligned))?;
+    writeln!(writer, "{}", border(&widths, '├', '┼', '┤'))?;
+    let first_total = rows
+        .iter()
+        .position(|row| row.first().is_some_and(|label| label == TOTAL_ROW_LABEL));
+    for (idx, row) in rows.iter().enumerate() {
+        if idx > 0 && first_total == Some(idx) {
+            writeln!(writer, "{}", border(&widths, '├', '┼', '┤'))?;
+        }
+        writeln!(writer, "{}", row_line(row, &widths, &right_aligned))?;
+    }
+    writeln!(writer, "{}", border(&widths, '└', '┴', '┘'))?;
+    Ok(())
+}
+
+fn cell_text(value: &AnyValue, float_precision: usize) -> String {
+    match value {
+        AnyValue::Null => String::new(),
+        AnyValue::String(text) => text.to_string(),
+        AnyValue::StringOwned(text) => text.to_string(),
+        AnyValue::Float32(number) => format!("{number:.float_precision$}"),
+        AnyValue::Float64(number) => format!("{number:.float_precision$}"),
+        other => other.to_string(),
+    }
+}
+
+/// Shortens `text` to at most `width` characters, ending with `…` when cut.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{kept}…")
+}
+
+fn border(widths: &[usize], left: char, middle: char, right: char) -> String {
+    let segments: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+    format!("{left}{}{right}", segments.join(&middle.to_string()))
+}
+
+fn row_line(cells: &[String], widths: &[usize], right_aligned: &[bool]) -> String {
+    let padded: Vec<String> = cells
+        .iter()
+        .zip(widths)
+        .zip(right_aligned)
+        .map(|((cell, width), right)| {
+            if *right {
+                format!(" {cell:>width$} ")
+            } else {
+                format!(" {cell:<width$} ")
+            }
+        })
+        .collect();
+    format!("│{}│", padded.join("│"))
+}
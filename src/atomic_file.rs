@@ -0,0 +1,33 @@
+//! Whole-file writes that never leave a truncated file behind.
+
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Writes to a uniquely named temp file beside `path`, syncs it and renames it over
+/// `path`, so readers never see a partially written file. The temp file is removed if
+/// `write` or the rename fails, and concurrent writers never share one.
+pub fn write_file_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<&std::fs::File>) -> Result<()>,
+) -> Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let temp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("creating a temp file in {dir:?}"))?;
+    let mut writer = BufWriter::new(temp.as_file());
+    write(&mut writer).with_context(|| format!("writing {:?}", temp.path()))?;
+    writer
+        .flush()
+        .with_context(|| format!("writing {:?}", temp.path()))?;
+    drop(writer);
+    temp.as_file()
+        .sync_all()
+        .with_context(|| format!("syncing {:?}", temp.path()))?;
+    temp.persist(path)
+        .with_context(|| format!("renaming temp file to {path:?}"))?;
+    Ok(())
+}
@@ -1,5 +1,90 @@
+use std::fmt;
+
 use chrono::{Datelike, Duration, NaiveDate};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A calendar month, built with `TryFrom<(i32, u32)>` or from a date so it is always
+/// valid.
+pub struct Month {
+    year: i32,
+    month: u32,
+}
+
+impl Month {
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    /// The month number, 1–12.
+    pub fn month(self) -> u32 {
+        self.month
+    }
+
+    pub fn first_day(self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("valid month start")
+    }
+
+    pub fn last_day(self) -> NaiveDate {
+        self.succ().first_day() - Duration::days(1)
+    }
+
+    pub fn succ(self) -> Month {
+        if self.month == 12 {
+            Month {
+                year: self.year + 1,
+                month: 1,
+            }
+        } else {
+            Month {
+                year: self.year,
+                month: self.month + 1,
+            }
+        }
+    }
+
+    pub fn pred(self) -> Month {
+        if self.month == 1 {
+            Month {
+                year: self.year - 1,
+                month: 12,
+            }
+        } else {
+            Month {
+                year: self.year,
+                month: self.month - 1,
+            }
+        }
+    }
+}
+
+impl From<NaiveDate> for Month {
+    fn from(day: NaiveDate) -> Self {
+        Month {
+            year: day.year(),
+            month: day.month(),
+        }
+    }
+}
+
+impl TryFrom<(i32, u32)> for Month {
+    type Error = anyhow::Error;
+
+    fn try_from((year, month): (i32, u32)) -> anyhow::Result<Self> {
+        if !(1..=12).contains(&month) {
+            anyhow::bail!("month {month} of {year} is outside 1..=12");
+        }
+        NaiveDate::from_ymd_opt(year, month, 1)
+            .ok_or_else(|| anyhow::anyhow!("year {year} is outside the supported date range"))?;
+        Ok(Month { year, month })
+    }
+}
+
+impl fmt::Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MonthWeek {
     pub month: u32,
@@ -15,6 +100,29 @@ impl MonthWeek {
             .map(|offset| self.week_start + Duration::days(offset))
             .collect()
     }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.week_start <= date && date <= self.week_end
+    }
+
+    /// Whether the two weeks share at least one date.
+    pub fn overlaps(&self, other: &MonthWeek) -> bool {
+        self.week_start <= other.week_end && other.week_start <= self.week_end
+    }
+
+    /// Position of this week within its month's weeks: 0.0 for the first, 1.0 for the last.
+    pub fn week_progress_fraction(&self, year: i32) -> f64 {
+        let weeks = month_weeks(Month {
+            year,
+            month: self.month,
+        });
+        let first = weeks.first().map_or(self.week_number, |w| w.week_number);
+        let last = weeks.last().map_or(self.week_number, |w| w.week_number);
+        if last == first {
+            return 1.0;
+        }
+        (self.week_number as f64 - first as f64) / (last - first) as f64
+    }
 }
 
 fn previous_sunday(day: NaiveDate) -> NaiveDate {
@@ -36,33 +144,64 @@ fn split_by_month(days: &[NaiveDate]) -> Vec<u32> {
 }
 
 fn make_month_week(
-    year: i32,
-    month: u32,
+    month: Month,
     week_start: NaiveDate,
     week_end: NaiveDate,
     week_number: usize,
 ) -> MonthWeek {
-    let month_first = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month start");
-    let days_in_month = if month == 12 {
-        NaiveDate::from_ymd_opt(year + 1, 1, 1)
-    } else {
-        NaiveDate::from_ymd_opt(year, month + 1, 1)
-    }
-    .expect("valid next month")
-    .pred_opt()
-    .expect("valid last day")
-    .day();
-    let month_last = NaiveDate::from_ymd_opt(year, month, days_in_month).expect("valid month end");
+    let month_first = month.first_day();
+    let month_last = month.succ().first_day().pred_opt().expect("valid last day");
 
     MonthWeek {
-        month,
+        month: month.month,
         week_start: week_start.max(month_first),
         week_end: week_end.min(month_last),
         week_number,
     }
 }
 
-pub fn partition_year_into_month_weeks(year: i32) -> Vec<MonthWeek> {
+/// A year's [`MonthWeek`]s in calendar order, as built by
+/// [`partition_year_into_month_weeks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YearPartition(Vec<MonthWeek>);
+
+impl YearPartition {
+    pub fn year(&self) -> i32 {
+        self.0
+            .first()
+            .expect("a year partition always has weeks")
+            .week_start
+            .year()
+    }
+
+    pub fn weeks_for_month(&self, month: u32) -> Vec<&MonthWeek> {
+        self.0.iter().filter(|w| w.month == month).collect()
+    }
+
+    pub fn week_for_date(&self, date: NaiveDate) -> Option<&MonthWeek> {
+        self.0.iter().find(|w| w.contains(date))
+    }
+
+    pub fn all_weeks(&self) -> &[MonthWeek] {
+        &self.0
+    }
+}
+
+/// Fails unless chrono can represent `year`'s whole week partition, whose first and last
+/// weeks reach up to six days into the neighbouring years; `year` is typically user
+/// input, and [`partition_year_into_month_weeks`] panics outside that range.
+pub fn check_partition_year(year: i32) -> anyhow::Result<()> {
+    let neighbour = |offset: i32, month: u32, day: u32| {
+        year.checked_add(offset)
+            .and_then(|year| NaiveDate::from_ymd_opt(year, month, day))
+    };
+    if neighbour(-1, 12, 25).is_none() || neighbour(1, 1, 7).is_none() {
+        anyhow::bail!("year {year} is outside the supported date range");
+    }
+    Ok(())
+}
+
+pub fn partition_year_into_month_weeks(year: i32) -> YearPartition {
     let first_day = NaiveDate::from_ymd_opt(year, 1, 1).expect("valid year start");
     let last_day = NaiveDate::from_ymd_opt(year, 12, 31).expect("valid year end");
     let anchor_week_start = previous_sunday(first_day);
@@ -80,34 +219,104 @@ pub fn partition_year_into_month_weeks(year: i32) -> Vec<MonthWeek> {
             .collect();
         for month in split_by_month(&in_year_days) {
             result.push(make_month_week(
-                year,
-                month,
+                Month { year, month },
                 week_start,
                 week_end,
                 week_number,
             ));
         }
     }
-    result
+    YearPartition(result)
 }
 
-pub fn month_weeks(year: i32, month: u32) -> Vec<MonthWeek> {
-    partition_year_into_month_weeks(year)
+pub fn month_weeks(month: Month) -> Vec<MonthWeek> {
+    partition_year_into_month_weeks(month.year)
+        .weeks_for_month(month.month)
         .into_iter()
-        .filter(|w| w.month == month)
+        .cloned()
         .collect()
 }
 
+pub fn month_weeks_raw(year: i32, month: u32) -> Vec<MonthWeek> {
+    month_weeks(Month { year, month })
+}
+
+/// 1-based position of `week` among its month's weeks, along with the month's week count.
+pub fn week_position_in_month(week: &MonthWeek) -> (usize, usize) {
+    let weeks = month_weeks(Month {
+        year: week.week_start.year(),
+        month: week.month,
+    });
+    let position = weeks
+        .iter()
+        .position(|w| w == week)
+        .map_or(0, |index| index + 1);
+    (position, weeks.len())
+}
+
 pub fn month_week_for_date(day: NaiveDate) -> anyhow::Result<MonthWeek> {
-    month_weeks(day.year(), day.month())
-        .into_iter()
-        .find(|w| w.week_start <= day && day <= w.week_end)
-        .ok_or_else(|| {
-            anyhow::anyhow!(
-                "Date {} not found in month weeks for {:04}-{:02}",
-                day,
-                day.year(),
-                day.month()
-            )
-        })
+    let month = Month::from(day);
+    partition_year_into_month_weeks(month.year)
+        .week_for_date(day)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Date {day} not found in month weeks for {month}"))
+}
+
+/// The partition week ending the day before `week` starts, which may be in the previous
+/// month or year.
+pub fn previous_week(week: &MonthWeek) -> anyhow::Result<MonthWeek> {
+    let day_before = week
+        .week_start
+        .pred_opt()
+        .ok_or_else(|| anyhow::anyhow!("no day before {}", week.week_start))?;
+    month_week_for_date(day_before)
+}
+
+/// The partition week starting the day after `week` ends, which may be in the next
+/// month or year.
+pub fn next_week(week: &MonthWeek) -> anyhow::Result<MonthWeek> {
+    let day_after = week
+        .week_end
+        .succ_opt()
+        .ok_or_else(|| anyhow::anyhow!("no day after {}", week.week_end))?;
+    month_week_for_date(day_after)
+}
+
+/// The `count` weeks ending with `week` (inclusive), oldest first, reaching back into
+/// the previous year when needed. Shorter when `week` is not a partition week.
+pub fn trailing_weeks(week: &MonthWeek, count: usize) -> Vec<MonthWeek> {
+    let year = week.week_start.year();
+    let weeks: Vec<MonthWeek> = [
+        partition_year_into_month_weeks(year - 1),
+        partition_year_into_month_weeks(year),
+    ]
+    .iter()
+    .flat_map(|partition| partition.all_weeks().iter().cloned())
+    .collect();
+    match weeks.iter().position(|w| w == week) {
+        Some(end) => weeks[(end + 1).saturating_sub(count)..=end].to_vec(),
+        None => Vec::new(),
+    }
+}
+
+/// The `count` complete calendar months before the one containing `day`, oldest first.
+pub fn complete_months_before(day: NaiveDate, count: usize) -> Vec<Month> {
+    let mut months: Vec<Month> =
+        std::iter::successors(Some(Month::from(day).pred()), |m| Some(m.pred()))
+            .take(count)
+            .collect();
+    months.reverse();
+    months
+}
+
+/// This month's weeks, where "this month" is taken from `chrono::Local` (the system
+/// time zone). `app::run` resolves today through `Config::today` instead, which honors
+/// the configured `timezone`.
+pub fn current_month_weeks() -> Vec<MonthWeek> {
+    month_weeks(Month::from(chrono::Local::now().date_naive()))
+}
+
+/// The week containing today's `chrono::Local` date.
+pub fn current_week() -> anyhow::Result<MonthWeek> {
+    month_week_for_date(chrono::Local::now().date_naive())
 }
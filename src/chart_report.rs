@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use polars::prelude::*;
+use serde::Serialize;
+
+use crate::report::resolve_group_color;
+use crate::report_metadata::ReportMetadata;
+
+pub const CHART_JS_CDN: &str = "https://cdn.jsdelivr.net/npm/chart.js@4";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartPoint {
+    pub week: String,
+    pub spent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartDataset {
+    pub label: String,
+    pub data: Vec<ChartPoint>,
+    pub background_color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartData {
+    pub labels: Vec<String>,
+    pub datasets: Vec<ChartDataset>,
+}
+
+/// Shapes the output of `report::build_weekly_group_spending` into Chart.js datasets.
+///
+/// `week_labels` pairs each `week_number` with its axis label, in display order.
+/// Spending is reported as a positive outflow, matching the visual report.
+pub fn build_chart_data(
+    weekly_spending: LazyFrame,
+    group_colors: &IndexMap<String, String>,
+    week_labels: &[(usize, String)],
+) -> Result<ChartData> {
+    let df = weekly_spending
+        .collect()
+        .context("collecting weekly spending for chart")?;
+
+    let week_numbers = df
+        .column("week_number")
+        .context("week_number column")?
+        .cast(&DataType::UInt64)
+        .context("week_number as u64")?;
+    let week_numbers = week_numbers.u64().context("week_number u64")?;
+    let groups = df
+        .column("category_group_name")
+        .context("category_group_name column")?
+        .str()
+        .context("category_group_name as str")?;
+    let spent = df
+        .column("spent")
+        .context("spent column")?
+        .f64()
+        .context("spent as f64")?;
+
+    let lookup: HashMap<(u64, &str), f64> = (0..df.height())
+        .filter_map(|i| Some(((week_numbers.get(i)?, groups.get(i)?), spent.get(i)?)))
+        .collect();
+
+    let datasets = group_colors
+        .keys()
+        .map(|group_name| ChartDataset {
+            label: group_name.clone(),
+            data: week_labels
+                .iter()
+                .map(|(week_number, label)| {
+                    let spent = lookup
+                        .get(&(*week_number as u64, group_name.as_str()))
+                        .copied()
+                        .unwrap_or(0.0);
+                    ChartPoint {
+                        week: label.clone(),
+                        // subtract rather than negate so zero weeks serialize as 0.0, not -0.0
+                        spent: 0.0 - spent,
+                    }
+                })
+                .collect(),
+            background_color: resolve_group_color(group_name, group_colors).to_string(),
+        })
+        .collect();
+
+    Ok(ChartData {
+        labels: week_labels.iter().map(|(_, label)| label.clone()).collect(),
+        datasets,
+    })
+}
+
+#[derive(Serialize)]
+struct ChartDocument<'a> {
+    metadata: &'a ReportMetadata,
+    #[serde(flatten)]
+    chart: &'a ChartData,
+}
+
+/// Chart data with a top-level `metadata` key alongside `labels` and `datasets`.
+pub fn chart_data_json(chart: &ChartData, metadata: &ReportMetadata) -> Result<String> {
+    serde_json::to_string_pretty(&ChartDocument { metadata, chart })
+        .context("serializing chart data")
+}
+
+/// Standalone page that renders `chart` with Chart.js; the data is inlined so the
+/// file works when opened directly from disk.
+pub fn build_chart_html(chart: &ChartData, title: &str) -> Result<String> {
+    let data_json = serde_json::to_string(chart)
+        .context("serializing chart data for HTML")?
+        .replace("</", "<\\/");
+    let escaped_title = html_escape::encode_text(title);
+
+    let html = [
+        "<!DOCTYPE html>",
+        r#"<html lang="en">"#,
+        "<head>",
+        r#"  <meta charset="utf-8">"#,
+        r#"  <meta name="viewport" content="width=device-width, initial-scale=1">"#,
+        &format!("  <title>{escaped_title}</title>"),
+        &format!(r#"  <script src="{CHART_JS_CDN}"></script>"#),
+        "</head>",
+        "<body>",
+        &format!("  <h1>{escaped_title}</h1>"),
+        r#"  <canvas id="spending-chart"></canvas>"#,
+        "  <script>",
+        &format!("    const chartData = {data_json};"),
+        r#"    new Chart(document.getElementById("spending-chart"), {"#,
+        r#"      type: "bar","#,
+        "      data: chartData,",
+        "      options: {",
+        r#"        parsing: { xAxisKey: "week", yAxisKey: "spent" },"#,
+        "        scales: { x: { stacked: true }, y: { stacked: true } },",
+        "      },",
+        "    });",
+        "  </script>",
+        "</body>",
+        "</html>",
+    ];
+
+    Ok(format!("{}\n", html.join("\n")))
+}
@@ -1,24 +1,63 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use indexmap::IndexMap;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum SimpleOutputFormat {
     #[serde(rename = "polars_print")]
     PolarsPrint,
     #[serde(rename = "csv_print")]
     CsvPrint,
+    #[serde(rename = "ascii_table")]
+    AsciiTable,
+    #[serde(rename = "stdout_json")]
+    StdoutJson,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum OutputFormat {
     Simple(SimpleOutputFormat),
-    CsvFile { csv_output: PathBuf },
-    VisualFile { visual_output: PathBuf },
+    CsvFile {
+        csv_output: PathBuf,
+    },
+    CsvFileAppend {
+        #[serde(rename = "csv_append")]
+        csv_output: PathBuf,
+    },
+    VisualFile {
+        visual_output: PathBuf,
+    },
+    ChartDataFile {
+        chart_output: PathBuf,
+    },
+    SqliteFile {
+        sql_output: PathBuf,
+    },
+    SummaryFile {
+        summary_output: PathBuf,
+    },
+    SlackBlocks {
+        #[serde(serialize_with = "serialize_redacted")]
+        slack_webhook: String,
+    },
+}
+
+impl OutputFormat {
+    /// `self` with the Slack webhook URL, a bearer secret, replaced by [`REDACTED`].
+    pub fn redacted(&self) -> OutputFormat {
+        match self {
+            OutputFormat::SlackBlocks { .. } => OutputFormat::SlackBlocks {
+                slack_webhook: REDACTED.to_string(),
+            },
+            other => other.clone(),
+        }
+    }
 }
 
 impl Default for OutputFormat {
@@ -27,10 +66,24 @@ impl Default for OutputFormat {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// What a run does when a category in a watched group that YNAB returned last time (per
+/// `serverKnowledgeCache`) is no longer returned, e.g. because it was deleted or merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingCategoryBehavior {
+    #[default]
+    Ignore,
+    Warn,
+    Error,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub budget_name: String,
+    #[serde(default)]
+    pub budget_id: Option<String>,
+    #[serde(serialize_with = "serialize_redacted")]
     pub personal_access_token: String,
     pub category_group_watch_list: IndexMap<String, String>,
     #[serde(default)]
@@ -39,10 +92,619 @@ pub struct Config {
     pub show_all_rows: bool,
     #[serde(default)]
     pub output_format: OutputFormat,
+    #[serde(default)]
+    pub preserve_group_order: bool,
+    #[serde(default)]
+    pub spending_alerts: bool,
+    #[serde(default = "default_alert_threshold_percent")]
+    pub alert_threshold_percent: f64,
+    #[serde(default)]
+    pub show_mom_trend: bool,
+    #[serde(default = "default_rate_limit_pause")]
+    pub rate_limit_pause: bool,
+    #[serde(default)]
+    pub payee_mapping: IndexMap<String, String>,
+    #[serde(default)]
+    pub show_income_breakdown: bool,
+    #[serde(default = "default_exclude_system_groups")]
+    pub exclude_system_groups: bool,
+    #[serde(default)]
+    pub csv_delimiter: Option<char>,
+    #[serde(default)]
+    pub show_sparklines: bool,
+    #[serde(default)]
+    pub default_months: Option<u32>,
+    #[serde(default = "default_exclude_transfers")]
+    pub exclude_transfers: bool,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub budget_overrides: HashMap<String, f64>,
+    #[serde(default)]
+    pub html_extra_css: Option<String>,
+    #[serde(default)]
+    pub print_mode: bool,
+    #[serde(default)]
+    pub transaction_detail_pages: bool,
+    #[serde(default)]
+    pub max_rows_per_group: Option<usize>,
+    #[serde(default)]
+    pub strict_mode: bool,
+    #[serde(default)]
+    pub check_threshold_percent: Option<f64>,
+    #[serde(default)]
+    pub currency_symbol: Option<String>,
+    #[serde(default)]
+    pub server_knowledge_cache: Option<PathBuf>,
+    #[serde(default)]
+    pub on_missing_category: MissingCategoryBehavior,
+}
+
+fn default_alert_threshold_percent() -> f64 {
+    100.0
+}
+
+fn default_rate_limit_pause() -> bool {
+    true
+}
+
+fn default_exclude_system_groups() -> bool {
+    true
+}
+
+fn default_exclude_transfers() -> bool {
+    true
+}
+
+impl Config {
+    /// A builder starting from the same defaults `load_config` applies to missing keys,
+    /// with an empty watch list.
+    pub fn builder(budget_name: &str, personal_access_token: &str) -> ConfigBuilder {
+        ConfigBuilder {
+            config: Config {
+                budget_name: budget_name.to_string(),
+                budget_id: None,
+                personal_access_token: personal_access_token.to_string(),
+                category_group_watch_list: IndexMap::new(),
+                resolution_date: None,
+                show_all_rows: false,
+                output_format: OutputFormat::default(),
+                preserve_group_order: false,
+                spending_alerts: false,
+                alert_threshold_percent: default_alert_threshold_percent(),
+                show_mom_trend: false,
+                rate_limit_pause: default_rate_limit_pause(),
+                payee_mapping: IndexMap::new(),
+                show_income_breakdown: false,
+                exclude_system_groups: default_exclude_system_groups(),
+                csv_delimiter: None,
+                show_sparklines: false,
+                default_months: None,
+                exclude_transfers: default_exclude_transfers(),
+                timezone: None,
+                budget_overrides: HashMap::new(),
+                html_extra_css: None,
+                print_mode: false,
+                transaction_detail_pages: false,
+                max_rows_per_group: None,
+                strict_mode: false,
+                check_threshold_percent: None,
+                currency_symbol: None,
+                server_knowledge_cache: None,
+                on_missing_category: MissingCategoryBehavior::default(),
+            },
+        }
+    }
+
+    /// The calendar date at `now` in the configured IANA `timezone` (e.g.
+    /// `"America/New_York"`), or in the system time zone when none is set.
+    pub fn date_at(&self, now: DateTime<Utc>) -> Result<NaiveDate> {
+        match &self.timezone {
+            Some(name) => {
+                let tz: Tz = name
+                    .parse()
+                    .map_err(|err| anyhow::anyhow!("{err}"))
+                    .with_context(|| format!("invalid timezone {name:?}"))?;
+                Ok(now.with_timezone(&tz).date_naive())
+            }
+            None => Ok(now.with_timezone(&chrono::Local).date_naive()),
+        }
+    }
+
+    /// The report's "today": `resolution_date` when set, otherwise the current date in
+    /// the configured `timezone`.
+    pub fn today(&self) -> Result<NaiveDate> {
+        self.resolution_date
+            .map_or_else(|| self.date_at(Utc::now()), Ok)
+    }
+
+    /// The all-defaults config for `budget_name`; shorthand for `builder(..).build()`.
+    pub fn for_budget(budget_name: &str, personal_access_token: &str) -> Config {
+        Self::builder(budget_name, personal_access_token).build()
+    }
+
+    /// `overlay`, except that its watch list is merged onto `base`'s: overlay colors
+    /// replace those of groups already in `base`, and new groups are appended.
+    pub fn merge(base: Config, overlay: Config) -> Config {
+        let mut category_group_watch_list = base.category_group_watch_list;
+        category_group_watch_list.extend(overlay.category_group_watch_list);
+        Config {
+            category_group_watch_list,
+            ..overlay
+        }
+    }
+}
+
+/// Shown in place of secrets (the access token and a Slack webhook URL) wherever a
+/// `Config` is serialized or printed.
+pub const REDACTED: &str = "***";
+
+/// One `key: value` line per setting, keyed as in the config JSON (in alphabetical
+/// order), with secrets as [`REDACTED`] and the watch list as `"Group" (#rrggbb)`
+/// entries.
+impl std::fmt::Display for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let serde_json::Value::Object(settings) =
+            serde_json::to_value(self).map_err(|_| std::fmt::Error)?
+        else {
+            return Err(std::fmt::Error);
+        };
+        let mut settings: Vec<_> = settings.into_iter().collect();
+        settings.sort_by(|(left, _), (right, _)| left.cmp(right));
+        settings.iter().try_for_each(|(key, value)| {
+            let shown = match (key.as_str(), value) {
+                ("categoryGroupWatchList", _) => self
+                    .category_group_watch_list
+                    .iter()
+                    .map(|(group, color)| format!("{group:?} ({color})"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                (_, serde_json::Value::String(text)) => text.clone(),
+                (_, serde_json::Value::Null) => "(unset)".to_string(),
+                (_, other) => other.to_string(),
+            };
+            writeln!(f, "{key}: {shown}")
+        })
+    }
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // destructured so a new field can't be left out of the output
+        let Config {
+            budget_name,
+            budget_id,
+            personal_access_token: _,
+            category_group_watch_list,
+            resolution_date,
+            show_all_rows,
+            output_format,
+            preserve_group_order,
+            spending_alerts,
+            alert_threshold_percent,
+            show_mom_trend,
+            rate_limit_pause,
+            payee_mapping,
+            show_income_breakdown,
+            exclude_system_groups,
+            csv_delimiter,
+            show_sparklines,
+            default_months,
+            exclude_transfers,
+            timezone,
+            budget_overrides,
+            html_extra_css,
+            print_mode,
+            transaction_detail_pages,
+            max_rows_per_group,
+            strict_mode,
+            check_threshold_percent,
+            currency_symbol,
+            server_knowledge_cache,
+            on_missing_category,
+        } = self;
+        f.debug_struct("Config")
+            .field("budget_name", budget_name)
+            .field("budget_id", budget_id)
+            .field("personal_access_token", &REDACTED)
+            .field("category_group_watch_list", category_group_watch_list)
+            .field("resolution_date", resolution_date)
+            .field("show_all_rows", show_all_rows)
+            .field("output_format", &output_format.redacted())
+            .field("preserve_group_order", preserve_group_order)
+            .field("spending_alerts", spending_alerts)
+            .field("alert_threshold_percent", alert_threshold_percent)
+            .field("show_mom_trend", show_mom_trend)
+            .field("rate_limit_pause", rate_limit_pause)
+            .field("payee_mapping", payee_mapping)
+            .field("show_income_breakdown", show_income_breakdown)
+            .field("exclude_system_groups", exclude_system_groups)
+            .field("csv_delimiter", csv_delimiter)
+            .field("show_sparklines", show_sparklines)
+            .field("default_months", default_months)
+            .field("exclude_transfers", exclude_transfers)
+            .field("timezone", timezone)
+            .field("budget_overrides", budget_overrides)
+            .field("html_extra_css", html_extra_css)
+            .field("print_mode", print_mode)
+            .field("transaction_detail_pages", transaction_detail_pages)
+            .field("max_rows_per_group", max_rows_per_group)
+            .field("strict_mode", strict_mode)
+            .field("check_threshold_percent", check_threshold_percent)
+            .field("currency_symbol", currency_symbol)
+            .field("server_knowledge_cache", server_knowledge_cache)
+            .field("on_missing_category", on_missing_category)
+            .finish()
+    }
+}
+
+/// Chainable setters over [`Config`], created by [`Config::builder`].
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    pub fn category_group_watch_list(mut self, watch_list: IndexMap<String, String>) -> Self {
+        self.config.category_group_watch_list = watch_list;
+        self
+    }
+
+    /// Adds one group to the watch list, keeping insertion order.
+    pub fn watch_group(mut self, group_name: &str, color: &str) -> Self {
+        self.config
+            .category_group_watch_list
+            .insert(group_name.to_string(), color.to_string());
+        self
+    }
+
+    /// Pins the budget by ID; it must still be the budget named `budget_name`.
+    pub fn budget_id(mut self, budget_id: &str) -> Self {
+        self.config.budget_id = Some(budget_id.to_string());
+        self
+    }
+
+    pub fn resolution_date(mut self, resolution_date: NaiveDate) -> Self {
+        self.config.resolution_date = Some(resolution_date);
+        self
+    }
+
+    pub fn show_all_rows(mut self, show_all_rows: bool) -> Self {
+        self.config.show_all_rows = show_all_rows;
+        self
+    }
+
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.config.output_format = output_format;
+        self
+    }
+
+    pub fn preserve_group_order(mut self, preserve_group_order: bool) -> Self {
+        self.config.preserve_group_order = preserve_group_order;
+        self
+    }
+
+    pub fn spending_alerts(mut self, spending_alerts: bool) -> Self {
+        self.config.spending_alerts = spending_alerts;
+        self
+    }
+
+    pub fn alert_threshold_percent(mut self, alert_threshold_percent: f64) -> Self {
+        self.config.alert_threshold_percent = alert_threshold_percent;
+        self
+    }
+
+    pub fn show_mom_trend(mut self, show_mom_trend: bool) -> Self {
+        self.config.show_mom_trend = show_mom_trend;
+        self
+    }
+
+    pub fn rate_limit_pause(mut self, rate_limit_pause: bool) -> Self {
+        self.config.rate_limit_pause = rate_limit_pause;
+        self
+    }
+
+    pub fn payee_mapping(mut self, payee_mapping: IndexMap<String, String>) -> Self {
+        self.config.payee_mapping = payee_mapping;
+        self
+    }
+
+    pub fn show_income_breakdown(mut self, show_income_breakdown: bool) -> Self {
+        self.config.show_income_breakdown = show_income_breakdown;
+        self
+    }
+
+    pub fn exclude_system_groups(mut self, exclude_system_groups: bool) -> Self {
+        self.config.exclude_system_groups = exclude_system_groups;
+        self
+    }
+
+    pub fn csv_delimiter(mut self, csv_delimiter: char) -> Self {
+        self.config.csv_delimiter = Some(csv_delimiter);
+        self
+    }
+
+    pub fn show_sparklines(mut self, show_sparklines: bool) -> Self {
+        self.config.show_sparklines = show_sparklines;
+        self
+    }
+
+    pub fn default_months(mut self, default_months: u32) -> Self {
+        self.config.default_months = Some(default_months);
+        self
+    }
+
+    pub fn exclude_transfers(mut self, exclude_transfers: bool) -> Self {
+        self.config.exclude_transfers = exclude_transfers;
+        self
+    }
+
+    pub fn timezone(mut self, timezone: &str) -> Self {
+        self.config.timezone = Some(timezone.to_string());
+        self
+    }
+
+    pub fn html_extra_css(mut self, html_extra_css: &str) -> Self {
+        self.config.html_extra_css = Some(html_extra_css.to_string());
+        self
+    }
+
+    pub fn print_mode(mut self, print_mode: bool) -> Self {
+        self.config.print_mode = print_mode;
+        self
+    }
+
+    pub fn transaction_detail_pages(mut self, transaction_detail_pages: bool) -> Self {
+        self.config.transaction_detail_pages = transaction_detail_pages;
+        self
+    }
+
+    pub fn max_rows_per_group(mut self, max_rows_per_group: usize) -> Self {
+        self.config.max_rows_per_group = Some(max_rows_per_group);
+        self
+    }
+
+    pub fn strict_mode(mut self, strict_mode: bool) -> Self {
+        self.config.strict_mode = strict_mode;
+        self
+    }
+
+    pub fn check_threshold_percent(mut self, check_threshold_percent: f64) -> Self {
+        self.config.check_threshold_percent = Some(check_threshold_percent);
+        self
+    }
+
+    pub fn currency_symbol(mut self, currency_symbol: &str) -> Self {
+        self.config.currency_symbol = Some(currency_symbol.to_string());
+        self
+    }
+
+    pub fn server_knowledge_cache(mut self, server_knowledge_cache: impl Into<PathBuf>) -> Self {
+        self.config.server_knowledge_cache = Some(server_knowledge_cache.into());
+        self
+    }
+
+    pub fn on_missing_category(mut self, on_missing_category: MissingCategoryBehavior) -> Self {
+        self.config.on_missing_category = on_missing_category;
+        self
+    }
+
+    /// Overrides one category's budgeted amount (see `report::apply_budget_override`).
+    pub fn budget_override(mut self, category_name: &str, budgeted: f64) -> Self {
+        self.config
+            .budget_overrides
+            .insert(category_name.to_string(), budgeted);
+        self
+    }
+
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+fn serialize_redacted<S: Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
+/// Pretty JSON of the effective config, with secrets redacted.
+pub fn dump_config(config: &Config) -> Result<String> {
+    serde_json::to_string_pretty(config).context("serializing config")
+}
+
+/// A `--set key=value` override. `key` is a `.`-separated path whose segments may be
+/// written in snake_case (`budget_name`) or as they appear in the JSON (`budgetName`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOverride {
+    pub key: String,
+    pub value: String,
+}
+
+impl std::str::FromStr for ConfigOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("expected key=value, got {s:?}"))?;
+        if key.is_empty() {
+            anyhow::bail!("empty key in override {s:?}");
+        }
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+fn snake_to_camel(segment: &str) -> String {
+    segment
+        .split('_')
+        .enumerate()
+        .map(|(i, part)| match (i, part.chars().next()) {
+            (0, _) | (_, None) => part.to_string(),
+            (_, Some(first)) => first.to_uppercase().chain(part.chars().skip(1)).collect(),
+        })
+        .collect()
+}
+
+/// The key in `object` that `segment` names, either verbatim or after camelCasing.
+fn resolve_key(
+    object: &serde_json::Map<String, serde_json::Value>,
+    segment: &str,
+) -> Option<String> {
+    [segment.to_string(), snake_to_camel(segment)]
+        .into_iter()
+        .find(|key| object.contains_key(key))
+}
+
+/// Patches `config_override` into the raw config JSON. Only the top-level key is checked
+/// against `known` (the fully-defaulted config); nested segments are camelCased when that
+/// matches an existing key, created otherwise, and validated by the final deserialize.
+fn apply_override(
+    raw: &mut serde_json::Value,
+    known: &serde_json::Value,
+    config_override: &ConfigOverride,
+) -> Result<()> {
+    let key = &config_override.key;
+    let segments: Vec<&str> = key.split('.').collect();
+    let (first, rest) = segments
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("empty override key"))?;
+    let known_fields = known
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("config is not a JSON object"))?;
+    let first = resolve_key(known_fields, first)
+        .ok_or_else(|| anyhow::anyhow!("unknown config key {key:?}"))?;
+
+    let mut known_leaf = Some(known);
+    let mut target = raw;
+    for (depth, segment) in std::iter::once(first.as_str())
+        .chain(rest.iter().copied())
+        .enumerate()
+    {
+        if !target.is_object() {
+            *target = serde_json::Value::Object(serde_json::Map::new());
+        }
+        let object = target
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("config is not a JSON object"))?;
+        let field = match depth {
+            0 => segment.to_string(),
+            _ => resolve_key(object, segment).unwrap_or_else(|| segment.to_string()),
+        };
+        known_leaf = known_leaf.and_then(|leaf| leaf.get(&field));
+        target = object.entry(field).or_insert(serde_json::Value::Null);
+    }
+
+    // string fields take the value verbatim, so `--set budget_name=2024` stays a string
+    *target = match known_leaf {
+        Some(serde_json::Value::String(_)) => {
+            serde_json::Value::String(config_override.value.clone())
+        }
+        _ => serde_json::from_str(&config_override.value)
+            .unwrap_or_else(|_| serde_json::Value::String(config_override.value.clone())),
+    };
+    Ok(())
 }
 
 pub fn load_config(path: &Path) -> Result<Config> {
+    load_config_with_overrides(path, &[])
+}
+
+fn read_config_json(path: &Path) -> Result<serde_json::Value> {
     let contents =
         std::fs::read_to_string(path).with_context(|| format!("reading config from {path:?}"))?;
-    serde_json::from_str(&contents).with_context(|| "parsing config JSON")
+    serde_json::from_str(&contents).with_context(|| format!("parsing config JSON from {path:?}"))
+}
+
+/// Lays the keys of the `overlay` config JSON over `base`, the JSON counterpart of
+/// [`Config::merge`]: a key in both takes the overlay's value, except
+/// `categoryGroupWatchList`, whose groups are merged.
+fn merge_config_json(base: &mut serde_json::Value, overlay: serde_json::Value) -> Result<()> {
+    let base = base
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("base config is not a JSON object"))?;
+    let serde_json::Value::Object(overlay) = overlay else {
+        anyhow::bail!("overlay config is not a JSON object");
+    };
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (
+                Some(serde_json::Value::Object(watch_list)),
+                serde_json::Value::Object(overlay_watch_list),
+            ) if key == "categoryGroupWatchList" => watch_list.extend(overlay_watch_list),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// [`load_config`], with each of `overrides` merged into the JSON before it is
+/// deserialized.
+pub fn load_config_with_overrides(path: &Path, overrides: &[ConfigOverride]) -> Result<Config> {
+    load_config_with_overlay(path, None, overrides)
+}
+
+/// [`load_config_with_overrides`], with the keys of the `overlay` config file (when
+/// there is one) laid over `path`'s before the overrides: set keys replace the base
+/// config's, and watch-list groups are merged as in [`Config::merge`].
+pub fn load_config_with_overlay(
+    path: &Path,
+    overlay: Option<&Path>,
+    overrides: &[ConfigOverride],
+) -> Result<Config> {
+    let mut raw = read_config_json(path)?;
+    if let Some(overlay) = overlay {
+        merge_config_json(&mut raw, read_config_json(overlay)?)
+            .with_context(|| format!("merging overlay config {overlay:?}"))?;
+    }
+    if !overrides.is_empty() {
+        let defaults: Config =
+            serde_json::from_value(raw.clone()).with_context(|| "parsing config JSON")?;
+        let known = serde_json::to_value(&defaults).context("serializing config")?;
+        for config_override in overrides {
+            apply_override(&mut raw, &known, config_override)
+                .with_context(|| format!("applying --set {}", config_override.key))?;
+        }
+    }
+    let mut config: Config = serde_json::from_value(raw).with_context(|| "parsing config JSON")?;
+    if config.category_group_watch_list.is_empty() {
+        config.category_group_watch_list = watch_list_from_env()?;
+    }
+    Ok(config)
+}
+
+fn is_hex_color(value: &str) -> bool {
+    (value.len() == 7 || value.len() == 4)
+        && value.starts_with('#')
+        && value[1..].chars().all(|ch| ch.is_ascii_hexdigit())
+}
+
+/// Reads `CRUSTYNAB_GROUP_<n>_NAME` / `CRUSTYNAB_GROUP_<n>_COLOR` pairs for n = 0, 1, …,
+/// stopping at the first missing name.
+pub fn watch_list_from_env() -> Result<IndexMap<String, String>> {
+    watch_list_from_lookup(|key| std::env::var(key).ok())
+}
+
+pub fn watch_list_from_lookup(
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<IndexMap<String, String>> {
+    let mut watch_list = IndexMap::new();
+    for n in 0.. {
+        let Some(name) = lookup(&format!("CRUSTYNAB_GROUP_{n}_NAME")) else {
+            break;
+        };
+        let color_key = format!("CRUSTYNAB_GROUP_{n}_COLOR");
+        let color = lookup(&color_key)
+            .ok_or_else(|| anyhow::anyhow!("{color_key} is not set for category group {name}"))?;
+        if !is_hex_color(&color) {
+            anyhow::bail!(
+                "{color_key} for category group {name} is not a #rrggbb or #rgb color: {color}"
+            );
+        }
+        watch_list.insert(name, color);
+    }
+    Ok(watch_list)
 }
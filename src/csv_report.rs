@@ -0,0 +1,95 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+
+use crate::config;
+
+/// How CSV output is written; the default is polars' comma-separated output with a header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub include_header: bool,
+    pub date_format: Option<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            include_header: true,
+            date_format: None,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Options for `cfg`'s `csvDelimiter`, which must be a single ASCII character.
+    pub fn from_config(cfg: &config::Config) -> Result<Self> {
+        let delimiter = match cfg.csv_delimiter {
+            None => b',',
+            Some(ch) => u8::try_from(ch).ok().filter(u8::is_ascii).ok_or_else(|| {
+                anyhow::anyhow!("csvDelimiter must be an ASCII character, got {ch:?}")
+            })?,
+        };
+        Ok(Self {
+            delimiter,
+            ..Self::default()
+        })
+    }
+}
+
+/// Renders DataFrames as CSV text, either with explicit [`CsvOptions`] or with the
+/// options it was created with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvRenderer {
+    pub options: CsvOptions,
+}
+
+impl CsvRenderer {
+    pub fn new(options: CsvOptions) -> Self {
+        Self { options }
+    }
+
+    /// `df` as CSV with the default options.
+    pub fn render(df: &mut DataFrame) -> Result<String> {
+        Self::render_with_options(df, &CsvOptions::default())
+    }
+
+    pub fn render_with_options(df: &mut DataFrame, opts: &CsvOptions) -> Result<String> {
+        let mut buf = Vec::new();
+        CsvWriter::new(&mut buf)
+            .with_separator(opts.delimiter)
+            .include_header(opts.include_header)
+            .with_date_format(opts.date_format.clone())
+            .finish(df)
+            .context("writing CSV")?;
+        String::from_utf8(buf).context("CSV not valid UTF-8")
+    }
+
+    /// `df` as CSV with this renderer's options.
+    pub fn render_frame(&self, df: &mut DataFrame) -> Result<String> {
+        Self::render_with_options(df, &self.options)
+    }
+
+    /// Appends `df` to the CSV log at `path` as a section opened by a `# {label}` line.
+    /// A missing or empty file gets the header row (when the options include one); later
+    /// sections are data rows only, so the whole file parses as one table once the `#`
+    /// lines are skipped.
+    pub fn append_section(&self, path: &Path, df: &mut DataFrame, label: &str) -> Result<()> {
+        let has_data = std::fs::metadata(path).is_ok_and(|metadata| metadata.len() > 0);
+        let opts = CsvOptions {
+            include_header: self.options.include_header && !has_data,
+            ..self.options.clone()
+        };
+        let csv = Self::render_with_options(df, &opts)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("opening {path:?} for appending"))?;
+        write!(file, "# {}\n{csv}", label.replace(['\r', '\n'], " "))
+            .with_context(|| format!("appending to {path:?}"))
+    }
+}
@@ -0,0 +1,33 @@
+//! How a budget writes amounts, shared by the YNAB client that reads a budget's
+//! currency settings and the reports that print them.
+
+pub const CURRENCY: &str = "£";
+
+/// How amounts are written: the symbol before the number, the separator between groups
+/// of thousands, the one before the decimals and how many decimal places to show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrencyFormat {
+    pub symbol: String,
+    pub group_separator: String,
+    pub decimal_separator: String,
+    pub precision: u8,
+}
+
+impl CurrencyFormat {
+    /// Pounds sterling, `£1,234.56`: the format every report used before it was
+    /// configurable.
+    pub fn gbp() -> Self {
+        Self {
+            symbol: CURRENCY.to_string(),
+            group_separator: ",".to_string(),
+            decimal_separator: ".".to_string(),
+            precision: 2,
+        }
+    }
+}
+
+impl Default for CurrencyFormat {
+    fn default() -> Self {
+        Self::gbp()
+    }
+}
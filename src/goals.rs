@@ -0,0 +1,125 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::calendar_weeks::Month;
+use crate::ynab::Category;
+
+/// How far year-to-date spending may stray from the expected share of an annual goal,
+/// as a fraction of that share, and still count as "on track".
+pub const GOAL_PROGRESS_TOLERANCE: f64 = 0.1;
+
+/// Maps YNAB's `goal_cadence` code to a display cadence.
+///
+/// YNAB codes 0 (none), 1 (monthly), 2 (weekly) and 13 (yearly) repeat every
+/// `goal_cadence_frequency` periods; 3..=12 repeat every 2..=11 months and 14 every
+/// 2 years. Categories without a goal target are budgeted annually.
+#[tracing::instrument(level = "debug")]
+pub fn infer_goal_cadence(goal_cadence: Option<i32>, goal_target: Option<i64>) -> &'static str {
+    match (goal_target, goal_cadence) {
+        (None, _) => "annual",
+        (Some(_), Some(1)) => "monthly",
+        (Some(_), Some(2)) => "weekly",
+        (Some(_), Some(4)) => "quarterly",
+        (Some(_), None | Some(0) | Some(13) | Some(14)) => "annual",
+        (Some(_), Some(_)) => "unknown",
+    }
+}
+
+/// Goal arithmetic shared by the report tables and the visual report: what a category
+/// plans to spend per year and per month, and whether spending keeps pace with its goal.
+pub struct GoalTracker;
+
+impl GoalTracker {
+    /// A year's planned spending from a month's `budgeted`: annual categories budget the
+    /// whole year at once, every other cadence budgets a month at a time.
+    pub fn planned_annual_amount(budgeted: f64, is_annual: bool) -> f64 {
+        if is_annual { budgeted } else { budgeted * 12.0 }
+    }
+
+    /// The monthly share of a year's planned spending.
+    pub fn per_month_amount(planned_annual: f64) -> f64 {
+        planned_annual / 12.0
+    }
+
+    /// How many months a goal target of `cadence` (an [`infer_goal_cadence`] label)
+    /// covers: 12 for annual goals, 3 for quarterly ones and 1 for every other cadence.
+    pub fn months_covered(cadence: &str) -> f64 {
+        match cadence {
+            "annual" => 12.0,
+            "quarterly" => 3.0,
+            _ => 1.0,
+        }
+    }
+
+    /// The part of a goal `target` of `cadence` committed to a single month.
+    pub fn monthly_target(target: f64, cadence: &str) -> f64 {
+        target / Self::months_covered(cadence)
+    }
+
+    /// [`GoalTracker::planned_annual_amount`] of `category`, with its cadence from
+    /// [`infer_goal_cadence`].
+    pub fn planned_annual(category: &Category) -> f64 {
+        let is_annual = infer_goal_cadence(category.goal_cadence, category.goal_target) == "annual";
+        Self::planned_annual_amount(category.budgeted as f64 / 1000.0, is_annual)
+    }
+
+    /// [`GoalTracker::per_month_amount`] of `category`'s
+    /// [`GoalTracker::planned_annual`] spending.
+    pub fn planned_per_month(category: &Category) -> f64 {
+        Self::per_month_amount(Self::planned_annual(category))
+    }
+
+    /// `category`'s goal target when it is an annual goal (and positive), the goals
+    /// `report::build_goal_progress_table` tracks.
+    pub fn annual_goal(category: &Category) -> Option<f64> {
+        category
+            .goal_target
+            .filter(|&target| target > 0)
+            .filter(|_| infer_goal_cadence(category.goal_cadence, category.goal_target) == "annual")
+            .map(|target| target as f64 / 1000.0)
+    }
+
+    /// The share of `annual_goal` due by the end of `month` (1–12).
+    pub fn expected_ytd(annual_goal: f64, month: u32) -> f64 {
+        annual_goal * f64::from(month) / 12.0
+    }
+
+    /// "on track" when year-to-date outflow `actual_ytd` (positive) is within
+    /// [`GOAL_PROGRESS_TOLERANCE`] of `expected_ytd`; otherwise "behind" or "ahead".
+    pub fn goal_status(actual_ytd: f64, expected_ytd: f64) -> &'static str {
+        let margin = expected_ytd * GOAL_PROGRESS_TOLERANCE;
+        if actual_ytd < expected_ytd - margin {
+            "behind"
+        } else if actual_ytd > expected_ytd + margin {
+            "ahead"
+        } else {
+            "on track"
+        }
+    }
+
+    /// How much of `month` of `year` has passed by the end of `as_of`, 0–100: 0 before
+    /// the month (or for an invalid month), 100 after it.
+    pub fn percent_of_month_elapsed(month: u32, year: i32, as_of: NaiveDate) -> f64 {
+        let Ok(month) = Month::try_from((year, month)) else {
+            return 0.0;
+        };
+        if as_of < month.first_day() {
+            0.0
+        } else if as_of >= month.last_day() {
+            100.0
+        } else {
+            f64::from(as_of.day()) / f64::from(month.last_day().day()) * 100.0
+        }
+    }
+
+    /// Whether year-to-date outflow (`ytd_spent`, signed like report `spent`, so
+    /// outflows are negative) has [`GoalTracker::goal_status`] "on track" against
+    /// `category`'s [`GoalTracker::annual_goal`] for the months up to and including
+    /// `as_of`'s, as in the goal progress table. Categories without an annual goal have
+    /// no schedule to fall behind and are always on track.
+    pub fn is_on_track(category: &Category, ytd_spent: f64, as_of: NaiveDate) -> bool {
+        Self::annual_goal(category).is_none_or(|annual_goal| {
+            Self::goal_status(-ytd_spent, Self::expected_ytd(annual_goal, as_of.month()))
+                == "on track"
+        })
+    }
+}
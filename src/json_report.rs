@@ -0,0 +1,52 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde_json::{Map, Value};
+
+/// Key naming the table each NDJSON row came from, e.g. `"report"` or `"totals"`.
+pub const TABLE_KEY: &str = "_table";
+
+/// Writes each row of `df` to `writer` as one JSON object per line, keyed by column
+/// name after a [`TABLE_KEY`] of `table`, and returns the number of rows written.
+/// Dates are ISO strings; nulls and non-finite floats are `null`.
+pub fn write_ndjson_rows(df: &DataFrame, table: &str, writer: &mut dyn Write) -> Result<usize> {
+    let columns = df.get_columns();
+    for row_idx in 0..df.height() {
+        let mut object = Map::new();
+        object.insert(TABLE_KEY.to_string(), Value::String(table.to_string()));
+        for column in columns {
+            let value = column
+                .get(row_idx)
+                .with_context(|| format!("reading {} row {row_idx}", column.name()))?;
+            object.insert(column.name().to_string(), json_value(&value));
+        }
+        let line = serde_json::to_string(&object).context("serializing NDJSON row")?;
+        writeln!(writer, "{line}")?;
+    }
+    Ok(df.height())
+}
+
+fn json_value(value: &AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(flag) => Value::Bool(*flag),
+        AnyValue::String(text) => Value::String(text.to_string()),
+        AnyValue::StringOwned(text) => Value::String(text.to_string()),
+        AnyValue::Int8(number) => Value::from(*number),
+        AnyValue::Int16(number) => Value::from(*number),
+        AnyValue::Int32(number) => Value::from(*number),
+        AnyValue::Int64(number) => Value::from(*number),
+        AnyValue::UInt8(number) => Value::from(*number),
+        AnyValue::UInt16(number) => Value::from(*number),
+        AnyValue::UInt32(number) => Value::from(*number),
+        AnyValue::UInt64(number) => Value::from(*number),
+        AnyValue::Float32(number) => {
+            serde_json::Number::from_f64(f64::from(*number)).map_or(Value::Null, Value::Number)
+        }
+        AnyValue::Float64(number) => {
+            serde_json::Number::from_f64(*number).map_or(Value::Null, Value::Number)
+        }
+        other => Value::String(other.to_string()),
+    }
+}
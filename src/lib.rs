@@ -1,5 +1,24 @@
+pub mod alerts;
+pub mod app;
+pub mod ascii_report;
+pub mod atomic_file;
 pub mod calendar_weeks;
+pub mod chart_report;
 pub mod config;
+pub mod csv_report;
+pub mod currency;
+pub mod goals;
+pub mod json_report;
+pub mod logging;
+pub mod names;
 pub mod report;
+pub mod report_metadata;
+pub mod server_knowledge_cache;
+pub mod slack_report;
+pub mod sql_report;
+pub mod summary_report;
 pub mod visual_report;
 pub mod ynab;
+pub mod zip_archive;
+
+pub use csv_report::CsvRenderer;
@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use tracing::Subscriber;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// A `tracing-subscriber` fmt subscriber writing one line per event that `filter` (an
+/// [`EnvFilter`] spec in `RUST_LOG` syntax, e.g. `debug`,
+/// `warn,crustynab::report=debug` or `[build_report_table]=debug`) enables:
+/// `LEVEL span{fields}:span{fields}: target: message fields`, without timestamps or
+/// colors.
+pub fn subscriber<W>(filter: &str, make_writer: W) -> Result<impl Subscriber + Send + Sync>
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    // the parse error's source repeats its own message, so only its message is kept
+    let filter = EnvFilter::try_new(filter)
+        .map_err(|err| anyhow::anyhow!("invalid log filter {filter:?}: {err}"))?;
+    Ok(tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(make_writer)
+        .with_ansi(false)
+        .without_time()
+        .finish())
+}
+
+/// Installs a stderr [`subscriber`] as the global default when `RUST_LOG` is set and
+/// non-empty; otherwise tracing stays disabled.
+pub fn init_from_env() -> Result<()> {
+    let Some(spec) = std::env::var("RUST_LOG")
+        .ok()
+        .filter(|spec| !spec.trim().is_empty())
+    else {
+        return Ok(());
+    };
+    tracing::subscriber::set_global_default(
+        subscriber(&spec, std::io::stderr).context("parsing RUST_LOG")?,
+    )
+    .context("installing tracing subscriber")
+}
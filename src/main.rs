@@ -1,16 +1,23 @@
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
-use chrono::Datelike;
-use clap::Parser;
-use polars::prelude::*;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
 
-use crustynab::calendar_weeks::month_week_for_date;
-use crustynab::config::{self, OutputFormat, SimpleOutputFormat};
+use crustynab::app::{RunOptions, run};
+use crustynab::calendar_weeks::{
+    Month, check_partition_year, month_weeks, partition_year_into_month_weeks,
+};
+use crustynab::config::{self, ConfigOverride};
+use crustynab::csv_report::CsvRenderer;
+use crustynab::logging;
 use crustynab::report;
-use crustynab::visual_report::build_visual_report_html;
-use crustynab::ynab::{HttpYnabClient, YnabApi};
+use crustynab::visual_report::{ColorScheme, scheme_group_colors};
+use crustynab::ynab::{HttpYnabClient, OfflineYnabClient, YnabApi};
 
 #[derive(Parser, Debug)]
 #[clap(author = "Simon Zeng", version, about = "YNAB budget reporting tool")]
@@ -18,163 +25,325 @@ struct Args {
     /// Path to config.json
     #[arg(short, long, default_value = "config.json")]
     config: PathBuf,
-}
 
-pub fn run(api: &dyn YnabApi, cfg: &config::Config) -> Result<()> {
-    let budgets = api.get_budgets()?;
-    let budget_id = report::get_budget_id(&budgets, &cfg.budget_name)
-        .ok_or_else(|| anyhow::anyhow!("no budget found with name {}", cfg.budget_name))?;
-
-    let category_groups = api.get_category_groups(&budget_id)?;
-    let missing =
-        report::get_missing_category_groups(&category_groups, &cfg.category_group_watch_list);
-    if !missing.is_empty() {
-        let mut names: Vec<&str> = missing.iter().map(String::as_str).collect();
-        names.sort();
-        eprintln!(
-            "Warning: categoryGroupWatchList includes unknown category groups: {}",
-            names.join(", ")
-        );
-    }
+    /// Config file whose keys replace the base config's, e.g. per-environment tokens
+    /// and output paths; watch-list groups are merged
+    #[arg(long, value_name = "PATH")]
+    overlay: Option<PathBuf>,
 
-    let categories_to_watch =
-        report::get_categories_to_watch(&category_groups, &cfg.category_group_watch_list);
+    /// Only include transactions whose payee matches this regex
+    #[arg(long, value_name = "REGEX")]
+    payee_filter: Option<String>,
 
-    let resolution_date = cfg
-        .resolution_date
-        .unwrap_or_else(|| chrono::Local::now().date_naive());
-    let report_week = month_week_for_date(resolution_date)?;
-    let report_start = report_week.week_start;
-    let report_end = report_week.week_end;
+    /// Disable ANSI colours in output. Setting the NO_COLOR environment variable to any
+    /// non-empty value has the same effect
+    #[arg(long)]
+    no_color: bool,
 
-    let month_categories: Vec<_> = categories_to_watch
-        .iter()
-        .map(|cat| api.get_month_category(&budget_id, report_start, &cat.id))
-        .collect::<Result<Vec<_>>>()
-        .context("fetching month categories")?;
+    /// Print the effective config as JSON (token redacted) and exit without reporting
+    #[arg(long)]
+    dump_config: bool,
 
-    let categories_budgeted = report::categories_to_polars(&month_categories)?;
+    /// Re-run the report every SECONDS seconds, skipping runs while the budget is
+    /// unchanged. Stop with Ctrl-C
+    #[arg(long, value_name = "SECONDS")]
+    watch: Option<u64>,
 
-    let transactions = api.get_transactions(&budget_id, report_start)?;
-    let transactions_frame = report::transactions_to_polars(&transactions)?;
-    let transactions_frame =
-        report::relevant_transactions(transactions_frame, report_start, report_end);
+    /// With the csv_print output format, also print the week's transactions sorted by
+    /// date with a cumulative running_total column
+    #[arg(long)]
+    running_total: bool,
 
-    let category_names: HashSet<String> = month_categories.iter().map(|c| c.name.clone()).collect();
+    /// With the csv_print and csv_output formats, write the report table wide: one
+    /// row per category and one spent column per category group
+    #[arg(long)]
+    wide: bool,
 
-    let report_table =
-        report::build_report_table(categories_budgeted, transactions_frame, &category_names)?;
+    /// With the polars_print and csv_print formats, also print each category's spending
+    /// against the previous week's, with the change and whether it improved
+    #[arg(long)]
+    compare_last_week: bool,
 
-    let report_table_full = report_table.clone();
-    let report_table_display = if cfg.show_all_rows {
-        report_table
-    } else {
-        report_table.filter(col("spent").neq(lit(0.0)))
-    };
+    /// With the polars_print and csv_print formats, also print year-to-date spending
+    /// against each annual goal's expected share and whether it is on track
+    #[arg(long)]
+    show_goal_progress: bool,
 
-    let category_group_totals =
-        report::build_category_group_totals_table(report_table_full.clone())?;
-
-    let week_year = report_week.week_start.year();
-    let week_number = report_week.week_number;
-    let start_label = report_start.format("%A %Y-%m-%d");
-    let end_label = report_end.format("%A %Y-%m-%d");
-    println!(
-        "Week {week_number} of {week_year}, starting on {start_label} and ending on {end_label}"
-    );
-
-    let week_short_start = format_short_date(report_start);
-    let week_short_end = format_short_date(report_end);
-    let visual_week_label = format!("Week {week_number} ({week_short_start} - {week_short_end})");
-
-    match &cfg.output_format {
-        OutputFormat::Simple(SimpleOutputFormat::PolarsPrint) => {
-            // SAFETY: single-threaded at this point, no concurrent env access
-            unsafe { std::env::set_var("POLARS_FMT_MAX_ROWS", "-1") };
-            let df = report_table_display
-                .collect()
-                .context("collecting report table")?;
-            let totals = category_group_totals
-                .collect()
-                .context("collecting totals")?;
-            println!("{df}");
-            println!("Category group totals");
-            println!("{totals}");
-        }
-        OutputFormat::Simple(SimpleOutputFormat::CsvPrint) => {
-            let mut df = report_table_display
-                .collect()
-                .context("collecting report table")?;
-            let mut totals = category_group_totals
-                .collect()
-                .context("collecting totals")?;
-            let csv = write_csv_string(&mut df)?;
-            let totals_csv = write_csv_string(&mut totals)?;
-            print!("{csv}");
-            println!("category_group_totals");
-            print!("{totals_csv}");
-        }
-        OutputFormat::CsvFile { csv_output } => {
-            let mut df = report_table_display
-                .collect()
-                .context("collecting report table")?;
-            let mut totals = category_group_totals
-                .collect()
-                .context("collecting totals")?;
-            let csv = write_csv_string(&mut df)?;
-            let totals_csv = write_csv_string(&mut totals)?;
-
-            let stem = csv_output
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("report");
-            let ext = csv_output
-                .extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("csv");
-            let totals_path =
-                csv_output.with_file_name(format!("{stem}_category_group_totals.{ext}"));
-
-            std::fs::write(csv_output, &csv).with_context(|| format!("writing {csv_output:?}"))?;
-            std::fs::write(&totals_path, &totals_csv)
-                .with_context(|| format!("writing {totals_path:?}"))?;
+    /// Print each pipeline stage's row counts and timing to stderr. Setting the
+    /// CRUSTYNAB_VERBOSE environment variable to 1 has the same effect
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Also write the week's transactions, after payee mapping and filtering, to PATH
+    /// as CSV (date, payee_name, category_name, amount)
+    #[arg(long, value_name = "PATH")]
+    export_transactions: Option<PathBuf>,
+
+    /// Print the report's week number and start and end dates to stderr after the report
+    #[arg(long)]
+    print_period: bool,
+
+    /// After the report, list categories spending over checkThresholdPercent (default
+    /// 100) of a non-zero budget on stderr and exit with code 1 if there are any
+    #[arg(long)]
+    check: bool,
+
+    /// Use this budget ID (a stable UUID, shown in the budget's YNAB web address)
+    /// directly, skipping the budget list lookup by name. Overrides the config's budgetId
+    #[arg(long, value_name = "UUID")]
+    budget_id: Option<String>,
+
+    /// Report the last N complete calendar months, one row per category and month,
+    /// instead of a single week. Overrides the config's defaultMonths
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    months: Option<u32>,
+
+    /// Color category groups from a palette (pastel, earth, ocean or high-contrast) in
+    /// watch list order, replacing the colors in config
+    #[arg(long, value_name = "NAME")]
+    color_scheme: Option<ColorScheme>,
+
+    /// Override one config key, e.g. `--set budget_name=Household` or
+    /// `--set output_format.csv_output=/tmp/report.csv`. Repeatable
+    #[arg(
+        long = "set",
+        visible_alias = "config-override",
+        value_name = "KEY=VALUE"
+    )]
+    overrides: Vec<ConfigOverride>,
+
+    /// Read the budget from a YNAB full-budget export JSON file instead of the API
+    #[arg(long, value_name = "PATH")]
+    offline: Option<PathBuf>,
+
+    /// Append every YNAB API response to PATH as NDJSON, for debugging
+    #[arg(long, value_name = "PATH")]
+    request_log: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the week partition for a year, or for one month of it
+    PrintWeeks {
+        #[arg(long)]
+        year: i32,
+
+        /// Only print this month's weeks (1-12)
+        #[arg(long)]
+        month: Option<u32>,
+
+        #[arg(long, value_enum, default_value_t = WeeksFormat::Table)]
+        format: WeeksFormat,
+    },
+    /// Compare two report CSVs (as written by csv_output) by category: added and
+    /// removed categories, and numeric values that changed by more than 0.01
+    Diff {
+        before: PathBuf,
+
+        after: PathBuf,
+
+        /// Write the differences to this CSV file instead of printing them
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum WeeksFormat {
+    Table,
+    Json,
+}
+
+#[derive(Serialize)]
+struct WeekRow {
+    week_number: usize,
+    month: u32,
+    week_start: chrono::NaiveDate,
+    week_end: chrono::NaiveDate,
+    days: Vec<String>,
+}
+
+/// Calls `run` every `interval` until Ctrl-C, skipping reruns while the budget's
+/// `last_modified_on` is unchanged. Budgets without a modification time always rerun.
+/// Status lines go to stderr, keeping stdout to the report output.
+fn watch(
+    api: &dyn YnabApi,
+    cfg: &config::Config,
+    opts: &RunOptions,
+    interval: Duration,
+) -> Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))
+        .context("installing Ctrl-C handler")?;
+
+    let mut last_rendered: Option<Option<String>> = None;
+    while running.load(Ordering::SeqCst) {
+        let budgets = api.get_budgets()?;
+        let last_modified = match opts.budget_id.as_ref().or(cfg.budget_id.as_ref()) {
+            Some(budget_id) => budgets
+                .iter()
+                .find(|budget| &budget.id == budget_id)
+                .and_then(|budget| budget.last_modified_on.clone()),
+            None => report::get_budget_last_modified(&budgets, &cfg.budget_name),
+        };
+        let changed = last_modified.is_none() || last_rendered.as_ref() != Some(&last_modified);
+        if changed {
+            run(api, cfg, opts, &mut std::io::stdout().lock())?;
+            last_rendered = Some(last_modified);
         }
-        OutputFormat::VisualFile { visual_output } => {
-            let html = build_visual_report_html(
-                report_table_full,
-                &cfg.category_group_watch_list,
-                &visual_week_label,
-                week_year,
-                cfg.show_all_rows,
-            )?;
-            std::fs::write(visual_output, &html)
-                .with_context(|| format!("writing {visual_output:?}"))?;
+        let status = if changed {
+            "budget changed, report refreshed"
+        } else {
+            "no changes"
+        };
+        eprintln!(
+            "[{}] {status}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+
+        let next_run = Instant::now() + interval;
+        while running.load(Ordering::SeqCst) && Instant::now() < next_run {
+            std::thread::sleep(Duration::from_millis(200).min(interval));
         }
     }
-
     Ok(())
 }
 
-fn write_csv_string(df: &mut DataFrame) -> Result<String> {
-    let mut buf = Vec::new();
-    CsvWriter::new(&mut buf).finish(df).context("writing CSV")?;
-    String::from_utf8(buf).context("CSV not valid UTF-8")
+fn print_weeks(year: i32, month: Option<u32>, format: WeeksFormat) -> Result<()> {
+    check_partition_year(year)?;
+    let weeks = match month {
+        Some(month) => month_weeks(Month::try_from((year, month))?),
+        None => partition_year_into_month_weeks(year).all_weeks().to_vec(),
+    };
+    let rows: Vec<WeekRow> = weeks
+        .iter()
+        .map(|week| WeekRow {
+            week_number: week.week_number,
+            month: week.month,
+            week_start: week.week_start,
+            week_end: week.week_end,
+            days: week
+                .dates()
+                .iter()
+                .map(|date| date.format("%a").to_string())
+                .collect(),
+        })
+        .collect();
+
+    match format {
+        WeeksFormat::Json => {
+            let json = serde_json::to_string_pretty(&rows).context("serializing weeks")?;
+            println!("{json}");
+        }
+        WeeksFormat::Table => {
+            println!(
+                "{:>4}  {:>5}  {:<10}  {:<10}  Days",
+                "Week", "Month", "Start", "End"
+            );
+            for row in &rows {
+                println!(
+                    "{:>4}  {:>5}  {:<10}  {:<10}  {}",
+                    row.week_number,
+                    row.month,
+                    row.week_start,
+                    row.week_end,
+                    row.days.join(", ")
+                );
+            }
+        }
+    }
+    Ok(())
 }
 
-fn format_short_date(date: chrono::NaiveDate) -> String {
-    let formatted = date.format("%b %d").to_string();
-    if let Some(space_pos) = formatted.rfind(' ') {
-        let (prefix, day_part) = formatted.split_at(space_pos + 1);
-        if day_part.starts_with('0') {
-            return format!("{}{}", prefix, &day_part[1..]);
+fn diff_reports(before: &Path, after: &Path, output: Option<&Path>, color: bool) -> Result<()> {
+    let diff = report::diff_csv_reports(before, after)?;
+    match output {
+        Some(path) => {
+            let csv = CsvRenderer::render(&mut report::csv_diff_table(&diff)?)?;
+            std::fs::write(path, csv).with_context(|| format!("writing CSV diff to {path:?}"))
+        }
+        None => {
+            print!("{}", report::format_csv_diff(&diff, color)?);
+            Ok(())
         }
     }
-    formatted
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let cfg = config::load_config(&args.config)?;
-    let api = HttpYnabClient::new(&cfg.personal_access_token)?;
-    run(&api, &cfg)
+    logging::init_from_env()?;
+    let no_color = args.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    match args.command {
+        Some(Command::PrintWeeks {
+            year,
+            month,
+            format,
+        }) => return print_weeks(year, month, format),
+        Some(Command::Diff {
+            before,
+            after,
+            output,
+        }) => {
+            let color = !no_color && std::io::stdout().is_terminal();
+            return diff_reports(&before, &after, output.as_deref(), color);
+        }
+        None => {}
+    }
+    let mut cfg =
+        config::load_config_with_overlay(&args.config, args.overlay.as_deref(), &args.overrides)?;
+    if let Some(scheme) = args.color_scheme {
+        cfg.category_group_watch_list = scheme_group_colors(&cfg.category_group_watch_list, scheme);
+    }
+    if args.dump_config {
+        println!("{}", config::dump_config(&cfg)?);
+        return Ok(());
+    }
+    let api: Box<dyn YnabApi> = match &args.offline {
+        Some(export_path) => Box::new(OfflineYnabClient::from_path(export_path)?),
+        None => {
+            let api = HttpYnabClient::new(&cfg.personal_access_token)?
+                .with_rate_limit_pause(cfg.rate_limit_pause);
+            let api = match &cfg.server_knowledge_cache {
+                Some(path) => api.with_server_knowledge_cache(path),
+                None => api,
+            };
+            Box::new(match args.request_log {
+                Some(path) => api.with_log_file(path),
+                None => api,
+            })
+        }
+    };
+    // SAFETY: single-threaded at this point, no concurrent env access
+    unsafe { std::env::set_var("POLARS_FMT_MAX_ROWS", "-1") };
+    if no_color {
+        // SAFETY: single-threaded at this point, no concurrent env access
+        unsafe { std::env::set_var("NO_COLOR", "1") };
+    }
+    let opts = RunOptions {
+        payee_filter: args.payee_filter,
+        color: !no_color && std::io::stderr().is_terminal(),
+        running_total: args.running_total,
+        months: args.months,
+        wide: args.wide,
+        compare_last_week: args.compare_last_week,
+        show_goal_progress: args.show_goal_progress,
+        budget_id: args.budget_id,
+        export_transactions: args.export_transactions,
+        print_period: args.print_period,
+        check: args.check,
+        verbose: args.verbose || std::env::var("CRUSTYNAB_VERBOSE").is_ok_and(|v| v == "1"),
+    };
+    match args.watch {
+        Some(seconds) => watch(
+            api.as_ref(),
+            &cfg,
+            &opts,
+            Duration::from_secs(seconds.max(1)),
+        ),
+        None => run(api.as_ref(), &cfg, &opts, &mut std::io::stdout().lock()),
+    }
 }
@@ -0,0 +1,25 @@
+const DELETED_SUFFIX: &str = " (deleted)";
+
+fn strip_year_suffix(name: &str) -> Option<&str> {
+    let stem = name.strip_suffix(')')?;
+    let (stem, year) = stem.split_at_checked(stem.len().checked_sub(4)?)?;
+    let stem = stem.strip_suffix(" (")?;
+    year.chars().all(|ch| ch.is_ascii_digit()).then_some(stem)
+}
+
+/// Strips the `" (deleted)"` and `" (YYYY)"` suffixes YNAB adds to archived categories,
+/// along with surrounding whitespace. Suffixes are removed repeatedly, so
+/// `"Phone (2019) (deleted)"` becomes `"Phone"`.
+pub fn sanitize_category_name(name: &str) -> &str {
+    let mut current = name.trim();
+    loop {
+        let stripped = current
+            .strip_suffix(DELETED_SUFFIX)
+            .or_else(|| strip_year_suffix(current))
+            .map(str::trim_end);
+        match stripped {
+            Some(next) => current = next,
+            None => return current,
+        }
+    }
+}
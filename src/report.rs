@@ -1,10 +1,16 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
 
 use anyhow::{Context, Result};
 use chrono::NaiveDate;
 use polars::prelude::*;
+use regex::Regex;
 
-use crate::ynab::{BudgetSummary, Category, CategoryGroup, Transaction};
+use crate::calendar_weeks::{Month, MonthWeek, month_weeks};
+use crate::goals::{GoalTracker, infer_goal_cadence};
+use crate::names::sanitize_category_name;
+use crate::server_knowledge_cache::KnownCategory;
+use crate::ynab::{BudgetSummary, Category, CategoryGroup, GoalType, Transaction};
 
 // --- Newtypes for DataFrames ---
 
@@ -14,8 +20,345 @@ pub struct CategoryFrame(pub LazyFrame);
 #[derive(Clone)]
 pub struct TransactionFrame(pub LazyFrame);
 
+/// Per-category budget changes from [`diff_category_frames`].
+#[derive(Clone)]
+pub struct DeltaFrame(pub LazyFrame);
+
+/// Decimal places floats are rounded to before frames are compared or hashed, so that
+/// equality is transitive and equal frames always hash alike.
+const FRAME_FLOAT_DECIMALS: i32 = 6;
+
+/// Collects `frame` with its rows sorted by every column, so row order never affects
+/// comparisons.
+fn sorted_content(frame: &LazyFrame) -> Result<DataFrame> {
+    let df = frame.clone().collect().context("collecting frame")?;
+    sort_by_every_column(df)
+}
+
+fn sort_by_every_column(df: DataFrame) -> Result<DataFrame> {
+    let columns = df.get_column_names_owned();
+    if columns.is_empty() {
+        return Ok(df);
+    }
+    df.sort(columns, SortMultipleOptions::default())
+        .context("sorting frame rows")
+}
+
+/// `values` rounded to [`FRAME_FLOAT_DECIMALS`] places, as text; negative zero is
+/// written as zero.
+fn rounded_float_column(name: &PlSmallStr, values: &Float64Chunked) -> Column {
+    let scale = 10f64.powi(FRAME_FLOAT_DECIMALS);
+    let decimals = FRAME_FLOAT_DECIMALS as usize;
+    let rounded: Vec<Option<String>> = values
+        .iter()
+        .map(|value| {
+            value.map(|value| format!("{:.decimals$}", (value * scale).round() / scale + 0.0))
+        })
+        .collect();
+    Column::new(name.clone(), rounded)
+}
+
+/// The frame's schema and its rows sorted, with float columns as
+/// [`rounded_float_column`] text: the form frames are compared and hashed in.
+fn canonical_content(frame: &LazyFrame) -> Result<(String, DataFrame)> {
+    let df = frame.clone().collect().context("collecting frame")?;
+    let schema = format!("{:?}", df.schema());
+    let columns: Vec<Column> = df
+        .get_columns()
+        .iter()
+        .map(|column| match column.f64() {
+            Ok(values) => rounded_float_column(column.name(), values),
+            Err(_) => column.clone(),
+        })
+        .collect();
+    let df = DataFrame::new(columns).context("rebuilding rounded frame")?;
+    Ok((schema, sort_by_every_column(df)?))
+}
+
+/// Content equality: same schema and the same rows in any order, floats rounded to
+/// [`FRAME_FLOAT_DECIMALS`] places. Frames that fail to collect are never equal.
+fn frames_equal(left: &LazyFrame, right: &LazyFrame) -> bool {
+    match (canonical_content(left), canonical_content(right)) {
+        (Ok((left_schema, left)), Ok((right_schema, right))) => {
+            left_schema == right_schema && left.equals_missing(&right)
+        }
+        _ => false,
+    }
+}
+
+/// Hashes the schema and the [`canonical_content`] rows as CSV, the same values
+/// [`frames_equal`] compares. A frame that fails to collect hashes as empty.
+fn hash_frame<H: std::hash::Hasher>(frame: &LazyFrame, state: &mut H) {
+    use std::hash::Hash;
+
+    let csv = canonical_content(frame).and_then(|(schema, mut df)| {
+        let mut buf = Vec::new();
+        CsvWriter::new(&mut buf)
+            .finish(&mut df)
+            .context("writing frame CSV for hashing")?;
+        Ok((schema, buf))
+    });
+    if let Ok((schema, csv)) = csv {
+        schema.hash(state);
+        csv.hash(state);
+    }
+}
+
+/// Shows the sorted, collected rows, so `assert_eq!` failures print what differed.
+fn debug_frame(name: &str, frame: &LazyFrame, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match sorted_content(frame) {
+        Ok(df) => write!(f, "{name}({df})"),
+        Err(err) => write!(f, "{name}(<{err:#}>)"),
+    }
+}
+
+impl std::fmt::Debug for CategoryFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        debug_frame("CategoryFrame", &self.0, f)
+    }
+}
+
+impl std::fmt::Debug for TransactionFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        debug_frame("TransactionFrame", &self.0, f)
+    }
+}
+
+impl std::fmt::Debug for DeltaFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        debug_frame("DeltaFrame", &self.0, f)
+    }
+}
+
+impl PartialEq for CategoryFrame {
+    fn eq(&self, other: &Self) -> bool {
+        frames_equal(&self.0, &other.0)
+    }
+}
+
+impl Eq for CategoryFrame {}
+
+impl std::hash::Hash for CategoryFrame {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        hash_frame(&self.0, state);
+    }
+}
+
+impl PartialEq for TransactionFrame {
+    fn eq(&self, other: &Self) -> bool {
+        frames_equal(&self.0, &other.0)
+    }
+}
+
+impl Eq for TransactionFrame {}
+
+impl std::hash::Hash for TransactionFrame {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        hash_frame(&self.0, state);
+    }
+}
+
+const CATEGORY_COLUMNS: [&str; 5] = [
+    "category_name",
+    "category_group_name",
+    "budgeted",
+    "balance",
+    "goal_cadence",
+];
+
+const TRANSACTION_COLUMNS: [&str; 4] = ["date", "amount", "payee_name", "category_name"];
+
+fn scan_csv_with_columns(path: &Path, expected: &[&str]) -> Result<(LazyFrame, Schema)> {
+    let mut frame = LazyCsvReader::new(path)
+        .with_has_header(true)
+        .with_try_parse_dates(true)
+        .finish()
+        .with_context(|| format!("reading CSV from {path:?}"))?;
+    let schema = frame
+        .collect_schema()
+        .with_context(|| format!("inferring CSV schema for {path:?}"))?;
+    let missing: Vec<&str> = expected
+        .iter()
+        .copied()
+        .filter(|name| !schema.contains(name))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "CSV {path:?} is missing expected columns: {}",
+            missing.join(", ")
+        );
+    }
+    Ok((frame, schema.as_ref().clone()))
+}
+
+/// Fails listing every column of `expected` that `frame` lacks or holds with another
+/// type, naming the frame as `frame_name`.
+fn check_schema(frame: &LazyFrame, frame_name: &str, expected: &[(&str, DataType)]) -> Result<()> {
+    let schema = frame
+        .clone()
+        .collect_schema()
+        .with_context(|| format!("resolving {frame_name} schema"))?;
+    let problems: Vec<String> = expected
+        .iter()
+        .filter_map(|(name, dtype)| match schema.get(name) {
+            None => Some(format!("{name} is missing")),
+            Some(actual) if actual != dtype => {
+                Some(format!("{name} is {actual}, expected {dtype}"))
+            }
+            Some(_) => None,
+        })
+        .collect();
+    if !problems.is_empty() {
+        anyhow::bail!("{frame_name} schema mismatch: {}", problems.join("; "));
+    }
+    Ok(())
+}
+
+fn casts_for_present(schema: &Schema, columns: &[(&str, DataType)]) -> Vec<Expr> {
+    columns
+        .iter()
+        .filter(|(name, _)| schema.contains(name))
+        .map(|(name, dtype)| col(*name).cast(dtype.clone()))
+        .collect()
+}
+
+impl CategoryFrame {
+    #[tracing::instrument(level = "debug")]
+    pub fn from_csv(path: &Path) -> Result<CategoryFrame> {
+        let (frame, schema) = scan_csv_with_columns(path, &CATEGORY_COLUMNS)?;
+        let frame = CategoryFrame(frame.with_columns(casts_for_present(
+            &schema,
+            &[
+                ("category_name", DataType::String),
+                ("category_group_name", DataType::String),
+                ("budgeted", DataType::Float64),
+                ("balance", DataType::Float64),
+                ("goal_cadence", DataType::String),
+                ("goal_cadence_detail", DataType::Int32),
+                ("goal_target_milliunits", DataType::Int64),
+                ("goal_percent_complete", DataType::Int32),
+                ("goal_type", DataType::String),
+            ],
+        )));
+        frame
+            .schema_check()
+            .with_context(|| format!("checking categories read from {path:?}"))?;
+        Ok(frame)
+    }
+
+    /// Fails with every missing or wrong-typed column the report transforms rely on, so
+    /// an externally built frame is rejected before Polars fails deep inside them.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn schema_check(&self) -> Result<()> {
+        check_schema(
+            &self.0,
+            "CategoryFrame",
+            &[
+                ("category_name", DataType::String),
+                ("category_group_name", DataType::String),
+                ("budgeted", DataType::Float64),
+                ("balance", DataType::Float64),
+                ("goal_cadence", DataType::String),
+            ],
+        )
+    }
+
+    /// The categories in `group_name`, still lazy.
+    pub fn filter_by_group(self, group_name: &str) -> CategoryFrame {
+        CategoryFrame(
+            self.0
+                .filter(col("category_group_name").eq(lit(group_name))),
+        )
+    }
+
+    /// The categories named in `names`, still lazy.
+    pub fn filter_by_category_names(self, names: &HashSet<String>) -> CategoryFrame {
+        let names_vec: Vec<&str> = names.iter().map(String::as_str).collect();
+        let names_series = Series::new("_cat_filter".into(), &names_vec);
+        CategoryFrame(self.0.filter(col("category_name").is_in(lit(names_series))))
+    }
+}
+
+impl TransactionFrame {
+    #[tracing::instrument(level = "debug")]
+    pub fn from_csv(path: &Path) -> Result<TransactionFrame> {
+        let (frame, schema) = scan_csv_with_columns(path, &TRANSACTION_COLUMNS)?;
+        let frame = TransactionFrame(frame.with_columns(casts_for_present(
+            &schema,
+            &[
+                ("date", DataType::Date),
+                ("amount", DataType::Float64),
+                ("payee_name", DataType::String),
+                ("category_name", DataType::String),
+                ("id", DataType::String),
+                ("subtransaction_id", DataType::String),
+            ],
+        )));
+        frame
+            .schema_check()
+            .with_context(|| format!("checking transactions read from {path:?}"))?;
+        Ok(frame)
+    }
+
+    /// Like [`CategoryFrame::schema_check`], for the transaction columns.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn schema_check(&self) -> Result<()> {
+        check_schema(
+            &self.0,
+            "TransactionFrame",
+            &[
+                ("date", DataType::Date),
+                ("amount", DataType::Float64),
+                ("payee_name", DataType::String),
+                ("category_name", DataType::String),
+            ],
+        )
+    }
+
+    /// The sum of `amount` across every transaction, counting null amounts as zero; 0.0
+    /// for an empty frame.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn total_spent(&self) -> Result<f64> {
+        let df = self
+            .0
+            .clone()
+            .select([col("amount")
+                .cast(DataType::Float64)
+                .fill_null(lit(0.0))
+                .sum()])
+            .collect()
+            .context("summing transaction amounts")?;
+        Ok(df
+            .column("amount")
+            .context("amount column")?
+            .f64()
+            .context("amount as f64")?
+            .get(0)
+            .unwrap_or(0.0))
+    }
+
+    /// [`TransactionFrame::total_spent`] over the transactions in `category`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn total_for_category(&self, category: &str) -> Result<f64> {
+        TransactionFrame(
+            self.0
+                .clone()
+                .filter(col("category_name").eq(lit(category))),
+        )
+        .total_spent()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupTotals {
+    pub budgeted: f64,
+    pub spent: f64,
+    pub balance: f64,
+}
+
 // --- Pure functions ---
 
+#[tracing::instrument(level = "debug", skip(budgets))]
 pub fn get_budget_id(budgets: &[BudgetSummary], budget_name: &str) -> Option<String> {
     budgets
         .iter()
@@ -23,6 +366,33 @@ pub fn get_budget_id(budgets: &[BudgetSummary], budget_name: &str) -> Option<Str
         .map(|b| b.id.clone())
 }
 
+/// Checks that `budget_id` is one of `budgets` and is the budget named `budget_name`.
+#[tracing::instrument(level = "debug", skip(budgets))]
+pub fn check_budget_id(
+    budgets: &[BudgetSummary],
+    budget_id: &str,
+    budget_name: &str,
+) -> Result<()> {
+    match budgets.iter().find(|b| b.id == budget_id) {
+        None => anyhow::bail!("no budget found with id {budget_id}"),
+        Some(budget) if budget.name != budget_name => anyhow::bail!(
+            "budgetId {budget_id} is the budget {:?}, not budgetName {budget_name:?}",
+            budget.name
+        ),
+        Some(_) => Ok(()),
+    }
+}
+
+/// `last_modified_on` of the budget named `budget_name`, if YNAB reported one.
+#[tracing::instrument(level = "debug", skip(budgets))]
+pub fn get_budget_last_modified(budgets: &[BudgetSummary], budget_name: &str) -> Option<String> {
+    budgets
+        .iter()
+        .find(|b| b.name == budget_name)
+        .and_then(|b| b.last_modified_on.clone())
+}
+
+#[tracing::instrument(level = "debug", skip(groups))]
 pub fn get_missing_category_groups(
     groups: &[CategoryGroup],
     watch_list: &indexmap::IndexMap<String, String>,
@@ -35,64 +405,360 @@ pub fn get_missing_category_groups(
         .collect()
 }
 
+/// Color given to watched groups the watch list has no color for.
+pub const FALLBACK_GROUP_COLOR: &str = "#cccccc";
+
+/// `group_name`'s color from the watch list, or [`FALLBACK_GROUP_COLOR`] when the group
+/// is missing or its color is blank.
+#[tracing::instrument(level = "debug", skip(watch_list))]
+pub fn resolve_group_color<'a>(
+    group_name: &str,
+    watch_list: &'a indexmap::IndexMap<String, String>,
+) -> &'a str {
+    watch_list
+        .get(group_name)
+        .map(String::as_str)
+        .filter(|color| !color.trim().is_empty())
+        .unwrap_or(FALLBACK_GROUP_COLOR)
+}
+
+/// Name prefixes of the groups YNAB creates itself; a group whose name equals or
+/// starts with one of these is a system group.
+pub const SYSTEM_GROUP_PREFIXES: &[&str] = &[
+    "Internal Master Category",
+    "Credit Card Payments",
+    "Hidden Categories",
+];
+
+#[tracing::instrument(level = "debug")]
+pub fn is_system_group_name(name: &str) -> bool {
+    SYSTEM_GROUP_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+#[tracing::instrument(level = "debug", skip(group), fields(group_name = %group.name))]
+pub fn is_system_group(group: &CategoryGroup) -> bool {
+    is_system_group_name(&group.name)
+}
+
+/// The categories [`get_categories_to_watch`] selected, with the warnings for the caller
+/// to show.
+#[derive(Debug, Clone)]
+pub struct WatchedCategories {
+    pub categories: Vec<Category>,
+    pub warnings: Vec<String>,
+}
+
+/// Categories of the watched groups that are neither hidden nor deleted. With
+/// `exclude_system_groups`, system groups are dropped even when they are watched.
+/// Watched groups left with no categories get a warning, or fail the call with
+/// `strict_mode`.
+#[tracing::instrument(level = "debug", skip(groups, known_categories))]
 pub fn get_categories_to_watch(
     groups: &[CategoryGroup],
     watch_list: &indexmap::IndexMap<String, String>,
-) -> Vec<Category> {
-    let watched_names: HashSet<&str> = watch_list.keys().map(String::as_str).collect();
+    known_categories: Option<&HashSet<String>>,
+    exclude_system_groups: bool,
+    strict_mode: bool,
+) -> Result<WatchedCategories> {
+    let deleted_warnings = known_categories
+        .map(|known| get_deleted_known_categories(groups, watch_list, known))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|name| format!("category {name} was deleted in YNAB and is no longer reported"));
+    let system_group_warnings = watch_list
+        .keys()
+        .filter(|name| exclude_system_groups && is_system_group_name(name))
+        .map(|name| {
+            format!(
+                "category group {name} is a YNAB system group and is excluded; set excludeSystemGroups to false to report it"
+            )
+        });
+
+    let reported_groups: Vec<&CategoryGroup> = watched_groups(groups, watch_list)
+        .filter(|g| !(exclude_system_groups && is_system_group(g)))
+        .collect();
+    let empty_messages: Vec<String> = reported_groups
+        .iter()
+        .filter(|g| !g.categories.iter().any(|c| !c.hidden && !c.deleted))
+        .map(|g| {
+            format!(
+                "group '{}' has no visible categories and will produce an empty report section",
+                g.name
+            )
+        })
+        .collect();
+    if strict_mode && !empty_messages.is_empty() {
+        anyhow::bail!("{} (strictMode)", empty_messages.join("; "));
+    }
+
+    Ok(WatchedCategories {
+        categories: reported_groups
+            .into_iter()
+            .flat_map(|g| g.categories.iter())
+            .filter(|c| !c.hidden && !c.deleted)
+            .cloned()
+            .collect(),
+        warnings: deleted_warnings
+            .chain(system_group_warnings)
+            .chain(empty_messages.iter().map(|message| format!("{message}.")))
+            .collect(),
+    })
+}
+
+/// Picks the `watched` categories, in their order, out of a whole month's categories.
+/// Month categories that lack `category_group_name` take it from the watched entry.
+#[tracing::instrument(level = "debug", skip_all, fields(month_categories = month_categories.len(), watched = watched.len()))]
+pub fn select_watched_month_categories(
+    month_categories: Vec<Category>,
+    watched: &[Category],
+) -> Result<Vec<Category>> {
+    let mut by_id: HashMap<String, Category> = month_categories
+        .into_iter()
+        .map(|category| (category.id.clone(), category))
+        .collect();
+    watched
+        .iter()
+        .map(|watched| {
+            let mut category = by_id
+                .remove(&watched.id)
+                .ok_or_else(|| anyhow::anyhow!("month has no category {}", watched.name))?;
+            if category.category_group_name.is_none() {
+                category
+                    .category_group_name
+                    .clone_from(&watched.category_group_name);
+            }
+            Ok(category)
+        })
+        .collect()
+}
+
+#[tracing::instrument(level = "debug", skip(groups, known_categories))]
+pub fn get_deleted_known_categories(
+    groups: &[CategoryGroup],
+    watch_list: &indexmap::IndexMap<String, String>,
+    known_categories: &HashSet<String>,
+) -> Vec<String> {
+    let mut names: Vec<String> = watched_categories(groups, watch_list)
+        .filter(|c| c.deleted && known_categories.contains(&c.name))
+        .map(|c| c.name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Every category in `groups` that YNAB has not deleted, keyed by ID, whether or not it
+/// is hidden or watched.
+#[tracing::instrument(level = "debug", skip_all, fields(groups = groups.len()))]
+pub fn known_categories(groups: &[CategoryGroup]) -> BTreeMap<String, KnownCategory> {
     groups
         .iter()
-        .filter(|g| watched_names.contains(g.name.as_str()))
-        .flat_map(|g| g.categories.iter())
-        .filter(|c| !c.hidden)
-        .cloned()
+        .filter(|group| !group.deleted)
+        .flat_map(|group| {
+            group.categories.iter().filter(|c| !c.deleted).map(|c| {
+                let known = KnownCategory {
+                    group: group.name.clone(),
+                    name: c.name.clone(),
+                };
+                (c.id.clone(), known)
+            })
+        })
         .collect()
 }
 
+/// The names of the `previous` categories (recorded by an earlier run) in a group of
+/// `watch_list` whose IDs are missing from `current`, sorted. Renamed, hidden or
+/// unwatched categories keep their IDs, so only those deleted or merged away count.
+#[tracing::instrument(level = "debug", skip_all, fields(previous = previous.len(), current = current.len()))]
+pub fn get_vanished_categories(
+    previous: &BTreeMap<String, KnownCategory>,
+    current: &BTreeMap<String, KnownCategory>,
+    watch_list: &indexmap::IndexMap<String, String>,
+) -> Vec<String> {
+    let mut names: Vec<String> = previous
+        .iter()
+        .filter(|(id, known)| !current.contains_key(*id) && watch_list.contains_key(&known.group))
+        .map(|(_, known)| known.name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+fn watched_groups<'a>(
+    groups: &'a [CategoryGroup],
+    watch_list: &indexmap::IndexMap<String, String>,
+) -> impl Iterator<Item = &'a CategoryGroup> {
+    let watched_names: HashSet<String> = watch_list.keys().cloned().collect();
+    groups
+        .iter()
+        .filter(move |g| watched_names.contains(&g.name))
+}
+
+fn watched_categories<'a>(
+    groups: &'a [CategoryGroup],
+    watch_list: &indexmap::IndexMap<String, String>,
+) -> impl Iterator<Item = &'a Category> {
+    watched_groups(groups, watch_list).flat_map(|g| g.categories.iter())
+}
+
 fn date_to_polars_days(date: NaiveDate) -> i32 {
     let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch");
     (date - epoch).num_days() as i32
 }
 
+/// YNAB's category name for a transaction split across several categories.
+pub const SPLIT_CATEGORY_NAME: &str = "Split";
+
+/// Where a split transaction with no subtransactions (a malformed YNAB entry) is
+/// reported, with the transaction's own amount.
+pub const UNCATEGORIZED_SPLIT_CATEGORY: &str = "Uncategorized Split";
+
+fn is_empty_split(txn: &Transaction) -> bool {
+    txn.subtransactions.is_empty() && txn.category_name.as_deref() == Some(SPLIT_CATEGORY_NAME)
+}
+
+/// The IDs of the split transactions without subtransactions that
+/// [`transactions_to_polars`] reports as [`UNCATEGORIZED_SPLIT_CATEGORY`].
+#[tracing::instrument(level = "debug", skip_all, fields(transactions = transactions.len()))]
+pub fn empty_split_transactions(transactions: &[Transaction]) -> Vec<String> {
+    transactions
+        .iter()
+        .filter(|txn| is_empty_split(txn))
+        .map(|txn| txn.id.clone())
+        .collect()
+}
+
+/// Where [`TransactionExpandOptions::include_uncategorized`] reports transactions with
+/// no category and no subtransactions.
+pub const UNCATEGORIZED_CATEGORY: &str = "Uncategorized";
+
+/// How [`transactions_to_polars_with`] turns transactions into category rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TransactionExpandOptions {
+    /// Report transactions with no category and no subtransactions under
+    /// [`UNCATEGORIZED_CATEGORY`] instead of dropping them.
+    pub include_uncategorized: bool,
+}
+
+/// Largest milliunit magnitude an `f64` holds exactly (2^53); larger amounts lose
+/// precision and are treated as invalid by [`transactions_to_polars`].
+pub const MAX_EXACT_MILLIUNITS: u64 = 1 << 53;
+
 struct TransactionRow {
+    id: Option<String>,
+    subtransaction_id: Option<String>,
     date: NaiveDate,
-    amount: f64,
+    amount_milli: i64,
     payee_name: Option<String>,
     category_name: String,
 }
 
-fn expand_transaction(txn: &Transaction) -> Vec<TransactionRow> {
+/// `amount_milli` in currency units, or `None` when it is beyond
+/// [`MAX_EXACT_MILLIUNITS`] or doesn't convert to a finite number.
+fn milliunits_to_amount(amount_milli: i64) -> Option<f64> {
+    let amount = amount_milli as f64 / 1000.0;
+    (amount.is_finite() && amount_milli.unsigned_abs() <= MAX_EXACT_MILLIUNITS).then_some(amount)
+}
+
+/// `(id, amount_milli)` of each row whose amount has no valid conversion; subtransaction
+/// rows are listed by their subtransaction ID.
+fn invalid_row_amounts(rows: &[TransactionRow]) -> Vec<(String, i64)> {
+    rows.iter()
+        .filter(|r| milliunits_to_amount(r.amount_milli).is_none())
+        .map(|r| {
+            let id = r.id.as_deref().or(r.subtransaction_id.as_deref());
+            (id.unwrap_or("<no id>").to_string(), r.amount_milli)
+        })
+        .collect()
+}
+
+/// The transactions (or subtransactions) [`transactions_to_polars`] counts as 0 because
+/// their milliunit amount has no exact `f64` value.
+#[tracing::instrument(level = "debug", skip_all, fields(transactions = transactions.len()))]
+pub fn invalid_transaction_amounts(transactions: &[Transaction]) -> Vec<(String, i64)> {
+    let rows: Vec<TransactionRow> = transactions
+        .iter()
+        .flat_map(|txn| expand_transaction(txn, TransactionExpandOptions::default()))
+        .collect();
+    invalid_row_amounts(&rows)
+}
+
+fn expand_transaction(txn: &Transaction, options: TransactionExpandOptions) -> Vec<TransactionRow> {
     if !txn.subtransactions.is_empty() {
         txn.subtransactions
             .iter()
             .filter_map(|sub| {
                 sub.category_name.as_ref().map(|cat_name| TransactionRow {
+                    id: None,
+                    subtransaction_id: Some(sub.id.clone()),
                     date: txn.date,
-                    amount: sub.amount as f64 / 1000.0,
+                    amount_milli: sub.amount,
                     payee_name: sub.payee_name.clone().or_else(|| txn.payee_name.clone()),
-                    category_name: cat_name.clone(),
+                    category_name: sanitize_category_name(cat_name).to_string(),
                 })
             })
             .collect()
     } else if let Some(cat_name) = &txn.category_name {
+        let cat_name = if is_empty_split(txn) {
+            UNCATEGORIZED_SPLIT_CATEGORY
+        } else {
+            cat_name
+        };
+        vec![TransactionRow {
+            id: Some(txn.id.clone()),
+            subtransaction_id: None,
+            date: txn.date,
+            amount_milli: txn.amount,
+            payee_name: txn.payee_name.clone(),
+            category_name: sanitize_category_name(cat_name).to_string(),
+        }]
+    } else if options.include_uncategorized {
         vec![TransactionRow {
+            id: Some(txn.id.clone()),
+            subtransaction_id: None,
             date: txn.date,
-            amount: txn.amount as f64 / 1000.0,
+            amount_milli: txn.amount,
             payee_name: txn.payee_name.clone(),
-            category_name: cat_name.clone(),
+            category_name: UNCATEGORIZED_CATEGORY.to_string(),
         }]
     } else {
         vec![]
     }
 }
 
+#[tracing::instrument(level = "debug", skip(transactions), fields(transactions = transactions.len()))]
 pub fn transactions_to_polars(transactions: &[Transaction]) -> Result<TransactionFrame> {
-    let rows: Vec<TransactionRow> = transactions.iter().flat_map(expand_transaction).collect();
+    transactions_to_polars_with(transactions, TransactionExpandOptions::default())
+}
+
+/// Like [`transactions_to_polars`], expanding transactions as `options` says.
+#[tracing::instrument(level = "debug", skip(transactions), fields(transactions = transactions.len()))]
+pub fn transactions_to_polars_with(
+    transactions: &[Transaction],
+    options: TransactionExpandOptions,
+) -> Result<TransactionFrame> {
+    let rows: Vec<TransactionRow> = transactions
+        .iter()
+        .flat_map(|txn| expand_transaction(txn, options))
+        .collect();
+    tracing::debug!(
+        rows = rows.len(),
+        "expanded transactions into category rows"
+    );
 
     let dates: Vec<i32> = rows.iter().map(|r| date_to_polars_days(r.date)).collect();
-    let amounts: Vec<f64> = rows.iter().map(|r| r.amount).collect();
+    let amounts: Vec<f64> = rows
+        .iter()
+        .map(|r| milliunits_to_amount(r.amount_milli).unwrap_or(0.0))
+        .collect();
     let payees: Vec<Option<&str>> = rows.iter().map(|r| r.payee_name.as_deref()).collect();
     let categories: Vec<&str> = rows.iter().map(|r| r.category_name.as_str()).collect();
+    let ids: Vec<Option<&str>> = rows.iter().map(|r| r.id.as_deref()).collect();
+    let subtransaction_ids: Vec<Option<&str>> = rows
+        .iter()
+        .map(|r| r.subtransaction_id.as_deref())
+        .collect();
 
     let date_series = Column::new("date".into(), &dates)
         .cast(&DataType::Date)
@@ -102,14 +768,20 @@ pub fn transactions_to_polars(transactions: &[Transaction]) -> Result<Transactio
         Column::new("amount".into(), &amounts),
         Column::new("payee_name".into(), &payees),
         Column::new("category_name".into(), &categories),
+        Column::new("id".into(), &ids),
+        Column::new("subtransaction_id".into(), &subtransaction_ids),
     ])
     .context("building transactions DataFrame")?;
 
     Ok(TransactionFrame(df.lazy()))
 }
 
+#[tracing::instrument(level = "debug", skip(categories), fields(categories = categories.len()))]
 pub fn categories_to_polars(categories: &[Category]) -> Result<CategoryFrame> {
-    let names: Vec<&str> = categories.iter().map(|c| c.name.as_str()).collect();
+    let names: Vec<&str> = categories
+        .iter()
+        .map(|c| sanitize_category_name(&c.name))
+        .collect();
     let group_names: Vec<&str> = categories
         .iter()
         .map(|c| c.category_group_name.as_deref().unwrap_or("Uncategorized"))
@@ -123,15 +795,22 @@ pub fn categories_to_polars(categories: &[Category]) -> Result<CategoryFrame> {
         .map(|c| c.balance as f64 / 1000.0)
         .collect();
     let goal_cadence: Vec<&str> = categories
+        .iter()
+        .map(|c| infer_goal_cadence(c.goal_cadence, c.goal_target))
+        .collect();
+    let goal_cadence_detail: Vec<Option<i32>> = categories.iter().map(|c| c.goal_cadence).collect();
+    let goal_target: Vec<Option<i64>> = categories.iter().map(|c| c.goal_target).collect();
+    let goal_percent_complete: Vec<Option<i32>> = categories
         .iter()
         .map(|c| {
-            if c.goal_target.is_some() && c.goal_cadence == Some(1) {
-                "monthly"
-            } else {
-                "annual"
-            }
+            c.goal_percentage_complete
+                .map(|percent| percent.clamp(0, 100))
         })
         .collect();
+    let goal_type: Vec<Option<&str>> = categories
+        .iter()
+        .map(|c| c.goal_type.as_ref().map(GoalType::code))
+        .collect();
 
     let df = DataFrame::new(vec![
         Column::new("category_name".into(), &names),
@@ -139,73 +818,1059 @@ pub fn categories_to_polars(categories: &[Category]) -> Result<CategoryFrame> {
         Column::new("budgeted".into(), &budgeted),
         Column::new("balance".into(), &balance),
         Column::new("goal_cadence".into(), &goal_cadence),
+        Column::new("goal_cadence_detail".into(), &goal_cadence_detail),
+        Column::new("goal_target_milliunits".into(), &goal_target),
+        Column::new("goal_percent_complete".into(), &goal_percent_complete),
+        Column::new("goal_type".into(), &goal_type),
     ])
     .context("building categories DataFrame")?;
 
     Ok(CategoryFrame(df.lazy()))
 }
 
+/// [`categories_to_polars`] plus a `fetch_date` (`Date`) column holding `fetch_date` on
+/// every row, so category snapshots from several periods stay told apart.
+#[tracing::instrument(level = "debug", skip(categories), fields(categories = categories.len()))]
+pub fn categories_to_polars_with_metadata(
+    categories: &[Category],
+    fetch_date: NaiveDate,
+) -> Result<CategoryFrame> {
+    let frame = categories_to_polars(categories)?;
+    Ok(CategoryFrame(
+        frame.0.with_column(
+            lit(date_to_polars_days(fetch_date))
+                .cast(DataType::Date)
+                .alias("fetch_date"),
+        ),
+    ))
+}
+
+/// Adds `free_balance`: `balance` minus the goal target still committed for this month.
+///
+/// Annual and quarterly targets are prorated to a monthly share
+/// ([`GoalTracker::monthly_target`]); other cadences commit the whole target.
+/// Categories without a goal keep their full balance free.
+#[tracing::instrument(level = "debug", skip(categories))]
+pub fn with_free_balance(categories: CategoryFrame) -> CategoryFrame {
+    let target = col("goal_target_milliunits")
+        .cast(DataType::Float64)
+        .fill_null(lit(0.0))
+        / lit(1000.0);
+    let target = when(target.clone().gt(lit(0.0)))
+        .then(target)
+        .otherwise(lit(0.0));
+    let prorated_target = target.map_many(
+        |columns| {
+            let targets = columns[0].f64()?;
+            let cadences = columns[1].str()?;
+            let prorated: Float64Chunked = targets
+                .iter()
+                .zip(cadences.iter())
+                .map(|(target, cadence)| {
+                    target.map(|target| {
+                        GoalTracker::monthly_target(target, cadence.unwrap_or_default())
+                    })
+                })
+                .collect();
+            Ok(Some(
+                prorated.with_name(columns[0].name().clone()).into_column(),
+            ))
+        },
+        &[col("goal_cadence")],
+        GetOutput::from_type(DataType::Float64),
+    );
+
+    CategoryFrame(
+        categories
+            .0
+            .with_columns([(col("balance") - prorated_target).alias("free_balance")]),
+    )
+}
+
+/// Replaces `budgeted` with the configured amount for each category named in
+/// `overrides`, and adds `budget_override_applied` marking the rows that changed.
+#[tracing::instrument(level = "debug", skip_all, fields(overrides = overrides.len()))]
+pub fn apply_budget_override(cf: CategoryFrame, overrides: &HashMap<String, f64>) -> CategoryFrame {
+    let (budgeted, applied) = overrides.iter().fold(
+        (col("budgeted"), lit(false)),
+        |(budgeted, applied), (name, amount)| {
+            let is_match = col("category_name").eq(lit(name.as_str()));
+            (
+                when(is_match.clone())
+                    .then(lit(*amount))
+                    .otherwise(budgeted),
+                applied.or(is_match),
+            )
+        },
+    );
+    CategoryFrame(cf.0.with_columns([
+        budgeted.alias("budgeted"),
+        applied.alias("budget_override_applied"),
+    ]))
+}
+
+/// Sorts by date (keeping the input order within a day) and adds `running_total`,
+/// the cumulative sum of `amount` down the sorted rows.
+#[tracing::instrument(level = "debug", skip(tf))]
+pub fn annotate_with_running_total(tf: TransactionFrame) -> Result<TransactionFrame> {
+    Ok(TransactionFrame(
+        tf.0.sort(
+            ["date"],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )
+        .with_columns([col("amount").cum_sum(false).alias("running_total")]),
+    ))
+}
+
+#[tracing::instrument(level = "debug", skip(tf))]
 pub fn relevant_transactions(
     tf: TransactionFrame,
     start_date: NaiveDate,
     end_date: NaiveDate,
 ) -> TransactionFrame {
-    let start = date_to_polars_days(start_date);
-    let end = date_to_polars_days(end_date);
-    TransactionFrame(
-        tf.0.filter(
-            col("date")
-                .cast(DataType::Int32)
-                .gt_eq(lit(start))
-                .and(col("date").cast(DataType::Int32).lt_eq(lit(end))),
-        ),
-    )
+    relevant_transactions_in_groups(tf, start_date, end_date, None, &HashMap::new())
 }
 
-pub fn build_report_table(
-    categories: CategoryFrame,
-    transactions: TransactionFrame,
-    category_names: &HashSet<String>,
-) -> Result<LazyFrame> {
-    let names_vec: Vec<&str> = category_names.iter().map(String::as_str).collect();
-    let names_series = Series::new("_cat_filter".into(), &names_vec);
+/// Like [`relevant_transactions`], additionally keeping only transactions whose category
+/// belongs to one of `group_filter`'s groups when it is `Some`. Categories missing from
+/// `category_name_to_group` are dropped by the group filter.
+#[tracing::instrument(level = "debug", skip(tf, group_filter, category_name_to_group))]
+pub fn relevant_transactions_in_groups(
+    tf: TransactionFrame,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    group_filter: Option<&HashSet<String>>,
+    category_name_to_group: &HashMap<String, String>,
+) -> TransactionFrame {
+    let in_range = tf.0.filter(
+        col("date")
+            .gt_eq(lit(start_date))
+            .and(col("date").lt_eq(lit(end_date))),
+    );
 
-    let total_spent = transactions
-        .0
-        .filter(col("category_name").is_in(lit(names_series)))
-        .group_by([col("category_name")])
-        .agg([col("amount").sum().alias("spent")]);
+    match group_filter {
+        Some(groups) => {
+            let names: Vec<&str> = category_name_to_group
+                .iter()
+                .filter(|(_, group)| groups.contains(*group))
+                .map(|(name, _)| name.as_str())
+                .collect();
+            let names_series = Series::new("_group_cat_filter".into(), &names);
+            TransactionFrame(in_range.filter(col("category_name").is_in(lit(names_series))))
+        }
+        None => TransactionFrame(in_range),
+    }
+}
 
-    let report = categories
+/// Maps each category in `categories` to its `category_group_name`.
+#[tracing::instrument(level = "debug", skip(categories))]
+pub fn category_name_to_group(categories: &CategoryFrame) -> Result<HashMap<String, String>> {
+    let df = categories
         .0
-        .join(
-            total_spent,
-            [col("category_name")],
-            [col("category_name")],
-            JoinArgs::new(JoinType::Left),
-        )
-        .with_columns([col("spent").fill_null(lit(0.0))])
-        .select([
-            col("category_group_name"),
-            col("category_name"),
-            col("budgeted"),
-            col("spent"),
-            col("balance"),
-            col("goal_cadence"),
-        ])
-        .sort(
-            ["category_group_name", "category_name"],
-            SortMultipleOptions::default(),
-        );
-
-    Ok(report)
+        .clone()
+        .select([col("category_name"), col("category_group_name")])
+        .collect()
+        .context("collecting category groups")?;
+    let names = df
+        .column("category_name")
+        .context("category_name column")?
+        .str()
+        .context("category_name as str")?;
+    let groups = df
+        .column("category_group_name")
+        .context("category_group_name column")?
+        .str()
+        .context("category_group_name as str")?;
+    let mapping: HashMap<String, String> = names
+        .iter()
+        .zip(groups.iter())
+        .filter_map(|(name, group)| Some((name?.to_string(), group?.to_string())))
+        .collect();
+    tracing::debug!(categories = mapping.len(), "mapped categories to groups");
+    Ok(mapping)
 }
 
-pub fn build_category_group_totals_table(report_table: LazyFrame) -> Result<LazyFrame> {
-    let group_totals = report_table
-        .clone()
-        .group_by([col("category_group_name")])
-        .agg([
+/// Appends a `month` column ("YYYY-MM") naming which of `months` each transaction falls
+/// in; transactions dated outside all of them get a null month.
+#[tracing::instrument(level = "debug", skip(tf))]
+pub fn annotate_with_month(tf: TransactionFrame, months: &[Month]) -> Result<TransactionFrame> {
+    let (days, labels): (Vec<i32>, Vec<String>) = months
+        .iter()
+        .flat_map(|month| {
+            month
+                .first_day()
+                .iter_days()
+                .take_while(move |day| *day <= month.last_day())
+                .map(move |day| (date_to_polars_days(day), month.to_string()))
+        })
+        .unzip();
+
+    let month_lookup = DataFrame::new(vec![
+        Column::new("_month_date".into(), &days)
+            .cast(&DataType::Date)
+            .context("casting month lookup dates")?,
+        Column::new("month".into(), &labels),
+    ])
+    .context("building month lookup DataFrame")?;
+
+    Ok(TransactionFrame(tf.0.join(
+        month_lookup.lazy(),
+        [col("date")],
+        [col("_month_date")],
+        JoinArgs::new(JoinType::Left),
+    )))
+}
+
+/// Appends a `week_number` column from `month`'s week partition; transactions dated
+/// outside that month get a null week.
+#[tracing::instrument(level = "debug", skip(tf))]
+pub fn annotate_with_week(tf: TransactionFrame, month: Month) -> Result<TransactionFrame> {
+    let (days, week_numbers): (Vec<i32>, Vec<u32>) = month_weeks(month)
+        .iter()
+        .flat_map(|week| {
+            week.dates()
+                .into_iter()
+                .map(move |day| (date_to_polars_days(day), week.week_number as u32))
+        })
+        .unzip();
+
+    let week_lookup = DataFrame::new(vec![
+        Column::new("_week_date".into(), &days)
+            .cast(&DataType::Date)
+            .context("casting week lookup dates")?,
+        Column::new("week_number".into(), &week_numbers),
+    ])
+    .context("building week lookup DataFrame")?;
+
+    Ok(TransactionFrame(tf.0.join(
+        week_lookup.lazy(),
+        [col("date")],
+        [col("_week_date")],
+        JoinArgs::new(JoinType::Left),
+    )))
+}
+
+/// Payee-name prefix YNAB gives both sides of a transfer between two of the budget's
+/// accounts, followed by the other account's name.
+pub const TRANSFER_PAYEE_PREFIX: &str = "Transfer : ";
+
+/// Drops uncategorized transfers (payees starting with [`TRANSFER_PAYEE_PREFIX`] and no
+/// category); null payees are kept. YNAB records a transfer between two budget accounts
+/// as an outflow from one plus a matching inflow to the other, neither categorized, so
+/// keeping them would count money that never left the budget once as spending and
+/// again as income. A transfer to an off-budget tracking account carries a category and
+/// is real spending, so it stays.
+#[tracing::instrument(level = "debug", skip(tf))]
+pub fn filter_transfers(tf: TransactionFrame) -> TransactionFrame {
+    let not_transfer_payee = col("payee_name").map(
+        |column| {
+            let payees = column.str()?;
+            let keep: BooleanChunked = payees
+                .iter()
+                .map(|payee| Some(!payee.is_some_and(|p| p.starts_with(TRANSFER_PAYEE_PREFIX))))
+                .collect();
+            Ok(Some(keep.with_name(column.name().clone()).into_column()))
+        },
+        GetOutput::from_type(DataType::Boolean),
+    );
+    TransactionFrame(tf.0.filter(not_transfer_payee.or(col("category_name").is_not_null())))
+}
+
+#[tracing::instrument(level = "debug", skip(tf))]
+pub fn filter_by_payee_regex(tf: TransactionFrame, pattern: &str) -> Result<TransactionFrame> {
+    let regex =
+        Regex::new(pattern).with_context(|| format!("invalid payee filter regex {pattern:?}"))?;
+    let matches_payee = col("payee_name").map(
+        move |column| {
+            let payees = column.str()?;
+            let matched: BooleanChunked = payees
+                .iter()
+                .map(|payee| Some(regex.is_match(payee.unwrap_or(""))))
+                .collect();
+            Ok(Some(matched.with_name(column.name().clone()).into_column()))
+        },
+        GetOutput::from_type(DataType::Boolean),
+    );
+    Ok(TransactionFrame(tf.0.filter(matches_payee)))
+}
+
+/// Renames payees using `mapping`, whose keys are exact payee names or glob patterns
+/// such as `"Amazon*"`. The first matching key in insertion order wins; unmatched
+/// and null payees pass through unchanged.
+#[tracing::instrument(level = "debug", skip(tf))]
+pub fn apply_payee_mapping(
+    tf: TransactionFrame,
+    mapping: &indexmap::IndexMap<String, String>,
+) -> Result<TransactionFrame> {
+    if mapping.is_empty() {
+        return Ok(tf);
+    }
+    let rules: Vec<(String, glob::Pattern, String)> = mapping
+        .iter()
+        .map(|(key, canonical)| {
+            let pattern = glob::Pattern::new(key)
+                .with_context(|| format!("invalid payeeMapping pattern {key:?}"))?;
+            Ok((key.clone(), pattern, canonical.clone()))
+        })
+        .collect::<Result<_>>()?;
+
+    let mapped_payee = col("payee_name").map(
+        move |column| {
+            let payees = column.str()?;
+            let mapped: StringChunked = payees
+                .iter()
+                .map(|payee| {
+                    payee.map(|payee| {
+                        rules
+                            .iter()
+                            .find(|(key, pattern, _)| key == payee || pattern.matches(payee))
+                            .map_or(payee, |(_, _, canonical)| canonical.as_str())
+                    })
+                })
+                .collect();
+            Ok(Some(mapped.with_name(column.name().clone()).into_column()))
+        },
+        GetOutput::from_type(DataType::String),
+    );
+    Ok(TransactionFrame(
+        tf.0.with_columns([mapped_payee.alias("payee_name")]),
+    ))
+}
+
+/// Which transactions [`build_report_table`] sums into `spent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransactionSide {
+    /// Inflows and outflows netted together.
+    #[default]
+    All,
+    /// Only inflows (`amount > 0`).
+    Income,
+    /// Only outflows (`amount <= 0`).
+    Expense,
+}
+
+impl TransactionSide {
+    #[tracing::instrument(level = "debug", skip(tf))]
+    pub fn select(self, tf: TransactionFrame) -> TransactionFrame {
+        match self {
+            TransactionSide::All => tf,
+            TransactionSide::Income => TransactionFrame(tf.0.filter(col("amount").gt(lit(0.0)))),
+            TransactionSide::Expense => {
+                TransactionFrame(tf.0.filter(col("amount").lt_eq(lit(0.0))))
+            }
+        }
+    }
+}
+
+/// Splits `tf` into `(income, expenses)`. Zero-amount transactions land with the
+/// expenses so the two frames always partition the input.
+#[tracing::instrument(level = "debug", skip(tf))]
+pub fn split_transactions_by_sign(tf: TransactionFrame) -> (TransactionFrame, TransactionFrame) {
+    (
+        TransactionSide::Income.select(tf.clone()),
+        TransactionSide::Expense.select(tf),
+    )
+}
+
+/// Each category's share of its group's total `budgeted`, as a percentage; null when the
+/// group has nothing budgeted.
+fn pct_of_group_budgeted() -> Expr {
+    let group_budgeted = col("budgeted").sum().over([col("category_group_name")]);
+    when(group_budgeted.clone().eq(lit(0.0)))
+        .then(lit(NULL).cast(DataType::Float64))
+        .otherwise(col("budgeted") / group_budgeted * lit(100.0))
+        .alias("pct_of_group_budgeted")
+}
+
+/// Category columns copied into the report table when `categories` has them.
+const OPTIONAL_REPORT_COLUMNS: [&str; 3] = [
+    "free_balance",
+    "goal_percent_complete",
+    "budget_override_applied",
+];
+
+/// Carries [`OPTIONAL_REPORT_COLUMNS`] through when `categories` has them (see
+/// [`with_free_balance`] and [`apply_budget_override`]) and adds `pct_of_group_budgeted`. `side` restricts which
+/// transactions count towards `spent`.
+#[tracing::instrument(level = "debug", skip(categories, transactions, category_names), fields(categories = category_names.len()))]
+pub fn build_report_table(
+    categories: CategoryFrame,
+    transactions: TransactionFrame,
+    category_names: &HashSet<String>,
+    side: TransactionSide,
+) -> Result<LazyFrame> {
+    let names_vec: Vec<&str> = category_names.iter().map(String::as_str).collect();
+    let watched_names = DataFrame::new(vec![Column::new("category_name".into(), names_vec)])
+        .context("building watched category names frame")?
+        .lazy();
+
+    // The watch-list restriction is a semi join on the names rather than an `is_in`
+    // filter against a literal series, so it is part of the lazy plan: the optimizer
+    // pushes the two-column projection into the transaction scan, and the aggregate runs
+    // before the join onto categories, which therefore sees one row per category rather
+    // than one per transaction. `benches/report_table.rs` measured no difference from
+    // the old filter at 10k transactions.
+    let total_spent = side
+        .select(transactions)
+        .0
+        .join(
+            watched_names,
+            [col("category_name")],
+            [col("category_name")],
+            JoinArgs::new(JoinType::Semi),
+        )
+        .group_by([col("category_name")])
+        .agg([col("amount").sum().alias("spent")]);
+
+    let categories_schema = categories
+        .0
+        .clone()
+        .collect_schema()
+        .context("reading categories schema")?;
+    let columns: Vec<Expr> = [
+        "category_group_name",
+        "category_name",
+        "budgeted",
+        "spent",
+        "balance",
+        "goal_cadence",
+    ]
+    .into_iter()
+    .chain(
+        OPTIONAL_REPORT_COLUMNS
+            .into_iter()
+            .filter(|name| categories_schema.contains(name)),
+    )
+    .map(col)
+    .collect();
+
+    // `sanitize_category_name` gives an archived category (e.g. "Phone (2019)") the
+    // name of its successor, and both share one spent total, so categories are merged
+    // by name first; joining each of them would count that spending twice.
+    let merged_categories = categories.0.group_by_stable([col("category_name")]).agg(
+        categories_schema
+            .iter_names()
+            .filter(|name| name.as_str() != "category_name")
+            .map(|name| match name.as_str() {
+                "budgeted" | "balance" | "free_balance" => col(name.clone()).sum(),
+                "budget_override_applied" => col(name.clone()).any(true),
+                _ => col(name.clone()).first(),
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    tracing::debug!("planning join of spent totals onto categories");
+    let report = merged_categories
+        .join(
+            total_spent,
+            [col("category_name")],
+            [col("category_name")],
+            JoinArgs::new(JoinType::Left),
+        )
+        .with_columns([col("spent").fill_null(lit(0.0))])
+        .select(columns)
+        .with_columns([pct_of_group_budgeted()])
+        .sort(
+            ["category_group_name", "category_name"],
+            SortMultipleOptions::default(),
+        );
+    tracing::debug!("report table join planned");
+
+    Ok(report)
+}
+
+/// A collected report table with the partition week it covers, so the frame can be
+/// labelled without a redundant date column.
+#[derive(Debug, Clone)]
+pub struct ReportTableWithMeta {
+    pub frame: DataFrame,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    pub week_number: usize,
+}
+
+impl ReportTableWithMeta {
+    /// `week 3, 2024-03-10 to 2024-03-16`
+    pub fn period_label(&self) -> String {
+        format!(
+            "week {}, {} to {}",
+            self.week_number, self.week_start, self.week_end
+        )
+    }
+}
+
+/// Serializes the metadata only (`week_number`, `week_start`, `week_end`), for embedding
+/// in JSON output next to the rendered table.
+impl serde::Serialize for ReportTableWithMeta {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("ReportTableWithMeta", 3)?;
+        state.serialize_field("week_number", &self.week_number)?;
+        state.serialize_field("week_start", &self.week_start)?;
+        state.serialize_field("week_end", &self.week_end)?;
+        state.end()
+    }
+}
+
+/// Collects a table built by [`build_report_table`] (and any columns added since) along
+/// with `week`'s dates and number.
+#[tracing::instrument(level = "debug", skip(report_table))]
+pub fn collect_with_meta(report_table: LazyFrame, week: &MonthWeek) -> Result<ReportTableWithMeta> {
+    Ok(ReportTableWithMeta {
+        frame: report_table.collect().context("collecting report table")?,
+        week_start: week.week_start,
+        week_end: week.week_end,
+        week_number: week.week_number,
+    })
+}
+
+/// One row per watched category and month, with columns `category_name`,
+/// `category_group_name`, `month` ("YYYY-MM"), `budgeted`, `spent` and `balance`.
+/// `month_categories` pairs each month with that month's category snapshot, and the
+/// transactions are bucketed into those months with [`annotate_with_month`].
+#[tracing::instrument(level = "debug", skip_all, fields(months = month_categories.len(), categories = category_names.len()))]
+pub fn build_monthly_report_table(
+    month_categories: Vec<(Month, CategoryFrame)>,
+    transactions: TransactionFrame,
+    category_names: &HashSet<String>,
+) -> Result<LazyFrame> {
+    let months: Vec<Month> = month_categories.iter().map(|(month, _)| *month).collect();
+    let names_vec: Vec<&str> = category_names.iter().map(String::as_str).collect();
+    let names_series = Series::new("_cat_filter".into(), &names_vec);
+
+    let monthly_spent = annotate_with_month(transactions, &months)?
+        .0
+        .filter(
+            col("category_name")
+                .is_in(lit(names_series))
+                .and(col("month").is_not_null()),
+        )
+        .group_by([col("category_name"), col("month")])
+        .agg([col("amount").sum().alias("spent")]);
+
+    let categories: Vec<LazyFrame> = month_categories
+        .into_iter()
+        .map(|(month, categories)| {
+            categories.0.select([
+                col("category_name"),
+                col("category_group_name"),
+                lit(month.to_string()).alias("month"),
+                col("budgeted"),
+                col("balance"),
+            ])
+        })
+        .collect();
+    if categories.is_empty() {
+        return Ok(DataFrame::new(vec![
+            Column::new_empty("category_name".into(), &DataType::String),
+            Column::new_empty("category_group_name".into(), &DataType::String),
+            Column::new_empty("month".into(), &DataType::String),
+            Column::new_empty("budgeted".into(), &DataType::Float64),
+            Column::new_empty("spent".into(), &DataType::Float64),
+            Column::new_empty("balance".into(), &DataType::Float64),
+        ])
+        .context("building empty monthly report")?
+        .lazy());
+    }
+
+    Ok(concat(categories, UnionArgs::default())
+        .context("concatenating monthly categories")?
+        .join(
+            monthly_spent,
+            [col("category_name"), col("month")],
+            [col("category_name"), col("month")],
+            JoinArgs::new(JoinType::Left),
+        )
+        .with_columns([col("spent").fill_null(lit(0.0))])
+        .select([
+            col("category_name"),
+            col("category_group_name"),
+            col("month"),
+            col("budgeted"),
+            col("spent"),
+            col("balance"),
+        ])
+        .sort(
+            ["category_group_name", "category_name", "month"],
+            SortMultipleOptions::default(),
+        ))
+}
+
+/// Pivots `report_table` to one row per `category_name` and one `spent` column per
+/// category group, named after the group and in report order; a category's value is null
+/// under every group but its own. Polars only pivots materialized frames, so this collects
+/// `report_table` and returns the result as a new lazy frame.
+#[tracing::instrument(level = "debug", skip(report_table))]
+pub fn report_table_to_wide_format(report_table: LazyFrame) -> Result<LazyFrame> {
+    let df = report_table
+        .select([
+            col("category_name"),
+            col("category_group_name"),
+            col("spent"),
+        ])
+        .collect()
+        .context("collecting report table for wide format")?;
+    let wide = polars::lazy::frame::pivot::pivot_stable(
+        &df,
+        ["category_group_name"],
+        Some(["category_name"]),
+        Some(["spent"]),
+        false,
+        None,
+        None,
+    )
+    .context("pivoting report table to wide format")?;
+    tracing::debug!(
+        rows = wide.height(),
+        columns = wide.width(),
+        "pivoted report table"
+    );
+    Ok(wide.lazy())
+}
+
+/// Per-category inflows for the income breakdown: `category_group_name`,
+/// `category_name` and `income`, keeping only categories that received money.
+#[tracing::instrument(level = "debug", skip(categories, transactions, category_names))]
+pub fn build_income_table(
+    categories: CategoryFrame,
+    transactions: TransactionFrame,
+    category_names: &HashSet<String>,
+) -> Result<LazyFrame> {
+    Ok(build_report_table(
+        categories,
+        transactions,
+        category_names,
+        TransactionSide::Income,
+    )?
+    .filter(col("spent").gt(lit(0.0)))
+    .select([
+        col("category_group_name"),
+        col("category_name"),
+        col("spent").alias("income"),
+    ]))
+}
+
+fn categories_where(df: &DataFrame, mask: &BooleanChunked) -> Result<Vec<String>> {
+    let names = df
+        .column("category_name")
+        .context("category_name column")?
+        .str()
+        .context("category_name as str")?;
+    Ok(names
+        .iter()
+        .zip(mask.iter())
+        .filter(|(_, flagged)| flagged.unwrap_or(false))
+        .map(|(name, _)| name.unwrap_or("<null>").to_string())
+        .collect())
+}
+
+/// Sanity-checks a collected report table: required columns have no nulls and there is
+/// exactly one row per category. Every violation is listed in the returned error.
+/// Positive `spent` (a week of net refunds) and negative `budgeted` (which YNAB allows)
+/// are unusual but valid, so they are only logged as warnings.
+#[tracing::instrument(level = "debug", skip(df), fields(rows = df.height()))]
+pub fn validate_report_table(df: &DataFrame, category_count: usize) -> Result<()> {
+    let mut violations: Vec<String> = Vec::new();
+
+    for name in [
+        "category_name",
+        "category_group_name",
+        "budgeted",
+        "spent",
+        "balance",
+    ] {
+        match df.column(name) {
+            Ok(column) if column.null_count() > 0 => {
+                violations.push(format!("{name} has {} nulls", column.null_count()))
+            }
+            Ok(_) => {}
+            Err(_) => violations.push(format!("{name} column is missing")),
+        }
+    }
+
+    let sign_checks = [
+        ("spent", "positive spent", true),
+        ("budgeted", "negative budgeted", false),
+    ];
+    for (name, label, positive_is_bad) in sign_checks {
+        let Ok(values) = df.column(name).and_then(|c| c.f64().cloned()) else {
+            continue;
+        };
+        let mask = if positive_is_bad {
+            values.gt(0.0)
+        } else {
+            values.lt(0.0)
+        };
+        let offenders = categories_where(df, &mask)?;
+        if !offenders.is_empty() {
+            tracing::warn!(categories = %offenders.join(", "), "report table has {label}");
+        }
+    }
+
+    if df.height() != category_count {
+        violations.push(format!(
+            "expected {category_count} rows (one per category), found {}",
+            df.height()
+        ));
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        anyhow::bail!(
+            "report table failed validation:\n- {}",
+            violations.join("\n- ")
+        )
+    }
+}
+
+/// Re-sorts any frame with a `category_group_name` column so groups follow the watch
+/// list's insertion order. The sort is stable, so rows keep their existing order within
+/// a group, and groups absent from the watch list (such as the `Total` row) come last.
+#[tracing::instrument(level = "debug", skip(frame))]
+pub fn order_groups_by_watch_list(
+    frame: LazyFrame,
+    watch_list: &indexmap::IndexMap<String, String>,
+) -> Result<LazyFrame> {
+    let group_names: Vec<&str> = watch_list.keys().map(String::as_str).collect();
+    let group_order: Vec<u32> = (0..group_names.len() as u32).collect();
+    let order_df = DataFrame::new(vec![
+        Column::new("category_group_name".into(), &group_names),
+        Column::new("_group_order".into(), &group_order),
+    ])
+    .context("building watch list order DataFrame")?;
+
+    let ordered = frame
+        .join(
+            order_df.lazy(),
+            [col("category_group_name")],
+            [col("category_group_name")],
+            JoinArgs::new(JoinType::Left),
+        )
+        .sort(
+            ["_group_order"],
+            SortMultipleOptions::default()
+                .with_nulls_last(true)
+                .with_maintain_order(true),
+        )
+        .drop(["_group_order"]);
+
+    Ok(ordered)
+}
+
+/// `categories` merged to one row per `category_name`, with the first group name and the
+/// summed `budgeted`, so that categories sharing a sanitized name (e.g. "Rent" and
+/// "Rent (2023)") join as one, as in [`build_report_table`].
+fn budgeted_by_name(categories: LazyFrame) -> LazyFrame {
+    categories
+        .group_by_stable([col("category_name")])
+        .agg([col("category_group_name").first(), col("budgeted").sum()])
+}
+
+/// Adds `budget_change`, this month's `budgeted` minus the previous month's. Categories
+/// missing from `previous` get a null change.
+#[tracing::instrument(level = "debug", skip(report_table, previous))]
+pub fn with_budget_change(report_table: LazyFrame, previous: CategoryFrame) -> LazyFrame {
+    let previous_budgeted = budgeted_by_name(previous.0).select([
+        col("category_name"),
+        col("budgeted").alias("_previous_budgeted"),
+    ]);
+    report_table
+        .join(
+            previous_budgeted,
+            [col("category_name")],
+            [col("category_name")],
+            JoinArgs::new(JoinType::Left),
+        )
+        .with_columns([(col("budgeted") - col("_previous_budgeted")).alias("budget_change")])
+        .drop(["_previous_budgeted"])
+}
+
+/// One row per category in either frame, for month-start planning: `category_group_name`,
+/// `category_name`, `budgeted_before`, `budgeted_after` and `budgeted_delta` (after minus
+/// before). A category missing from one frame counts as budgeting 0 there, so added
+/// categories have a delta of their `budgeted` and removed ones its negation.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn diff_category_frames(before: CategoryFrame, after: CategoryFrame) -> Result<DeltaFrame> {
+    let before = budgeted_by_name(before.0).select([
+        col("category_group_name").alias("_before_group_name"),
+        col("category_name"),
+        col("budgeted").alias("budgeted_before"),
+    ]);
+    let after = budgeted_by_name(after.0).select([
+        col("category_group_name"),
+        col("category_name"),
+        col("budgeted").alias("budgeted_after"),
+    ]);
+
+    Ok(DeltaFrame(
+        after
+            .join(
+                before,
+                [col("category_name")],
+                [col("category_name")],
+                JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+            )
+            .with_columns([
+                col("category_group_name").fill_null(col("_before_group_name")),
+                col("budgeted_before").fill_null(lit(0.0)),
+                col("budgeted_after").fill_null(lit(0.0)),
+            ])
+            .select([
+                col("category_group_name"),
+                col("category_name"),
+                col("budgeted_before"),
+                col("budgeted_after"),
+                (col("budgeted_after") - col("budgeted_before")).alias("budgeted_delta"),
+            ])
+            .sort(
+                ["category_group_name", "category_name"],
+                SortMultipleOptions::default(),
+            ),
+    ))
+}
+
+/// One row per category in either report table, with `spent` from `this_week`,
+/// `last_week_spent`, their difference `delta_spent` and `improved`. A category missing
+/// from one week counts as spending 0 there. `spent` is signed (outflows negative), so
+/// `improved` (less spent than last week) is a positive `delta_spent`.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn build_wow_comparison_table(this_week: LazyFrame, last_week: LazyFrame) -> Result<LazyFrame> {
+    let this_week = this_week.select([
+        col("category_group_name"),
+        col("category_name"),
+        col("spent"),
+    ]);
+    let last_week = last_week.select([
+        col("category_group_name").alias("_last_group_name"),
+        col("category_name"),
+        col("spent").alias("last_week_spent"),
+    ]);
+    let delta_spent = col("spent") - col("last_week_spent");
+
+    Ok(this_week
+        .join(
+            last_week,
+            [col("category_name")],
+            [col("category_name")],
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .with_columns([
+            col("category_group_name").fill_null(col("_last_group_name")),
+            col("spent").fill_null(lit(0.0)),
+            col("last_week_spent").fill_null(lit(0.0)),
+        ])
+        .select([
+            col("category_group_name"),
+            col("category_name"),
+            col("spent"),
+            col("last_week_spent"),
+            delta_spent.clone().alias("delta_spent"),
+            delta_spent.gt(lit(0.0)).alias("improved"),
+        ])
+        .sort(
+            ["category_group_name", "category_name"],
+            SortMultipleOptions::default(),
+        ))
+}
+
+/// Year-to-date progress of each category with an annual goal, as of the end of `month`
+/// (1–12): `annual_goal` (the goal target), `expected_ytd` (`month / 12` of it,
+/// [`GoalTracker::expected_ytd`]), `actual_ytd` (outflows from `year_to_date_spent`, a
+/// frame of `category_name` and signed `spent`) and `goal_status`
+/// ([`GoalTracker::goal_status`]). Spending within
+/// [`crate::goals::GOAL_PROGRESS_TOLERANCE`] of the expected amount is "on track"; less
+/// is "behind" and more is "ahead" of schedule.
+#[tracing::instrument(level = "debug", skip(categories, year_to_date_spent))]
+pub fn build_goal_progress_table(
+    categories: CategoryFrame,
+    year_to_date_spent: LazyFrame,
+    month: u32,
+) -> Result<LazyFrame> {
+    if !(1..=12).contains(&month) {
+        anyhow::bail!("goal progress month must be between 1 and 12, got {month}");
+    }
+    let annual_goals = categories
+        .0
+        .filter(
+            col("goal_cadence")
+                .eq(lit("annual"))
+                .and(col("goal_target_milliunits").gt(lit(0))),
+        )
+        .select([
+            col("category_group_name"),
+            col("category_name"),
+            (col("goal_target_milliunits").cast(DataType::Float64) / lit(1000.0))
+                .alias("annual_goal"),
+        ]);
+    let spent = year_to_date_spent.select([col("category_name"), col("spent")]);
+
+    Ok(annual_goals
+        .join(
+            spent,
+            [col("category_name")],
+            [col("category_name")],
+            JoinArgs::new(JoinType::Left),
+        )
+        .with_columns([
+            col("annual_goal")
+                .map(
+                    move |column| {
+                        let expected: Float64Chunked = column
+                            .f64()?
+                            .apply_values(|goal| GoalTracker::expected_ytd(goal, month));
+                        Ok(Some(expected.into_column()))
+                    },
+                    GetOutput::from_type(DataType::Float64),
+                )
+                .alias("expected_ytd"),
+            (lit(0.0) - col("spent"))
+                .fill_null(lit(0.0))
+                .alias("actual_ytd"),
+        ])
+        .with_columns([col("actual_ytd")
+            .map_many(
+                |columns| {
+                    let actual = columns[0].f64()?;
+                    let expected = columns[1].f64()?;
+                    let status: StringChunked = actual
+                        .iter()
+                        .zip(expected.iter())
+                        .map(|(actual, expected)| {
+                            Some(GoalTracker::goal_status(actual?, expected?))
+                        })
+                        .collect();
+                    Ok(Some(status.into_column()))
+                },
+                &[col("expected_ytd")],
+                GetOutput::from_type(DataType::String),
+            )
+            .alias("goal_status")])
+        .select([
+            col("category_group_name"),
+            col("category_name"),
+            col("annual_goal"),
+            col("expected_ytd"),
+            col("actual_ytd"),
+            col("goal_status"),
+        ])
+        .sort(
+            ["category_group_name", "category_name"],
+            SortMultipleOptions::default(),
+        ))
+}
+
+/// Adds `percent_spent` and `weighted_percent_spent`, where the latter divides the
+/// raw percentage by the share of the month's weeks that have elapsed. Values above
+/// 1.0 mean the category is spending faster than the month is passing.
+#[tracing::instrument(level = "debug", skip(report_table))]
+pub fn with_weighted_percent_spent(
+    report_table: LazyFrame,
+    current_week_number: usize,
+    total_weeks_in_month: usize,
+) -> Result<LazyFrame> {
+    if current_week_number == 0 || current_week_number > total_weeks_in_month {
+        anyhow::bail!(
+            "week {current_week_number} is outside a month of {total_weeks_in_month} weeks"
+        );
+    }
+    let expected_fraction = current_week_number as f64 / total_weeks_in_month as f64;
+
+    let percent_spent = when(col("budgeted").eq(lit(0.0)))
+        .then(lit(NULL).cast(DataType::Float64))
+        .otherwise(-col("spent") / col("budgeted") * lit(100.0));
+
+    Ok(report_table
+        .with_columns([percent_spent.alias("percent_spent")])
+        .with_columns([
+            (col("percent_spent") / lit(expected_fraction * 100.0)).alias("weighted_percent_spent")
+        ]))
+}
+
+#[tracing::instrument(level = "debug", skip(report_table))]
+pub fn group_totals(report_table: &LazyFrame, group_name: &str) -> Result<GroupTotals> {
+    sum_totals(
+        report_table
+            .clone()
+            .filter(col("category_group_name").eq(lit(group_name))),
+    )
+    .with_context(|| format!("totalling category group {group_name}"))
+}
+
+#[tracing::instrument(level = "debug", skip(report_table))]
+pub fn overall_totals(report_table: &LazyFrame) -> Result<GroupTotals> {
+    sum_totals(report_table.clone()).context("totalling report")
+}
+
+fn sum_totals(report_table: LazyFrame) -> Result<GroupTotals> {
+    let df = report_table
+        .select([
+            col("budgeted").sum(),
+            col("spent").sum(),
+            col("balance").sum(),
+        ])
+        .collect()
+        .context("collecting totals")?;
+    let value = |name: &str| -> Result<f64> {
+        Ok(df
+            .column(name)
+            .with_context(|| format!("{name} column"))?
+            .f64()
+            .with_context(|| format!("{name} as f64"))?
+            .get(0)
+            .unwrap_or(0.0))
+    };
+    Ok(GroupTotals {
+        budgeted: value("budgeted")?,
+        spent: value("spent")?,
+        balance: value("balance")?,
+    })
+}
+
+/// Order of the group rows in [`build_category_group_totals_table`]; the `Total` row
+/// always comes last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupTotalsSortOrder {
+    /// By `category_group_name`.
+    #[default]
+    Alphabetical,
+    /// Largest `spent` magnitude first.
+    BySpentDesc,
+    /// Largest `budgeted` first.
+    ByBudgetedDesc,
+}
+
+impl GroupTotalsSortOrder {
+    fn sort(self, group_totals: LazyFrame) -> LazyFrame {
+        let by_name_within = |key: Expr| {
+            group_totals.clone().sort_by_exprs(
+                [key, col("category_group_name")],
+                SortMultipleOptions::default().with_order_descending_multi([true, false]),
+            )
+        };
+        match self {
+            GroupTotalsSortOrder::Alphabetical => group_totals
+                .clone()
+                .sort(["category_group_name"], SortMultipleOptions::default()),
+            GroupTotalsSortOrder::BySpentDesc => by_name_within(
+                when(col("spent").lt(lit(0.0)))
+                    .then(-col("spent"))
+                    .otherwise(col("spent")),
+            ),
+            GroupTotalsSortOrder::ByBudgetedDesc => by_name_within(col("budgeted")),
+        }
+    }
+}
+
+#[tracing::instrument(level = "debug", skip(report_table))]
+pub fn build_category_group_totals_table(
+    report_table: LazyFrame,
+    sort_order: GroupTotalsSortOrder,
+) -> Result<LazyFrame> {
+    let group_totals = report_table
+        .clone()
+        .group_by([col("category_group_name")])
+        .agg([
             col("budgeted").sum().alias("budgeted"),
             col("spent").sum().alias("spent"),
             col("balance").sum().alias("balance"),
@@ -215,8 +1880,8 @@ pub fn build_category_group_totals_table(report_table: LazyFrame) -> Result<Lazy
             col("budgeted"),
             col("spent"),
             col("balance"),
-        ])
-        .sort(["category_group_name"], SortMultipleOptions::default());
+        ]);
+    let group_totals = sort_order.sort(group_totals);
 
     let overall_total = report_table.select([
         lit("Total").alias("category_group_name"),
@@ -230,3 +1895,355 @@ pub fn build_category_group_totals_table(report_table: LazyFrame) -> Result<Lazy
 
     Ok(result)
 }
+
+/// Weekly outflow per `category_group_name`, one row per watched group and week:
+/// `week_number`, `category_group_name`, `spent`.
+#[tracing::instrument(level = "debug", skip_all, fields(weeks = weeks.len()))]
+pub fn build_weekly_group_spending(
+    categories: CategoryFrame,
+    transactions: TransactionFrame,
+    category_names: &HashSet<String>,
+    weeks: &[MonthWeek],
+) -> Result<LazyFrame> {
+    weekly_spending_by(
+        categories,
+        transactions,
+        category_names,
+        weeks,
+        "category_group_name",
+    )
+}
+
+/// Weekly outflow per category over `weeks`, for sparklines: `week_number`,
+/// `category_name`, `spent`, with a row for every category in every week and rows in
+/// the order of `weeks`.
+#[tracing::instrument(level = "debug", skip_all, fields(weeks = weeks.len()))]
+pub fn build_weekly_trend_table(
+    categories: CategoryFrame,
+    transactions: TransactionFrame,
+    category_names: &HashSet<String>,
+    weeks: &[MonthWeek],
+) -> Result<LazyFrame> {
+    weekly_spending_by(
+        categories,
+        transactions,
+        category_names,
+        weeks,
+        "category_name",
+    )
+}
+
+/// `spent` summed by `key` for each of `weeks`, sorted by week position then `key`.
+fn weekly_spending_by(
+    categories: CategoryFrame,
+    transactions: TransactionFrame,
+    category_names: &HashSet<String>,
+    weeks: &[MonthWeek],
+    key: &str,
+) -> Result<LazyFrame> {
+    let per_week = weeks
+        .iter()
+        .enumerate()
+        .map(|(position, week)| {
+            let week_transactions =
+                relevant_transactions(transactions.clone(), week.week_start, week.week_end);
+            let week_report = build_report_table(
+                categories.clone(),
+                week_transactions,
+                category_names,
+                TransactionSide::All,
+            )?;
+            Ok(week_report
+                .group_by([col(key)])
+                .agg([col("spent").sum().alias("spent")])
+                .select([
+                    lit(position as u32).alias("_week_position"),
+                    lit(week.week_number as u32).alias("week_number"),
+                    col(key),
+                    col("spent"),
+                ]))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if per_week.is_empty() {
+        let empty = DataFrame::new(vec![
+            Column::new_empty("week_number".into(), &DataType::UInt32),
+            Column::new_empty(key.into(), &DataType::String),
+            Column::new_empty("spent".into(), &DataType::Float64),
+        ])
+        .context("building empty weekly spending DataFrame")?;
+        return Ok(empty.lazy());
+    }
+
+    let result = concat(per_week, UnionArgs::default())
+        .context("concatenating weekly spending")?
+        .sort(["_week_position", key], SortMultipleOptions::default())
+        .select([col("week_number"), col(key), col("spent")]);
+
+    Ok(result)
+}
+
+/// Differences in a numeric column up to this size between two report files are
+/// treated as rounding, not changes.
+pub const CSV_DIFF_TOLERANCE: f64 = 1e-2;
+
+/// Two report CSVs compared row by row on `category_name`. `added` and `removed` are the
+/// rows of the after and before files with no counterpart, with that file's columns.
+/// `changed` has `category_name` and a `{column}_before`/`{column}_after` pair for each
+/// numeric column in both files, for categories where any pair differs by more than
+/// [`CSV_DIFF_TOLERANCE`] (or is null on one side only). All three are sorted by
+/// `category_name`.
+#[derive(Debug, Clone)]
+pub struct CsvDiff {
+    pub added: DataFrame,
+    pub removed: DataFrame,
+    pub changed: DataFrame,
+}
+
+fn category_names_series(frame: &LazyFrame) -> Result<Series> {
+    Ok(frame
+        .clone()
+        .select([col("category_name").cast(DataType::String)])
+        .collect()
+        .context("collecting category names")?
+        .column("category_name")
+        .context("category_name column missing")?
+        .as_materialized_series()
+        .clone())
+}
+
+fn rows_without_counterpart(frame: LazyFrame, other: &LazyFrame) -> Result<DataFrame> {
+    frame
+        .filter(
+            col("category_name")
+                .cast(DataType::String)
+                .is_in(lit(category_names_series(other)?))
+                .not(),
+        )
+        .sort(["category_name"], SortMultipleOptions::default())
+        .collect()
+        .context("collecting unmatched report rows")
+}
+
+fn suffixed_numeric_columns(frame: LazyFrame, columns: &[String], suffix: &str) -> LazyFrame {
+    frame.select(
+        std::iter::once(col("category_name").cast(DataType::String))
+            .chain(columns.iter().map(|name| {
+                col(name.as_str())
+                    .cast(DataType::Float64)
+                    .alias(format!("{name}_{suffix}"))
+            }))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn value_changed(before: Expr, after: Expr) -> Expr {
+    let difference = before.clone() - after.clone();
+    difference
+        .clone()
+        .gt(lit(CSV_DIFF_TOLERANCE))
+        .or(difference.lt(lit(-CSV_DIFF_TOLERANCE)))
+        .fill_null(lit(false))
+        .or(before.is_null().neq(after.is_null()))
+}
+
+/// Compares the report CSVs at `before` and `after` (as written by `csv_output`, in the
+/// long layout); see [`CsvDiff`]. Both must have a `category_name` column.
+#[tracing::instrument(level = "debug")]
+pub fn diff_csv_reports(before: &Path, after: &Path) -> Result<CsvDiff> {
+    let (before_frame, before_schema) = scan_csv_with_columns(before, &["category_name"])?;
+    let (after_frame, after_schema) = scan_csv_with_columns(after, &["category_name"])?;
+    let numeric_columns: Vec<String> = before_schema
+        .iter()
+        .filter(|(name, dtype)| {
+            name.as_str() != "category_name"
+                && dtype.is_primitive_numeric()
+                && after_schema
+                    .get(name)
+                    .is_some_and(DataType::is_primitive_numeric)
+        })
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    let any_changed = numeric_columns
+        .iter()
+        .map(|name| value_changed(col(format!("{name}_before")), col(format!("{name}_after"))))
+        .fold(lit(false), Expr::or);
+    let changed = suffixed_numeric_columns(before_frame.clone(), &numeric_columns, "before")
+        .join(
+            suffixed_numeric_columns(after_frame.clone(), &numeric_columns, "after"),
+            [col("category_name")],
+            [col("category_name")],
+            JoinArgs::new(JoinType::Inner),
+        )
+        .filter(any_changed)
+        .sort(["category_name"], SortMultipleOptions::default())
+        .collect()
+        .context("collecting changed report rows")?;
+
+    Ok(CsvDiff {
+        added: rows_without_counterpart(after_frame.clone(), &before_frame)?,
+        removed: rows_without_counterpart(before_frame, &after_frame)?,
+        changed,
+    })
+}
+
+/// One line of a [`CsvDiff`]: a whole added or removed category, or one changed column
+/// of a category in both files.
+struct CsvDiffEntry {
+    change: &'static str,
+    category_name: String,
+    column: Option<String>,
+    before: Option<f64>,
+    after: Option<f64>,
+}
+
+fn frame_category_names(df: &DataFrame) -> Result<Vec<String>> {
+    Ok(df
+        .column("category_name")
+        .context("category_name column missing")?
+        .cast(&DataType::String)
+        .context("casting category_name to string")?
+        .str()
+        .context("category_name is not a string column")?
+        .into_iter()
+        .map(|name| name.unwrap_or_default().to_string())
+        .collect())
+}
+
+fn f64_values(df: &DataFrame, name: &str) -> Result<Vec<Option<f64>>> {
+    Ok(df
+        .column(name)
+        .with_context(|| format!("{name} column missing"))?
+        .f64()
+        .with_context(|| format!("{name} is not a float column"))?
+        .into_iter()
+        .collect())
+}
+
+fn csv_diff_entries(diff: &CsvDiff) -> Result<Vec<CsvDiffEntry>> {
+    let whole_rows = |df: &DataFrame, change: &'static str| -> Result<Vec<CsvDiffEntry>> {
+        Ok(frame_category_names(df)?
+            .into_iter()
+            .map(|category_name| CsvDiffEntry {
+                change,
+                category_name,
+                column: None,
+                before: None,
+                after: None,
+            })
+            .collect())
+    };
+    let columns: Vec<String> = diff
+        .changed
+        .get_column_names()
+        .iter()
+        .filter_map(|name| name.strip_suffix("_before").map(str::to_string))
+        .collect();
+    let values = columns
+        .iter()
+        .map(|name| {
+            Ok((
+                name,
+                f64_values(&diff.changed, &format!("{name}_before"))?,
+                f64_values(&diff.changed, &format!("{name}_after"))?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let changed = frame_category_names(&diff.changed)?
+        .into_iter()
+        .enumerate()
+        .flat_map(|(row, category_name)| {
+            values
+                .iter()
+                .filter(move |(_, before, after)| {
+                    let (before, after) = (before[row], after[row]);
+                    match (before, after) {
+                        (Some(before), Some(after)) => (before - after).abs() > CSV_DIFF_TOLERANCE,
+                        _ => before.is_some() != after.is_some(),
+                    }
+                })
+                .map(move |(name, before, after)| CsvDiffEntry {
+                    change: "changed",
+                    category_name: category_name.clone(),
+                    column: Some(name.to_string()),
+                    before: before[row],
+                    after: after[row],
+                })
+        });
+
+    Ok(whole_rows(&diff.removed, "removed")?
+        .into_iter()
+        .chain(whole_rows(&diff.added, "added")?)
+        .chain(changed)
+        .collect())
+}
+
+/// `diff` as one row per difference, for writing as CSV: `change` ("removed", "added"
+/// or "changed"), `category_name`, and for changes the `column` with its `before` and
+/// `after` values.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn csv_diff_table(diff: &CsvDiff) -> Result<DataFrame> {
+    let entries = csv_diff_entries(diff)?;
+    DataFrame::new(vec![
+        Column::new(
+            "change".into(),
+            entries.iter().map(|entry| entry.change).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "category_name".into(),
+            entries
+                .iter()
+                .map(|entry| entry.category_name.as_str())
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "column".into(),
+            entries
+                .iter()
+                .map(|entry| entry.column.as_deref())
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "before".into(),
+            entries.iter().map(|entry| entry.before).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "after".into(),
+            entries.iter().map(|entry| entry.after).collect::<Vec<_>>(),
+        ),
+    ])
+    .context("building CSV diff table")
+}
+
+/// `diff` as text, one line per difference: `- name` for removed categories, `+ name`
+/// for added ones and `~ name: column before -> after` for changed values, in red, green
+/// and yellow when `color` is set.
+#[tracing::instrument(level = "debug", skip(diff))]
+pub fn format_csv_diff(diff: &CsvDiff, color: bool) -> Result<String> {
+    let format_value = |value: Option<f64>| value.map_or("null".to_string(), |v| format!("{v:.2}"));
+    Ok(csv_diff_entries(diff)?
+        .iter()
+        .map(|entry| {
+            let (marker, ansi) = match entry.change {
+                "removed" => ("-", "31"),
+                "added" => ("+", "32"),
+                _ => ("~", "33"),
+            };
+            let line = match &entry.column {
+                Some(column) => format!(
+                    "{marker} {}: {column} {} -> {}",
+                    entry.category_name,
+                    format_value(entry.before),
+                    format_value(entry.after)
+                ),
+                None => format!("{marker} {}", entry.category_name),
+            };
+            if color {
+                format!("\x1b[{ansi}m{line}\x1b[0m\n")
+            } else {
+                format!("{line}\n")
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::calendar_weeks::MonthWeek;
+
+/// Describes the period and budget a report covers, so output files are self-describing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportMetadata {
+    pub week_number: usize,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    pub generated_at: DateTime<Utc>,
+    pub budget_name: String,
+}
+
+impl ReportMetadata {
+    /// `generated_at` is truncated to whole seconds.
+    pub fn new(week: &MonthWeek, budget_name: &str, generated_at: DateTime<Utc>) -> Self {
+        Self {
+            week_number: week.week_number,
+            week_start: week.week_start,
+            week_end: week.week_end,
+            generated_at: generated_at.trunc_subsecs(0),
+            budget_name: budget_name.to_string(),
+        }
+    }
+
+    /// One `<prefix>key: value` line per field, e.g. `# week_number: 3` for CSV or
+    /// `-- week_number: 3` for SQL.
+    pub fn header_lines(&self, prefix: &str) -> String {
+        [
+            ("week_number", self.week_number.to_string()),
+            ("week_start", self.week_start.to_string()),
+            ("week_end", self.week_end.to_string()),
+            (
+                "generated_at",
+                self.generated_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            ),
+            ("budget_name", self.budget_name.replace(['\r', '\n'], " ")),
+        ]
+        .iter()
+        .map(|(key, value)| format!("{prefix}{key}: {value}\n"))
+        .collect()
+    }
+
+    /// Reads back the lines written by [`ReportMetadata::header_lines`]; lines without
+    /// `prefix` are ignored.
+    pub fn from_header_lines(text: &str, prefix: &str) -> Result<Self> {
+        let fields: Vec<(&str, &str)> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix(prefix)?.split_once(": "))
+            .collect();
+        let field = |key: &str| -> Result<&str> {
+            fields
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| *value)
+                .with_context(|| format!("report metadata is missing {key}"))
+        };
+        let date = |key: &str| -> Result<NaiveDate> {
+            field(key)?
+                .parse()
+                .with_context(|| format!("parsing metadata {key}"))
+        };
+
+        Ok(Self {
+            week_number: field("week_number")?
+                .parse()
+                .context("parsing metadata week_number")?,
+            week_start: date("week_start")?,
+            week_end: date("week_end")?,
+            generated_at: field("generated_at")?
+                .parse()
+                .context("parsing metadata generated_at")?,
+            budget_name: field("budget_name")?.to_string(),
+        })
+    }
+}
@@ -0,0 +1,273 @@
+//! Local snapshots of a budget's categories and transactions for YNAB delta requests.
+//! YNAB answers a request carrying `last_knowledge_of_server` with only the entities
+//! changed since then, so the snapshot from the previous run is patched with the delta
+//! instead of downloading everything again.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::atomic_file::write_file_atomically;
+
+/// The categories endpoint's entities as of `server_knowledge`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CategoriesSnapshot {
+    server_knowledge: i64,
+    category_groups: Vec<Value>,
+}
+
+/// The transactions endpoint's entities dated `since_date` or later, as of
+/// `server_knowledge`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TransactionsSnapshot {
+    server_knowledge: i64,
+    since_date: NaiveDate,
+    transactions: Vec<Value>,
+}
+
+/// A category YNAB returned, as recorded for `onMissingCategory` so a later run can
+/// name the ones that have since disappeared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownCategory {
+    pub group: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BudgetSnapshot {
+    #[serde(default)]
+    categories: Option<CategoriesSnapshot>,
+    #[serde(default)]
+    transactions: Option<TransactionsSnapshot>,
+    #[serde(default)]
+    known_categories: Option<BTreeMap<String, KnownCategory>>,
+}
+
+/// A JSON file of per-budget snapshots, keyed by budget ID, each holding the last
+/// `server_knowledge` YNAB returned for an endpoint and the entities merged so far.
+#[derive(Debug, Clone)]
+pub struct ServerKnowledgeCache {
+    path: PathBuf,
+}
+
+fn id_of(entity: &Value) -> Option<&str> {
+    entity.get("id").and_then(Value::as_str)
+}
+
+fn is_deleted(entity: &Value) -> bool {
+    entity
+        .get("deleted")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// `entities` with each of `changes` replacing the entity with the same `id`, or
+/// appended when it is new.
+fn upsert_by_id(entities: Vec<Value>, changes: Vec<Value>) -> Vec<Value> {
+    changes.into_iter().fold(entities, |mut entities, change| {
+        match entities
+            .iter_mut()
+            .find(|entity| id_of(entity).is_some() && id_of(entity) == id_of(&change))
+        {
+            Some(entity) => *entity = change,
+            None => entities.push(change),
+        }
+        entities
+    })
+}
+
+/// `groups` patched with the changed `delta` groups: a delta group replaces the group's
+/// own fields, while its `categories` (only the changed ones) are merged into the
+/// group's.
+fn merge_category_groups(groups: Vec<Value>, delta: Vec<Value>) -> Vec<Value> {
+    delta.into_iter().fold(groups, |mut groups, mut change| {
+        match groups
+            .iter_mut()
+            .find(|group| id_of(group).is_some() && id_of(group) == id_of(&change))
+        {
+            Some(group) => {
+                let categories = |group: &mut Value| match group.get_mut("categories") {
+                    Some(Value::Array(categories)) => std::mem::take(categories),
+                    _ => Vec::new(),
+                };
+                let merged = upsert_by_id(categories(group), categories(&mut change));
+                if let Some(object) = change.as_object_mut() {
+                    object.insert("categories".to_string(), Value::Array(merged));
+                }
+                *group = change;
+            }
+            None => groups.push(change),
+        }
+        groups
+    })
+}
+
+fn dated_on_or_after(transaction: &Value, since_date: NaiveDate) -> bool {
+    transaction
+        .get("date")
+        .and_then(Value::as_str)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .is_some_and(|date| date >= since_date)
+}
+
+impl ServerKnowledgeCache {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every budget's snapshot; none before the file is first written, or when it cannot
+    /// be parsed, so a damaged cache costs one full fetch and is then rewritten.
+    fn load(&self) -> Result<BTreeMap<String, BudgetSnapshot>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let contents = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("reading server knowledge cache {:?}", self.path))?;
+        match serde_json::from_str(&contents) {
+            Ok(snapshots) => Ok(snapshots),
+            Err(err) => {
+                eprintln!(
+                    "Warning: discarding unreadable server knowledge cache {:?}: {err}",
+                    self.path
+                );
+                Ok(BTreeMap::new())
+            }
+        }
+    }
+
+    fn store(&self, snapshots: &BTreeMap<String, BudgetSnapshot>) -> Result<()> {
+        let contents =
+            serde_json::to_string(snapshots).context("serializing server knowledge cache")?;
+        write_file_atomically(&self.path, |writer| {
+            writer.write_all(contents.as_bytes())?;
+            Ok(())
+        })
+        .with_context(|| format!("writing server knowledge cache {:?}", self.path))
+    }
+
+    /// Applies `update` to `budget_id`'s snapshot and writes the file back.
+    fn update<T>(
+        &self,
+        budget_id: &str,
+        update: impl FnOnce(&mut BudgetSnapshot) -> T,
+    ) -> Result<T> {
+        let mut snapshots = self.load()?;
+        let result = update(snapshots.entry(budget_id.to_string()).or_default());
+        self.store(&snapshots)?;
+        Ok(result)
+    }
+
+    /// The `last_knowledge_of_server` to send for `budget_id`'s categories.
+    pub fn categories_knowledge(&self, budget_id: &str) -> Result<Option<i64>> {
+        Ok(self
+            .load()?
+            .get(budget_id)
+            .and_then(|snapshot| snapshot.categories.as_ref())
+            .map(|categories| categories.server_knowledge))
+    }
+
+    /// Records a categories response: `category_groups` replace the snapshot unless the
+    /// response `is_delta`, in which case they are merged into it. Returns the groups
+    /// as of `server_knowledge`.
+    pub fn sync_categories(
+        &self,
+        budget_id: &str,
+        server_knowledge: i64,
+        category_groups: Vec<Value>,
+        is_delta: bool,
+    ) -> Result<Vec<Value>> {
+        self.update(budget_id, |snapshot| {
+            let category_groups = match (snapshot.categories.take(), is_delta) {
+                (Some(cached), true) => {
+                    merge_category_groups(cached.category_groups, category_groups)
+                }
+                _ => category_groups,
+            };
+            snapshot.categories = Some(CategoriesSnapshot {
+                server_knowledge,
+                category_groups: category_groups.clone(),
+            });
+            category_groups
+        })
+    }
+
+    /// The `(last_knowledge_of_server, since_date)` to request `budget_id`'s
+    /// transactions from `since_date` with: set only when the snapshot reaches back that
+    /// far, and then its own (possibly earlier) `since_date`, so the delta covers every
+    /// cached transaction.
+    pub fn transactions_knowledge(
+        &self,
+        budget_id: &str,
+        since_date: NaiveDate,
+    ) -> Result<Option<(i64, NaiveDate)>> {
+        Ok(self
+            .load()?
+            .get(budget_id)
+            .and_then(|snapshot| snapshot.transactions.as_ref())
+            .filter(|transactions| transactions.since_date <= since_date)
+            .map(|transactions| (transactions.server_knowledge, transactions.since_date)))
+    }
+
+    /// Records a transactions response requested from `fetched_since`: `transactions`
+    /// replace the snapshot unless the response `is_delta`, in which case they are
+    /// merged into it and those YNAB marks deleted are dropped. Returns the snapshot's
+    /// transactions dated `since_date` or later.
+    pub fn sync_transactions(
+        &self,
+        budget_id: &str,
+        fetched_since: NaiveDate,
+        server_knowledge: i64,
+        transactions: Vec<Value>,
+        is_delta: bool,
+        since_date: NaiveDate,
+    ) -> Result<Vec<Value>> {
+        self.update(budget_id, |snapshot| {
+            let merged: Vec<Value> = match (snapshot.transactions.take(), is_delta) {
+                (Some(cached), true) => upsert_by_id(cached.transactions, transactions),
+                _ => transactions,
+            }
+            .into_iter()
+            .filter(|transaction| !is_deleted(transaction))
+            .collect();
+            let requested = merged
+                .iter()
+                .filter(|transaction| dated_on_or_after(transaction, since_date))
+                .cloned()
+                .collect();
+            snapshot.transactions = Some(TransactionsSnapshot {
+                server_knowledge,
+                since_date: fetched_since,
+                transactions: merged,
+            });
+            requested
+        })
+    }
+
+    /// The categories last recorded for `budget_id` by `record_known_categories`, keyed
+    /// by ID; none before the first record.
+    pub fn known_categories(
+        &self,
+        budget_id: &str,
+    ) -> Result<Option<BTreeMap<String, KnownCategory>>> {
+        Ok(self
+            .load()?
+            .get(budget_id)
+            .and_then(|snapshot| snapshot.known_categories.clone()))
+    }
+
+    /// Replaces the categories recorded for `budget_id`.
+    pub fn record_known_categories(
+        &self,
+        budget_id: &str,
+        categories: BTreeMap<String, KnownCategory>,
+    ) -> Result<()> {
+        self.update(budget_id, |snapshot| {
+            snapshot.known_categories = Some(categories);
+        })
+    }
+}
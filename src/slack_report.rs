@@ -0,0 +1,177 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use polars::prelude::*;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::{Value, json};
+
+use crate::visual_report::format_currency;
+
+/// Slack rejects section blocks with more than this many fields.
+const MAX_SECTION_FIELDS: usize = 10;
+/// Slack rejects header blocks whose text is longer than this.
+const MAX_HEADER_CHARS: usize = 150;
+/// Slack rejects messages with more than this many blocks.
+const MAX_MESSAGE_BLOCKS: usize = 50;
+
+/// Escapes the characters Slack's mrkdwn treats as control sequences.
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn header_block(text: &str) -> Value {
+    let text: String = text.chars().take(MAX_HEADER_CHARS).collect();
+    json!({"type": "header", "text": {"type": "plain_text", "text": text}})
+}
+
+fn text_section(markdown: &str) -> Value {
+    json!({"type": "section", "text": {"type": "mrkdwn", "text": markdown}})
+}
+
+/// Section blocks laying `rows` out as two columns (label, value), split so no block
+/// exceeds [`MAX_SECTION_FIELDS`].
+fn two_column_sections(rows: &[(String, String)]) -> Vec<Value> {
+    rows.chunks(MAX_SECTION_FIELDS / 2)
+        .map(|chunk| {
+            let fields: Vec<Value> = chunk
+                .iter()
+                .flat_map(|(label, value)| {
+                    [
+                        json!({"type": "mrkdwn", "text": escape_mrkdwn(label)}),
+                        json!({"type": "mrkdwn", "text": value}),
+                    ]
+                })
+                .collect();
+            json!({"type": "section", "fields": fields})
+        })
+        .collect()
+}
+
+fn str_column<'a>(df: &'a DataFrame, name: &str) -> Result<&'a StringChunked> {
+    df.column(name)
+        .with_context(|| format!("{name} column"))?
+        .str()
+        .with_context(|| format!("{name} as str"))
+}
+
+fn f64_column<'a>(df: &'a DataFrame, name: &str) -> Result<&'a Float64Chunked> {
+    df.column(name)
+        .with_context(|| format!("{name} column"))?
+        .f64()
+        .with_context(|| format!("{name} as f64"))
+}
+
+/// Each group's categories in report-table order, paired with their formatted outflow.
+fn category_rows_by_group(
+    report_df: &DataFrame,
+) -> Result<IndexMap<String, Vec<(String, String)>>> {
+    let groups = str_column(report_df, "category_group_name")?;
+    let names = str_column(report_df, "category_name")?;
+    let spent = f64_column(report_df, "spent")?;
+
+    Ok(groups.iter().zip(names.iter()).zip(spent.iter()).fold(
+        IndexMap::new(),
+        |mut acc: IndexMap<String, Vec<(String, String)>>, ((group, name), spent)| {
+            if let (Some(group), Some(name)) = (group, name) {
+                acc.entry(group.to_string()).or_default().push((
+                    name.to_string(),
+                    format_currency(-spent.unwrap_or(0.0), true),
+                ));
+            }
+            acc
+        },
+    ))
+}
+
+fn totals_rows(totals_df: &DataFrame) -> Result<Vec<(String, String)>> {
+    let groups = str_column(totals_df, "category_group_name")?;
+    let budgeted = f64_column(totals_df, "budgeted")?;
+    let spent = f64_column(totals_df, "spent")?;
+
+    Ok(groups
+        .iter()
+        .zip(budgeted.iter())
+        .zip(spent.iter())
+        .filter_map(|((group, budgeted), spent)| {
+            Some((
+                group?.to_string(),
+                format!(
+                    "spent {} of {}",
+                    format_currency(-spent.unwrap_or(0.0), true),
+                    format_currency(budgeted.unwrap_or(0.0), true),
+                ),
+            ))
+        })
+        .collect())
+}
+
+/// Slack Block Kit payloads for the report, one per message: per category group a bold
+/// title and two-column category/outflow fields, then a divider and the group totals
+/// from `totals_df`. The blocks are split so no message exceeds [`MAX_MESSAGE_BLOCKS`];
+/// each message starts with a header of `week_label`, marked "(continued)" after the
+/// first.
+pub fn report_to_slack_blocks(
+    report_df: &DataFrame,
+    totals_df: &DataFrame,
+    week_label: &str,
+) -> Result<Vec<Value>> {
+    let group_blocks = category_rows_by_group(report_df)?
+        .into_iter()
+        .flat_map(|(group, rows)| {
+            std::iter::once(text_section(&format!("*{}*", escape_mrkdwn(&group))))
+                .chain(two_column_sections(&rows))
+        });
+    let totals_blocks = [
+        json!({"type": "divider"}),
+        header_block("Category group totals"),
+    ]
+    .into_iter()
+    .chain(two_column_sections(&totals_rows(totals_df)?));
+
+    let body: Vec<Value> = group_blocks.chain(totals_blocks).collect();
+    Ok(body
+        .chunks(MAX_MESSAGE_BLOCKS - 1)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let label = if index == 0 {
+                week_label.to_string()
+            } else {
+                format!("{week_label} (continued)")
+            };
+            let blocks: Vec<Value> = std::iter::once(header_block(&label))
+                .chain(chunk.iter().cloned())
+                .collect();
+            json!({"text": label, "blocks": blocks})
+        })
+        .collect())
+}
+
+/// POSTs `payload` to a Slack incoming webhook, failing on any non-success status.
+pub fn post_slack_blocks(webhook_url: &str, payload: &Value) -> Result<()> {
+    let body = serde_json::to_string(payload).context("serializing Slack payload")?;
+    // reqwest needs a Tokio reactor, so the request is driven on a private runtime
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("building Tokio runtime for Slack webhook")?;
+
+    let (status, response_body) = runtime
+        .block_on(async {
+            let response = reqwest::Client::new()
+                .post(webhook_url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await?;
+            let status = response.status();
+            let text = response.text().await?;
+            Ok::<_, reqwest::Error>((status, text))
+        })
+        .context("posting report to Slack webhook")?;
+
+    if !status.is_success() {
+        anyhow::bail!("Slack webhook returned {status}: {response_body}");
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use polars::prelude::*;
+
+pub const REPORT_TABLE: &str = "category_report";
+pub const TOTALS_TABLE: &str = "category_group_totals";
+pub const INCOME_TABLE: &str = "category_income";
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_text(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn sql_type(dtype: &DataType) -> &'static str {
+    match dtype {
+        DataType::Float32 | DataType::Float64 => "REAL",
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => "INTEGER",
+        _ => "TEXT",
+    }
+}
+
+fn sql_literal(value: AnyValue) -> String {
+    match value {
+        AnyValue::Null => "NULL".to_string(),
+        AnyValue::Boolean(b) => if b { "1" } else { "0" }.to_string(),
+        AnyValue::Float32(f) if f.is_finite() => f.to_string(),
+        AnyValue::Float64(f) if f.is_finite() => f.to_string(),
+        AnyValue::Float32(_) | AnyValue::Float64(_) => "NULL".to_string(),
+        AnyValue::Int8(_)
+        | AnyValue::Int16(_)
+        | AnyValue::Int32(_)
+        | AnyValue::Int64(_)
+        | AnyValue::UInt8(_)
+        | AnyValue::UInt16(_)
+        | AnyValue::UInt32(_)
+        | AnyValue::UInt64(_) => value.to_string(),
+        AnyValue::String(s) => quote_text(s),
+        AnyValue::StringOwned(s) => quote_text(&s),
+        other => quote_text(&other.to_string()),
+    }
+}
+
+/// Emits `CREATE TABLE IF NOT EXISTS` plus one `INSERT OR REPLACE` per row of `df`.
+///
+/// Every row is stamped with `report_date`, which joins `key_columns` in the primary key
+/// so re-running a report for the same date replaces its rows instead of duplicating them.
+pub fn write_sql_inserts(
+    table: &str,
+    df: &DataFrame,
+    report_date: NaiveDate,
+    key_columns: &[&str],
+) -> Result<String> {
+    let table_name = quote_identifier(table);
+    let columns = df.get_columns();
+
+    let column_defs: Vec<String> = std::iter::once("\"report_date\" TEXT NOT NULL".to_string())
+        .chain(columns.iter().map(|c| {
+            format!(
+                "{} {}",
+                quote_identifier(c.name().as_str()),
+                sql_type(c.dtype())
+            )
+        }))
+        .collect();
+    let primary_key: Vec<String> = std::iter::once("report_date")
+        .chain(key_columns.iter().copied())
+        .map(quote_identifier)
+        .collect();
+    let column_names: Vec<String> = std::iter::once("report_date")
+        .chain(columns.iter().map(|c| c.name().as_str()))
+        .map(quote_identifier)
+        .collect();
+
+    let mut statements = vec![format!(
+        "CREATE TABLE IF NOT EXISTS {table_name} (\n  {},\n  PRIMARY KEY ({})\n);",
+        column_defs.join(",\n  "),
+        primary_key.join(", ")
+    )];
+
+    let date_literal = quote_text(&report_date.format("%Y-%m-%d").to_string());
+    for row in 0..df.height() {
+        let values = std::iter::once(Ok(date_literal.clone()))
+            .chain(columns.iter().map(|c| {
+                c.get(row)
+                    .map(sql_literal)
+                    .with_context(|| format!("reading {} row {row}", c.name()))
+            }))
+            .collect::<Result<Vec<_>>>()?;
+        statements.push(format!(
+            "INSERT OR REPLACE INTO {table_name} ({}) VALUES ({});",
+            column_names.join(", "),
+            values.join(", ")
+        ));
+    }
+
+    Ok(format!("{}\n", statements.join("\n")))
+}
+
+/// Inserts for the report and totals tables, plus [`INCOME_TABLE`] when `income_df`
+/// (from `report::build_income_table`) is given.
+pub fn build_sql_report(
+    report_df: &DataFrame,
+    totals_df: &DataFrame,
+    income_df: Option<&DataFrame>,
+    report_date: NaiveDate,
+) -> Result<String> {
+    let report_sql = write_sql_inserts(
+        REPORT_TABLE,
+        report_df,
+        report_date,
+        &["category_group_name", "category_name"],
+    )?;
+    let totals_sql = write_sql_inserts(
+        TOTALS_TABLE,
+        totals_df,
+        report_date,
+        &["category_group_name"],
+    )?;
+    let income_sql = income_df
+        .map(|df| write_sql_inserts(INCOME_TABLE, df, report_date, &["category_name"]))
+        .transpose()?
+        .unwrap_or_default();
+    Ok(format!("{report_sql}{totals_sql}{income_sql}"))
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+use crate::report::{GroupTotals, group_totals, overall_totals};
+use crate::visual_report::format_currency;
+
+pub const MAX_LINE_WIDTH: usize = 80;
+
+/// Shortens `text` to at most `width` characters, ending with `...` when cut.
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(3)).collect();
+    format!("{kept}...")
+}
+
+/// `label` on the left, `amount` on the right, truncating the label so the line fits.
+fn labelled_line(label: &str, amount: &str) -> String {
+    let label_width = MAX_LINE_WIDTH.saturating_sub(amount.chars().count() + 1);
+    let label = truncate_to_width(label, label_width);
+    let padding = MAX_LINE_WIDTH - label.chars().count() - amount.chars().count();
+    format!("{label}{}{amount}", " ".repeat(padding))
+}
+
+fn totals_line(totals: &GroupTotals) -> String {
+    format!(
+        "spent {} of {} (balance {})",
+        format_currency(-totals.spent, true),
+        format_currency(totals.budgeted, true),
+        format_currency(totals.balance, true),
+    )
+}
+
+/// Categories grouped by `category_group_name` in report-table order, with each
+/// category's outflow.
+fn spending_by_group(df: &DataFrame) -> Result<IndexMap<String, Vec<(String, f64)>>> {
+    let groups = df
+        .column("category_group_name")
+        .context("category_group_name column")?
+        .str()
+        .context("category_group_name as str")?;
+    let names = df
+        .column("category_name")
+        .context("category_name column")?
+        .str()
+        .context("category_name as str")?;
+    let spent = df
+        .column("spent")
+        .context("spent column")?
+        .f64()
+        .context("spent as f64")?;
+
+    Ok(groups.iter().zip(names.iter()).zip(spent.iter()).fold(
+        IndexMap::new(),
+        |mut acc: IndexMap<String, Vec<(String, f64)>>, ((group, name), spent)| {
+            if let (Some(group), Some(name)) = (group, name) {
+                acc.entry(group.to_string())
+                    .or_default()
+                    .push((name.to_string(), spent.unwrap_or(0.0)));
+            }
+            acc
+        },
+    ))
+}
+
+/// "Income" section listing each category's inflow from `report::build_income_table`.
+fn income_lines(income_table: &LazyFrame) -> Result<Vec<String>> {
+    let df = income_table
+        .clone()
+        .collect()
+        .context("collecting income table for summary")?;
+    let names = df
+        .column("category_name")
+        .context("income category_name column")?
+        .str()
+        .context("income category_name as str")?;
+    let income = df
+        .column("income")
+        .context("income column")?
+        .f64()
+        .context("income as f64")?;
+
+    Ok(std::iter::once("Income".to_string())
+        .chain(names.iter().zip(income.iter()).map(|(name, amount)| {
+            labelled_line(
+                &format!("  - {}", name.unwrap_or("")),
+                &format_currency(amount.unwrap_or(0.0), true),
+            )
+        }))
+        .chain(std::iter::once(labelled_line(
+            "  Total",
+            &format_currency(income.sum().unwrap_or(0.0), true),
+        )))
+        .chain(std::iter::once(String::new()))
+        .collect())
+}
+
+/// Renders a plain-text summary of the report table: one section per category group
+/// listing the categories that saw spending, an optional income section, and a grand
+/// total. Every line fits in [`MAX_LINE_WIDTH`] characters.
+pub fn build_summary(
+    report_table: &LazyFrame,
+    week_label: &str,
+    income_table: Option<&LazyFrame>,
+) -> Result<String> {
+    let df = report_table
+        .clone()
+        .collect()
+        .context("collecting report table for summary")?;
+
+    let mut lines = vec![truncate_to_width(week_label, MAX_LINE_WIDTH), String::new()];
+    for (group_name, categories) in spending_by_group(&df)? {
+        let totals = group_totals(report_table, &group_name)?;
+        lines.push(truncate_to_width(&group_name, MAX_LINE_WIDTH));
+        lines.push(truncate_to_width(
+            &format!("  Total: {}", totals_line(&totals)),
+            MAX_LINE_WIDTH,
+        ));
+        lines.extend(
+            categories
+                .iter()
+                .filter(|(_, spent)| *spent != 0.0)
+                .map(|(name, spent)| {
+                    labelled_line(&format!("  - {name}"), &format_currency(-spent, true))
+                }),
+        );
+        lines.push(String::new());
+    }
+
+    if let Some(income_table) = income_table {
+        lines.extend(income_lines(income_table)?);
+    }
+
+    let overall = overall_totals(report_table)?;
+    lines.push("Grand Total".to_string());
+    lines.extend(
+        [
+            ("  Budgeted", overall.budgeted),
+            ("  Spent", -overall.spent),
+            ("  Balance", overall.balance),
+        ]
+        .iter()
+        .map(|(label, value)| labelled_line(label, &format_currency(*value, true))),
+    );
+
+    Ok(format!("{}\n", lines.join("\n")))
+}
+
+pub fn write_summary(
+    path: &Path,
+    report_table: &LazyFrame,
+    week_label: &str,
+    income_table: Option<&LazyFrame>,
+) -> Result<()> {
+    let summary = build_summary(report_table, week_label, income_table)?;
+    std::fs::write(path, summary).with_context(|| format!("writing {path:?}"))
+}
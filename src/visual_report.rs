@@ -1,56 +1,207 @@
+use std::io::Write;
+
 use anyhow::{Context, Result};
 use indexmap::IndexMap;
 use polars::prelude::*;
 
-pub const CURRENCY: &str = "£";
+use crate::goals::GoalTracker;
+use crate::report::{TransactionFrame, resolve_group_color};
+
+pub use crate::currency::{CURRENCY, CurrencyFormat};
 
+/// `value` in pounds to 2 decimal places, or an empty string when it rounds to zero
+/// and `show_zero` is unset.
 pub fn format_currency(value: f64, show_zero: bool) -> String {
-    let rounded = (value * 100.0).round() / 100.0;
+    format_currency_with(value, show_zero, &CurrencyFormat::gbp(), 2)
+}
+
+/// `value` in `format` rounded to `precision` decimal places; with a precision of 0
+/// there is no decimal separator. Empty when the rounded value is zero and `show_zero`
+/// is unset.
+pub fn format_currency_with(
+    value: f64,
+    show_zero: bool,
+    format: &CurrencyFormat,
+    precision: u8,
+) -> String {
+    let scale = 10_f64.powi(i32::from(precision));
+    let rounded = (value * scale).round() / scale;
     if rounded == 0.0 && !show_zero {
         return String::new();
     }
     let sign = if rounded < 0.0 { "-" } else { "" };
-    let abs_val = rounded.abs();
-    format!("{sign}{CURRENCY}{}", format_with_commas(abs_val))
+    let digits = group_digits(
+        rounded,
+        u32::from(precision),
+        &format.group_separator,
+        &format.decimal_separator,
+    );
+    format!("{sign}{}{digits}", format.symbol)
 }
 
-fn format_with_commas(value: f64) -> String {
-    let formatted = format!("{:.2}", value);
-    let (integer_part, decimal_part) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+/// Reads an amount written by [`format_currency_with`] in `format` back into a number:
+/// the sign, symbol and group separators are dropped and the decimal separator read as
+/// a point.
+pub fn parse_currency(s: &str, format: &CurrencyFormat) -> Result<f64> {
+    let trimmed = s.trim();
+    let (negative, unsigned) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    let without_symbol = unsigned
+        .strip_prefix(format.symbol.as_str())
+        .unwrap_or(unsigned);
+    let without_groups = if format.group_separator.is_empty() {
+        without_symbol.to_string()
+    } else {
+        without_symbol.replace(format.group_separator.as_str(), "")
+    };
+    let normalized = if format.decimal_separator.is_empty() {
+        without_groups
+    } else {
+        without_groups.replace(format.decimal_separator.as_str(), ".")
+    };
+    let magnitude = normalized
+        .parse::<f64>()
+        .with_context(|| format!("Invalid currency amount: {s:?}"))?;
+    Ok(if negative { -magnitude } else { magnitude })
+}
 
-    let chars: Vec<char> = integer_part.chars().collect();
-    let with_commas: String = chars
-        .iter()
-        .rev()
-        .enumerate()
-        .fold(Vec::new(), |mut acc, (i, &c)| {
-            if i > 0 && i % 3 == 0 {
-                acc.push(',');
-            }
-            acc.push(c);
-            acc
-        })
-        .into_iter()
+/// Formats the absolute value of `value` with thousands separators; callers add any sign.
+/// With no decimal places there is no decimal point.
+pub fn format_with_commas(value: f64, decimal_places: u32) -> String {
+    group_digits(value, decimal_places, ",", ".")
+}
+
+fn group_digits(
+    value: f64,
+    decimal_places: u32,
+    group_separator: &str,
+    decimal_separator: &str,
+) -> String {
+    let formatted = format!("{:.*}", decimal_places as usize, value.abs());
+    let (integer_part, decimal_part) = match formatted.split_once('.') {
+        Some((integer_part, decimal_part)) => (integer_part, Some(decimal_part)),
+        None => (formatted.as_str(), None),
+    };
+
+    let digits: Vec<char> = integer_part.chars().collect();
+    let with_groups: String = digits
+        .rchunks(3)
         .rev()
-        .collect();
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(group_separator);
 
-    format!("{with_commas}.{decimal_part}")
+    match decimal_part {
+        Some(decimal_part) => format!("{with_groups}{decimal_separator}{decimal_part}"),
+        None => with_groups,
+    }
 }
 
-pub fn darken_hex(color: &str, factor: f64) -> String {
-    if !color.starts_with('#') || color.len() != 7 {
-        return color.to_string();
+pub const PASTEL_PALETTE: [&str; 8] = [
+    "#dfe7f5", "#f4dccb", "#e2f0d9", "#fbe5e1", "#ece4f4", "#fff2cc", "#d9f0ef", "#f2e6d9",
+];
+pub const EARTH_PALETTE: [&str; 8] = [
+    "#e8dcc4", "#d9c8a9", "#c9d6b8", "#e6cfb3", "#d4c4b0", "#c2b8a3", "#e0d5c1", "#cfbf9f",
+];
+pub const OCEAN_PALETTE: [&str; 8] = [
+    "#d6eaf8", "#c4e3ed", "#b8dbe0", "#d0e4f2", "#c6d8ee", "#bfe3dd", "#dbeff5", "#aed6e6",
+];
+pub const HIGH_CONTRAST_PALETTE: [&str; 8] = [
+    "#ffd54f", "#81d4fa", "#a5d6a7", "#ef9a9a", "#ce93d8", "#ffab91", "#80cbc4", "#e6ee9c",
+];
+
+/// Named palette for `--color-scheme`, assigning category group colors automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+    Pastel,
+    Earth,
+    Ocean,
+    HighContrast,
+}
+
+impl ColorScheme {
+    pub fn palette(self) -> &'static [&'static str] {
+        match self {
+            ColorScheme::Pastel => &PASTEL_PALETTE,
+            ColorScheme::Earth => &EARTH_PALETTE,
+            ColorScheme::Ocean => &OCEAN_PALETTE,
+            ColorScheme::HighContrast => &HIGH_CONTRAST_PALETTE,
+        }
     }
-    let parse = || -> Option<String> {
-        let r = u8::from_str_radix(&color[1..3], 16).ok()?;
-        let g = u8::from_str_radix(&color[3..5], 16).ok()?;
-        let b = u8::from_str_radix(&color[5..7], 16).ok()?;
-        let dr = (r as f64 * factor) as u8;
-        let dg = (g as f64 * factor) as u8;
-        let db = (b as f64 * factor) as u8;
-        Some(format!("#{dr:02x}{dg:02x}{db:02x}"))
-    };
-    parse().unwrap_or_else(|| color.to_string())
+}
+
+impl std::str::FromStr for ColorScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pastel" => Ok(ColorScheme::Pastel),
+            "earth" => Ok(ColorScheme::Earth),
+            "ocean" => Ok(ColorScheme::Ocean),
+            "high-contrast" => Ok(ColorScheme::HighContrast),
+            _ => anyhow::bail!(
+                "unknown color scheme {s:?}; expected pastel, earth, ocean or high-contrast"
+            ),
+        }
+    }
+}
+
+/// `watch_list` recolored from `scheme`'s palette, cycling through it in watch-list order
+/// and replacing any configured color. Without a scheme, blank colors are left to
+/// [`resolve_group_color`]'s gray fallback.
+pub fn scheme_group_colors(
+    watch_list: &IndexMap<String, String>,
+    scheme: ColorScheme,
+) -> IndexMap<String, String> {
+    watch_list
+        .keys()
+        .zip(scheme.palette().iter().cycle())
+        .map(|(group, color)| (group.clone(), color.to_string()))
+        .collect()
+}
+
+/// Channels of a `#rrggbb` or shorthand `#rgb` color; shorthand digits are doubled, so
+/// `#abc` reads as `#aabbcc`.
+fn parse_hex_rgb(color: &str) -> Option<[u8; 3]> {
+    let digits = color.strip_prefix('#')?;
+    if !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |hex: &str| u8::from_str_radix(hex, 16).ok();
+    match digits.len() {
+        6 => Some([
+            channel(&digits[0..2])?,
+            channel(&digits[2..4])?,
+            channel(&digits[4..6])?,
+        ]),
+        3 => {
+            let doubled = |i: usize| channel(&digits[i..=i].repeat(2));
+            Some([doubled(0)?, doubled(1)?, doubled(2)?])
+        }
+        _ => None,
+    }
+}
+
+/// Applies `adjust` to each channel of `color`, returning `color` unchanged when it is not
+/// a hex color.
+fn map_hex_channels(color: &str, adjust: impl Fn(u8) -> u8) -> String {
+    match parse_hex_rgb(color) {
+        Some([r, g, b]) => format!("#{:02x}{:02x}{:02x}", adjust(r), adjust(g), adjust(b)),
+        None => color.to_string(),
+    }
+}
+
+pub fn darken_hex(color: &str, factor: f64) -> String {
+    map_hex_channels(color, |channel| (channel as f64 * factor) as u8)
+}
+
+/// Moves each channel `amount` of the way towards white (0 keeps the color, 1 is white).
+pub fn lighten_hex(color: &str, amount: f64) -> String {
+    map_hex_channels(color, |channel| {
+        (channel as f64 + (255.0 - channel as f64) * amount) as u8
+    })
 }
 
 fn with_value_columns(df: &DataFrame) -> Result<DataFrame> {
@@ -70,14 +221,13 @@ fn with_value_columns(df: &DataFrame) -> Result<DataFrame> {
     let planned: Float64Chunked = is_annual
         .iter()
         .zip(budgeted.iter())
-        .map(|(is_ann, bud)| match (is_ann, bud) {
-            (Some(true), Some(b)) => Some(b),
-            (Some(false), Some(b)) => Some(b * 12.0),
-            _ => None,
-        })
+        .map(|(is_ann, bud)| Some(GoalTracker::planned_annual_amount(bud?, is_ann?)))
         .collect();
 
-    let per_month: Float64Chunked = planned.iter().map(|p| p.map(|v| v / 12.0)).collect();
+    let per_month: Float64Chunked = planned
+        .iter()
+        .map(|p| p.map(GoalTracker::per_month_amount))
+        .collect();
 
     let remaining = df.column("balance").context("balance column")?.clone();
 
@@ -104,6 +254,230 @@ fn with_value_columns(df: &DataFrame) -> Result<DataFrame> {
     Ok(result)
 }
 
+fn optional_f64(df: &DataFrame, column: &str, row: usize) -> Option<f64> {
+    df.column(column).ok()?.f64().ok()?.get(row)
+}
+
+fn optional_i32(df: &DataFrame, column: &str, row: usize) -> Option<i32> {
+    df.column(column).ok()?.i32().ok()?.get(row)
+}
+
+fn optional_bool(df: &DataFrame, column: &str, row: usize) -> Option<bool> {
+    df.column(column).ok()?.bool().ok()?.get(row)
+}
+
+/// Per-category `(week_number, spent)` points, oldest week first, keyed by category name.
+pub type Sparklines = IndexMap<String, Vec<(usize, f64)>>;
+
+/// Optional sections of the visual report beyond the main table.
+#[derive(Default)]
+pub struct VisualSections<'a> {
+    /// Per-category inflows from `report::build_income_table`, shown below the table
+    pub income_table: Option<LazyFrame>,
+    /// Week-by-week spend per category, shown as a "Trend" column of inline SVG bars
+    pub sparklines: Option<&'a Sparklines>,
+    /// Stylesheet from the config's `htmlExtraCss`, applied after the built-in styles
+    pub extra_css: Option<&'a str>,
+    /// Relative URL of a companion print stylesheet (see [`print_stylesheet`]) to link
+    /// for `media="print"`, written when the config's `printMode` is set
+    pub print_stylesheet_href: Option<&'a str>,
+    /// How amounts are written, e.g. the budget's own format from
+    /// `YnabApi::get_budget_currency_format`; [`CurrencyFormat::gbp`] when unset
+    pub currency_format: Option<&'a CurrencyFormat>,
+    /// Show at most this many categories per group, those with the largest spend, and
+    /// fold the rest into one "… and M more" row; group totals still count every row
+    pub max_rows_per_group: Option<usize>,
+    /// Relative URL of the directory holding the pages from
+    /// [`build_transaction_detail_html`]; category rows open theirs on double-click
+    pub transaction_detail_dir: Option<&'a str>,
+}
+
+/// The page styles shared by the weekly visual report and its transaction detail pages.
+const BASE_STYLE_RULES: [&str; 73] = [
+    "    :root {",
+    "      --grid: #d9d9d9;",
+    "      --header-bg: #f7f3e9;",
+    "      --text: #1f1f1f;",
+    "    }",
+    "    body {",
+    "      margin: 24px;",
+    r#"      font-family: "Alegreya Sans", "Trebuchet MS", sans-serif;"#,
+    "      color: var(--text);",
+    "      background: linear-gradient(180deg, #fbf9f4 0%, #f3efe7 100%);",
+    "      -webkit-user-select: text;",
+    "      user-select: text;",
+    "    }",
+    "    h1 {",
+    "      font-size: 20px;",
+    "      margin: 0 0 16px 0;",
+    "      letter-spacing: 0.02em;",
+    "      text-transform: uppercase;",
+    "    }",
+    "    table {",
+    "      width: 100%;",
+    "      border-collapse: collapse;",
+    "      background: #fffefc;",
+    "      box-shadow: 0 6px 24px rgba(0, 0, 0, 0.08);",
+    "      user-select: none;",
+    "    }",
+    "    th, td {",
+    "      border: 1px solid var(--grid);",
+    "      padding: 6px 8px;",
+    "      font-size: 13px;",
+    "      vertical-align: middle;",
+    "      -webkit-user-select: text;",
+    "      user-select: text;",
+    "    }",
+    "    th {",
+    "      background: var(--header-bg);",
+    "      text-align: left;",
+    "      font-weight: 700;",
+    "    }",
+    "    td.number {",
+    "      text-align: right;",
+    "      white-space: nowrap;",
+    "    }",
+    "    .goal-progress {",
+    "      display: inline-block;",
+    "      width: 10px;",
+    "      height: 10px;",
+    "      border-radius: 50%;",
+    "      vertical-align: middle;",
+    "    }",
+    "    .group-share {",
+    "      font-size: 10px;",
+    "      padding: 0 4px;",
+    "      border-radius: 6px;",
+    "      background: rgba(0, 0, 0, 0.08);",
+    "      vertical-align: middle;",
+    "    }",
+    "    .sparkline {",
+    "      display: block;",
+    "    }",
+    "    tr.total td {",
+    "      font-weight: 700;",
+    "      border-top: 2px solid #9a9a9a;",
+    "    }",
+    "    td.selected {",
+    "      outline: 2px solid #2a5d86;",
+    "      outline-offset: -2px;",
+    "      position: relative;",
+    "    }",
+    "    @media (max-width: 760px) {",
+    "      body { margin: 12px; }",
+    "      th, td { font-size: 12px; }",
+    "    }",
+];
+
+/// Print layout rules: white cells and page, no selection outline, a fixed-layout
+/// table scaled to the page width, and group totals kept on one page.
+const PRINT_RULES: [&str; 6] = [
+    "body { margin: 0; background: #ffffff; }",
+    "table { table-layout: fixed; width: 100%; box-shadow: none; }",
+    "th, td { background: #ffffff !important; font-size: 10px; overflow-wrap: anywhere; }",
+    "tr.total { page-break-inside: avoid; break-inside: avoid; }",
+    "td.selected { outline: none; }",
+    "thead { display: table-header-group; }",
+];
+
+/// [`PRINT_RULES`] as a standalone stylesheet, the companion file linked in print mode.
+pub fn print_stylesheet() -> String {
+    PRINT_RULES.iter().map(|rule| format!("{rule}\n")).collect()
+}
+
+/// The `<link>` to the companion print stylesheet, prefixed with a newline so it can
+/// follow the `<style>` blocks; empty when there is none.
+fn print_stylesheet_link(href: Option<&str>) -> String {
+    href.map(|href| {
+        format!(
+            "\n  <link rel=\"stylesheet\" href=\"{}\" media=\"print\">",
+            html_escape::encode_quoted_attribute(href)
+        )
+    })
+    .unwrap_or_default()
+}
+
+/// A `<style>` block for `extra_css`, prefixed with a newline so it can follow the
+/// built-in styles; empty when there is none. The CSS is escaped for a style element,
+/// so a `</style>` inside it cannot end the block early.
+fn extra_style_block(extra_css: Option<&str>) -> String {
+    extra_css
+        .map(|css| {
+            format!(
+                "\n  <style>\n{}\n  </style>",
+                html_escape::encode_style(css)
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Groups the output of `report::build_weekly_trend_table` into [`Sparklines`],
+/// keeping its week order.
+pub fn sparklines_from_trend_table(trend_table: LazyFrame) -> Result<Sparklines> {
+    let df = trend_table
+        .collect()
+        .context("collecting weekly trend table")?;
+    let week_numbers = df
+        .column("week_number")
+        .context("week_number column")?
+        .cast(&DataType::UInt64)
+        .context("week_number as u64")?;
+    let week_numbers = week_numbers.u64().context("week_number u64")?;
+    let names = df
+        .column("category_name")
+        .context("category_name column")?
+        .str()
+        .context("category_name as str")?;
+    let spent = df
+        .column("spent")
+        .context("spent column")?
+        .f64()
+        .context("spent as f64")?;
+
+    Ok((0..df.height()).fold(Sparklines::new(), |mut acc, i| {
+        if let (Some(week), Some(name)) = (week_numbers.get(i), names.get(i)) {
+            acc.entry(name.to_string())
+                .or_default()
+                .push((week as usize, spent.get(i).unwrap_or(0.0)));
+        }
+        acc
+    }))
+}
+
+const SPARKLINE_BAR_WIDTH: usize = 6;
+const SPARKLINE_GAP: usize = 2;
+const SPARKLINE_HEIGHT: usize = 20;
+
+/// Inline SVG bar chart of `points`' outflow (`-spent`, with inflows drawn as empty
+/// weeks), scaled to the largest week. Each bar carries a `<title>` tooltip.
+pub fn sparkline_svg(points: &[(usize, f64)], color: &str) -> String {
+    let outflows: Vec<f64> = points.iter().map(|(_, spent)| (-spent).max(0.0)).collect();
+    let max_outflow = outflows.iter().copied().fold(0.0_f64, f64::max);
+    let width = points.len() * (SPARKLINE_BAR_WIDTH + SPARKLINE_GAP);
+    let bars: String = points
+        .iter()
+        .zip(&outflows)
+        .enumerate()
+        .map(|(i, ((week_number, spent), outflow))| {
+            let height = if max_outflow > 0.0 {
+                ((outflow / max_outflow) * SPARKLINE_HEIGHT as f64).round() as usize
+            } else {
+                0
+            };
+            format!(
+                r#"<rect x="{}" y="{}" width="{SPARKLINE_BAR_WIDTH}" height="{height}" fill="{}"><title>Week {week_number}: {}</title></rect>"#,
+                i * (SPARKLINE_BAR_WIDTH + SPARKLINE_GAP),
+                SPARKLINE_HEIGHT - height,
+                html_escape::encode_double_quoted_attribute(color),
+                html_escape::encode_text(&format_currency(-spent, true)),
+            )
+        })
+        .collect();
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="sparkline" width="{width}" height="{SPARKLINE_HEIGHT}" viewBox="0 0 {width} {SPARKLINE_HEIGHT}" role="img" aria-label="weekly spending">{bars}</svg>"#
+    )
+}
+
 struct RowData {
     category: String,
     planned: f64,
@@ -114,11 +488,63 @@ struct RowData {
     is_total: bool,
     show_period_values: bool,
     is_annual: bool,
+    pace: Option<(f64, f64)>,
+    budget_change: Option<f64>,
+    /// `None` when the report has no `free_balance` column, hiding the "Free" column
+    free_balance: Option<Option<f64>>,
+    goal_percent_complete: Option<i32>,
+    pct_of_group_budgeted: Option<f64>,
+    /// Set when `budgeted` came from the config's `budgetOverrides`
+    budget_overridden: bool,
+    /// `None` when no sparklines were supplied, hiding the "Trend" column
+    sparkline: Option<Option<String>>,
+    /// Relative URL of the category's transaction detail page
+    detail_href: Option<String>,
 }
 
-fn row_html(data: &RowData) -> String {
+fn trend_arrow_html(budget_change: f64) -> String {
+    let rounded = (budget_change * 100.0).round() / 100.0;
+    let (arrow, color) = if rounded > 0.0 {
+        ("▲", "#e69138")
+    } else if rounded < 0.0 {
+        ("▼", "#38761d")
+    } else {
+        ("=", "#999999")
+    };
+    format!(r#" <span class="trend" style="color: {color};">{arrow}</span>"#)
+}
+
+fn goal_progress_html(percent: i32) -> String {
+    let percent = percent.clamp(0, 100);
+    format!(
+        r#"<span class="goal-progress" style="background: conic-gradient(#38761d {percent}%, #d9d9d9 0);" title="{percent}% of goal"></span> "#
+    )
+}
+
+fn row_html(data: &RowData, format: &CurrencyFormat) -> String {
+    let currency = |value: f64, show_zero: bool| {
+        format_currency_with(value, show_zero, format, format.precision)
+    };
     let class_name = if data.is_total { "total" } else { "group" };
     let row_style = format!(" style=\"background-color: {};\"", data.color);
+    let category_attributes = if data.is_total {
+        String::new()
+    } else {
+        let detail_href = data
+            .detail_href
+            .as_deref()
+            .map(|href| {
+                format!(
+                    " data-detail-href=\"{}\"",
+                    html_escape::encode_double_quoted_attribute(href)
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            " data-category=\"{}\"{detail_href}",
+            html_escape::encode_double_quoted_attribute(&data.category)
+        )
+    };
     let show_values = data.show_period_values || data.is_total;
 
     let annual_style = if data.is_annual {
@@ -133,42 +559,230 @@ fn row_html(data: &RowData) -> String {
     let remaining_value = if data.is_total || !show_values {
         String::new()
     } else {
-        format_currency(data.remaining, show_values)
+        let progress = data
+            .goal_percent_complete
+            .map(goal_progress_html)
+            .unwrap_or_default();
+        format!("{progress}{}", currency(data.remaining, show_values))
     };
 
     let escaped_category = html_escape::encode_quoted_attribute(&data.category);
+    let group_share = match data.pct_of_group_budgeted {
+        Some(percent) if !data.is_total => format!(
+            r#" <span class="group-share" title="share of group budget">{percent:.0}%</span>"#
+        ),
+        _ => String::new(),
+    };
+
+    let spent_title = match data.pace {
+        Some((percent, weighted)) if !data.is_total => {
+            format!(" title=\"{percent:.0}% spent, {weighted:.2}x expected pace\"")
+        }
+        _ => String::new(),
+    };
+
+    let trend = match data.budget_change {
+        Some(change) if !data.is_total => trend_arrow_html(change),
+        _ => String::new(),
+    };
+
+    let override_marker = if data.budget_overridden {
+        r#"<span class="override" title="budget overridden in config">*</span>"#
+    } else {
+        ""
+    };
 
     [
-        format!(r#"      <tr class="{class_name}"{row_style}>"#),
-        format!("        <td>{escaped_category}</td>"),
+        format!(r#"      <tr class="{class_name}"{category_attributes}{row_style}>"#),
+        format!("        <td>{escaped_category}{group_share}</td>"),
         format!(
-            r#"        <td class="number"{annual_style}>{}</td>"#,
-            format_currency(data.planned, data.is_total)
+            r#"        <td class="number"{annual_style}>{}{override_marker}</td>"#,
+            currency(data.planned, data.is_total)
         ),
         format!(
-            r#"        <td class="number"{annual_style}>{}</td>"#,
-            format_currency(data.per_month, data.is_total)
+            r#"        <td class="number"{annual_style}>{}{trend}</td>"#,
+            currency(data.per_month, data.is_total)
         ),
         format!(
-            r#"        <td class="number">{}</td>"#,
-            format_currency(-data.spent, show_values)
+            r#"        <td class="number"{spent_title}>{}</td>"#,
+            currency(-data.spent, show_values)
         ),
         format!(r#"        <td class="number">{remaining_value}</td>"#),
-        "      </tr>".to_string(),
     ]
+    .into_iter()
+    .chain(data.free_balance.map(|free_balance| {
+        let free_value = match free_balance {
+            Some(value) if !data.is_total && show_values => currency(value, true),
+            _ => String::new(),
+        };
+        format!(r#"        <td class="number">{free_value}</td>"#)
+    }))
+    .chain(data.sparkline.as_ref().map(|sparkline| {
+        format!(
+            r#"        <td class="trend-cell">{}</td>"#,
+            sparkline.as_deref().unwrap_or_default()
+        )
+    }))
+    .chain(["      </tr>".to_string()])
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Row indices of `values` to render: all of them, or with `max_rows` the `max_rows`
+/// with the largest absolute `spent` (earlier rows first on ties), in their original
+/// order.
+fn shown_row_indices(values: &DataFrame, max_rows: Option<usize>) -> Vec<usize> {
+    let all_rows: Vec<usize> = (0..values.height()).collect();
+    let Some(max_rows) = max_rows else {
+        return all_rows;
+    };
+    let abs_spent = |i: usize| optional_f64(values, "spent", i).unwrap_or(0.0).abs();
+    let mut by_spent = all_rows;
+    by_spent.sort_by(|a, b| abs_spent(*b).total_cmp(&abs_spent(*a)).then(a.cmp(b)));
+    by_spent.truncate(max_rows);
+    by_spent.sort_unstable();
+    by_spent
+}
+
+/// The "… and M more" row standing in for the `hidden` rows of a group's `values`,
+/// with their combined figures.
+fn more_rows_html(
+    values: &DataFrame,
+    hidden: &[usize],
+    color: &str,
+    show_free_balance: bool,
+    show_trend: bool,
+    format: &CurrencyFormat,
+) -> String {
+    let sum = |column: &str| -> f64 {
+        hidden
+            .iter()
+            .map(|&i| optional_f64(values, column, i).unwrap_or(0.0))
+            .sum()
+    };
+    let currency = |value: f64| format_currency_with(value, true, format, format.precision);
+    [
+        format!(r#"      <tr class="more" style="background-color: {color};">"#),
+        format!("        <td>… and {} more</td>", hidden.len()),
+        format!(
+            r#"        <td class="number">{}</td>"#,
+            currency(sum("planned"))
+        ),
+        format!(
+            r#"        <td class="number">{}</td>"#,
+            currency(sum("per_month"))
+        ),
+        format!(
+            r#"        <td class="number">{}</td>"#,
+            currency(-sum("spent"))
+        ),
+        format!(
+            r#"        <td class="number">{}</td>"#,
+            currency(sum("remaining"))
+        ),
+    ]
+    .into_iter()
+    .chain(show_free_balance.then(|| r#"        <td class="number"></td>"#.to_string()))
+    .chain(show_trend.then(|| r#"        <td class="trend-cell"></td>"#.to_string()))
+    .chain(["      </tr>".to_string()])
+    .collect::<Vec<_>>()
     .join("\n")
 }
 
+/// Income breakdown table from `report::build_income_table`, prefixed with a newline so
+/// it can trail the main table's closing tag.
+fn income_section_html(income_df: &DataFrame, format: &CurrencyFormat) -> Result<String> {
+    let currency = |value: f64| format_currency_with(value, true, format, format.precision);
+    let names = income_df
+        .column("category_name")
+        .context("income category_name column")?
+        .str()
+        .context("income category_name as str")?;
+    let income = income_df
+        .column("income")
+        .context("income column")?
+        .f64()
+        .context("income as f64")?;
+
+    let rows = names.iter().zip(income.iter()).map(|(name, amount)| {
+        format!(
+            r#"      <tr><td>{}</td><td class="number">{}</td></tr>"#,
+            html_escape::encode_text(name.unwrap_or("")),
+            currency(amount.unwrap_or(0.0))
+        )
+    });
+    let total: f64 = income.sum().unwrap_or(0.0);
+
+    let lines: Vec<String> = [
+        "  <h2>Income</h2>",
+        r#"  <table class="income">"#,
+        "    <thead>",
+        "      <tr><th>Category</th><th>Income</th></tr>",
+        "    </thead>",
+        "    <tbody>",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .chain(rows)
+    .chain([
+        format!(
+            r#"      <tr class="total"><td>Total Income</td><td class="number">{}</td></tr>"#,
+            currency(total)
+        ),
+        "    </tbody>".to_string(),
+        "  </table>".to_string(),
+    ])
+    .collect();
+
+    Ok(format!("\n{}", lines.join("\n")))
+}
+
 pub fn build_visual_report_html(
     report_table: LazyFrame,
     group_colors: &IndexMap<String, String>,
     week_label: &str,
     planned_year: i32,
     show_all_rows: bool,
+    sections: VisualSections,
 ) -> Result<String> {
+    let mut buffer = Vec::new();
+    write_visual_report_html(
+        &mut buffer,
+        report_table,
+        group_colors,
+        week_label,
+        planned_year,
+        show_all_rows,
+        sections,
+    )?;
+    String::from_utf8(buffer).context("visual report HTML not valid UTF-8")
+}
+
+/// Streams the visual report HTML to `writer`, flushing it once the page is written.
+pub fn write_visual_report_html<W: Write>(
+    mut writer: W,
+    report_table: LazyFrame,
+    group_colors: &IndexMap<String, String>,
+    week_label: &str,
+    planned_year: i32,
+    show_all_rows: bool,
+    sections: VisualSections,
+) -> Result<()> {
     let report_df = report_table
         .collect()
         .context("collecting report table for visual")?;
+    let gbp = CurrencyFormat::gbp();
+    let currency_format = sections.currency_format.unwrap_or(&gbp);
+    let income_section = sections
+        .income_table
+        .map(|income| {
+            income
+                .collect()
+                .context("collecting income table for visual")
+                .and_then(|df| income_section_html(&df, currency_format))
+        })
+        .transpose()?
+        .unwrap_or_default();
 
     let display_df = if show_all_rows {
         report_df.clone()
@@ -181,13 +795,24 @@ pub fn build_visual_report_html(
             .context("filtering display rows")?
     };
 
+    let show_free_balance = report_df.schema().contains("free_balance");
+    let free_balance_cell = |value: Option<f64>| show_free_balance.then_some(value);
+    let sparkline_cell = |category: Option<&str>, color: &str| {
+        sections.sparklines.map(|sparklines| {
+            category
+                .and_then(|name| sparklines.get(name))
+                .map(|points| sparkline_svg(points, &darken_hex(color, 0.6)))
+        })
+    };
+
     let mut rows: Vec<String> = Vec::new();
     let mut total_planned = 0.0_f64;
     let mut total_per_month = 0.0_f64;
     let mut total_spent = 0.0_f64;
     let mut total_remaining = 0.0_f64;
 
-    for (group_name, color) in group_colors {
+    for group_name in group_colors.keys() {
+        let color = resolve_group_color(group_name, group_colors);
         let group_df = report_df
             .clone()
             .lazy()
@@ -249,7 +874,8 @@ pub fn build_visual_report_html(
         total_spent += group_spent;
         total_remaining += group_remaining;
 
-        for i in 0..display_values.height() {
+        let shown_rows = shown_row_indices(&display_values, sections.max_rows_per_group);
+        for &i in &shown_rows {
             let cat_name = display_values
                 .column("category_name")
                 .context("cat name")?
@@ -292,49 +918,125 @@ pub fn build_visual_report_html(
                 .context("is_annual bool")?
                 .get(i)
                 .unwrap_or(false);
+            let pace = optional_f64(&display_values, "percent_spent", i).zip(optional_f64(
+                &display_values,
+                "weighted_percent_spent",
+                i,
+            ));
+            let budget_change = optional_f64(&display_values, "budget_change", i);
+            let free_balance = optional_f64(&display_values, "free_balance", i);
+            let goal_percent_complete = optional_i32(&display_values, "goal_percent_complete", i);
+            let pct_of_group_budgeted = optional_f64(&display_values, "pct_of_group_budgeted", i);
+            let budget_overridden =
+                optional_bool(&display_values, "budget_override_applied", i).unwrap_or(false);
 
-            rows.push(row_html(&RowData {
-                category: cat_name.to_string(),
-                planned,
-                per_month,
-                spent,
-                remaining,
-                color: color.clone(),
-                is_total: false,
-                show_period_values: spent != 0.0,
-                is_annual,
-            }));
+            rows.push(row_html(
+                &RowData {
+                    category: cat_name.to_string(),
+                    planned,
+                    per_month,
+                    spent,
+                    remaining,
+                    color: color.to_string(),
+                    is_total: false,
+                    show_period_values: spent != 0.0,
+                    is_annual,
+                    pace,
+                    budget_change,
+                    free_balance: free_balance_cell(free_balance),
+                    goal_percent_complete,
+                    pct_of_group_budgeted,
+                    budget_overridden,
+                    sparkline: sparkline_cell(Some(cat_name), color),
+                    detail_href: sections
+                        .transaction_detail_dir
+                        .map(|dir| format!("{dir}/{}", transaction_detail_file_name(cat_name))),
+                },
+                currency_format,
+            ));
         }
 
-        rows.push(row_html(&RowData {
-            category: format!("Total {group_name}"),
-            planned: group_planned,
-            per_month: group_per_month,
-            spent: group_spent,
-            remaining: group_remaining,
-            color: darken_hex(color, 0.85),
-            is_total: true,
-            show_period_values: true,
-            is_annual: false,
-        }));
+        let hidden_rows: Vec<usize> = (0..display_values.height())
+            .filter(|i| !shown_rows.contains(i))
+            .collect();
+        if !hidden_rows.is_empty() {
+            rows.push(more_rows_html(
+                &display_values,
+                &hidden_rows,
+                color,
+                show_free_balance,
+                sections.sparklines.is_some(),
+                currency_format,
+            ));
+        }
+
+        rows.push(row_html(
+            &RowData {
+                category: format!("Total {group_name}"),
+                planned: group_planned,
+                per_month: group_per_month,
+                spent: group_spent,
+                remaining: group_remaining,
+                color: darken_hex(color, 0.85),
+                is_total: true,
+                show_period_values: true,
+                is_annual: false,
+                pace: None,
+                budget_change: None,
+                free_balance: free_balance_cell(None),
+                goal_percent_complete: None,
+                pct_of_group_budgeted: None,
+                budget_overridden: false,
+                sparkline: sparkline_cell(None, ""),
+                detail_href: None,
+            },
+            currency_format,
+        ));
     }
 
     if !rows.is_empty() {
-        rows.push(row_html(&RowData {
-            category: "Total".to_string(),
-            planned: total_planned,
-            per_month: total_per_month,
-            spent: total_spent,
-            remaining: total_remaining,
-            color: "#b7b7b7".to_string(),
-            is_total: true,
-            show_period_values: true,
-            is_annual: false,
-        }));
+        rows.push(row_html(
+            &RowData {
+                category: "Total".to_string(),
+                planned: total_planned,
+                per_month: total_per_month,
+                spent: total_spent,
+                remaining: total_remaining,
+                color: "#b7b7b7".to_string(),
+                is_total: true,
+                show_period_values: true,
+                is_annual: false,
+                pace: None,
+                budget_change: None,
+                free_balance: free_balance_cell(None),
+                goal_percent_complete: None,
+                pct_of_group_budgeted: None,
+                budget_overridden: false,
+                sparkline: sparkline_cell(None, ""),
+                detail_href: None,
+            },
+            currency_format,
+        ));
     }
 
     let body_rows = rows.join("\n");
+    let free_balance_header = if show_free_balance {
+        "\n        <th rowspan=\"2\">Free</th>"
+    } else {
+        ""
+    };
+    let trend_header = if sections.sparklines.is_some() {
+        "\n        <th rowspan=\"2\">Trend</th>"
+    } else {
+        ""
+    };
     let escaped_week = html_escape::encode_text(week_label);
+    let base_style_rules = BASE_STYLE_RULES.join("\n");
+    let print_rules = PRINT_RULES
+        .iter()
+        .map(|rule| format!("      {rule}"))
+        .collect::<Vec<_>>()
+        .join("\n");
 
     let html = [
         "<!DOCTYPE html>",
@@ -344,63 +1046,15 @@ pub fn build_visual_report_html(
         r#"  <meta name="viewport" content="width=device-width, initial-scale=1">"#,
         "  <title>Budget Visual Report</title>",
         "  <style>",
-        "    :root {",
-        "      --grid: #d9d9d9;",
-        "      --header-bg: #f7f3e9;",
-        "      --text: #1f1f1f;",
-        "    }",
-        "    body {",
-        "      margin: 24px;",
-        r#"      font-family: "Alegreya Sans", "Trebuchet MS", sans-serif;"#,
-        "      color: var(--text);",
-        "      background: linear-gradient(180deg, #fbf9f4 0%, #f3efe7 100%);",
-        "      -webkit-user-select: text;",
-        "      user-select: text;",
+        &base_style_rules,
+        "    @media print {",
+        &print_rules,
         "    }",
-        "    h1 {",
-        "      font-size: 20px;",
-        "      margin: 0 0 16px 0;",
-        "      letter-spacing: 0.02em;",
-        "      text-transform: uppercase;",
-        "    }",
-        "    table {",
-        "      width: 100%;",
-        "      border-collapse: collapse;",
-        "      background: #fffefc;",
-        "      box-shadow: 0 6px 24px rgba(0, 0, 0, 0.08);",
-        "      user-select: none;",
-        "    }",
-        "    th, td {",
-        "      border: 1px solid var(--grid);",
-        "      padding: 6px 8px;",
-        "      font-size: 13px;",
-        "      vertical-align: middle;",
-        "      -webkit-user-select: text;",
-        "      user-select: text;",
-        "    }",
-        "    th {",
-        "      background: var(--header-bg);",
-        "      text-align: left;",
-        "      font-weight: 700;",
-        "    }",
-        "    td.number {",
-        "      text-align: right;",
-        "      white-space: nowrap;",
-        "    }",
-        "    tr.total td {",
-        "      font-weight: 700;",
-        "      border-top: 2px solid #9a9a9a;",
-        "    }",
-        "    td.selected {",
-        "      outline: 2px solid #2a5d86;",
-        "      outline-offset: -2px;",
-        "      position: relative;",
-        "    }",
-        "    @media (max-width: 760px) {",
-        "      body { margin: 12px; }",
-        "      th, td { font-size: 12px; }",
-        "    }",
-        "  </style>",
+        &format!(
+            "  </style>{}{}",
+            extra_style_block(sections.extra_css),
+            print_stylesheet_link(sections.print_stylesheet_href)
+        ),
         "</head>",
         "<body>",
         &format!("  <h1>{escaped_week}</h1>"),
@@ -410,7 +1064,9 @@ pub fn build_visual_report_html(
         r#"        <th rowspan="2">Category</th>"#,
         &format!(r#"        <th rowspan="2">{planned_year} (planned)</th>"#),
         &format!(r#"        <th rowspan="2">{planned_year} per month</th>"#),
-        &format!(r#"        <th colspan="2">{escaped_week}</th>"#),
+        &format!(
+            r#"        <th colspan="2">{escaped_week}</th>{free_balance_header}{trend_header}"#
+        ),
         "      </tr>",
         "      <tr>",
         "        <th>Spent</th>",
@@ -420,7 +1076,7 @@ pub fn build_visual_report_html(
         "    <tbody>",
         &body_rows,
         "    </tbody>",
-        "  </table>",
+        &format!("  </table>{income_section}"),
         "  <script>",
         r#"    const table = document.querySelector("table.selectable");"#,
         "    if (table) {",
@@ -504,11 +1160,338 @@ pub fn build_visual_report_html(
         r#"        event.clipboardData.setData("text/plain", lines.join("\n"));"#,
         "        event.preventDefault();",
         "      });",
+        r#"      table.addEventListener("dblclick", (event) => {"#,
+        r#"        const row = event.target.closest("tr[data-detail-href]");"#,
+        "        if (row) {",
+        r#"          window.open(row.dataset.detailHref, "_blank");"#,
+        "        }",
+        "      });",
         "    }",
         "  </script>",
         "</body>",
         "</html>",
     ];
 
+    html.iter()
+        .try_for_each(|line| writeln!(writer, "{line}"))
+        .context("writing visual report HTML")?;
+    writer.flush().context("flushing visual report HTML")
+}
+
+/// File name of `category_name`'s page from [`build_transaction_detail_html`]: ASCII
+/// letters, digits and `-` kept, every other byte written as `_xx` in hex.
+pub fn transaction_detail_file_name(category_name: &str) -> String {
+    let stem: String = category_name
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || byte == b'-' {
+                char::from(byte).to_string()
+            } else {
+                format!("_{byte:02x}")
+            }
+        })
+        .collect();
+    format!("{stem}.html")
+}
+
+/// Standalone page listing `category_name`'s transactions from `transactions` by date
+/// (then payee), with signed amounts and a total row, styled like the weekly report.
+pub fn build_transaction_detail_html(
+    category_name: &str,
+    transactions: TransactionFrame,
+    week_label: &str,
+) -> Result<String> {
+    build_transaction_detail_html_with(
+        category_name,
+        transactions,
+        week_label,
+        &CurrencyFormat::gbp(),
+    )
+}
+
+/// [`build_transaction_detail_html`] with amounts written in `format`.
+pub fn build_transaction_detail_html_with(
+    category_name: &str,
+    transactions: TransactionFrame,
+    week_label: &str,
+    format: &CurrencyFormat,
+) -> Result<String> {
+    let df = transactions
+        .0
+        .filter(col("category_name").eq(lit(category_name)))
+        .sort(["date", "payee_name"], SortMultipleOptions::default())
+        .select([
+            col("date").cast(DataType::String),
+            col("payee_name"),
+            col("amount"),
+        ])
+        .collect()
+        .context("collecting category transactions for detail page")?;
+    let dates = df
+        .column("date")
+        .context("date column")?
+        .str()
+        .context("date as str")?;
+    let payees = df
+        .column("payee_name")
+        .context("payee_name column")?
+        .str()
+        .context("payee_name as str")?;
+    let amounts = df
+        .column("amount")
+        .context("amount column")?
+        .f64()
+        .context("amount as f64")?;
+
+    let total: f64 = amounts.sum().unwrap_or(0.0);
+    let body_rows: Vec<String> = (0..df.height())
+        .map(|i| {
+            format!(
+                r#"      <tr class="group"><td>{}</td><td>{}</td><td class="number">{}</td></tr>"#,
+                dates.get(i).unwrap_or_default(),
+                html_escape::encode_text(payees.get(i).unwrap_or_default()),
+                format_currency_with(
+                    amounts.get(i).unwrap_or(0.0),
+                    true,
+                    format,
+                    format.precision,
+                )
+            )
+        })
+        .chain([format!(
+            r#"      <tr class="total"><td>Total</td><td></td><td class="number">{}</td></tr>"#,
+            format_currency_with(total, true, format, format.precision)
+        )])
+        .collect();
+
+    let escaped_category = html_escape::encode_text(category_name);
+    let escaped_week = html_escape::encode_text(week_label);
+    let base_style_rules = BASE_STYLE_RULES.join("\n");
+    let html = [
+        "<!DOCTYPE html>",
+        r#"<html lang="en">"#,
+        "<head>",
+        r#"  <meta charset="utf-8">"#,
+        r#"  <meta name="viewport" content="width=device-width, initial-scale=1">"#,
+        &format!("  <title>{escaped_category} transactions</title>"),
+        "  <style>",
+        &base_style_rules,
+        "  </style>",
+        "</head>",
+        "<body>",
+        &format!("  <h1>{escaped_category}, {escaped_week}</h1>"),
+        "  <table>",
+        "    <thead>",
+        "      <tr><th>Date</th><th>Payee</th><th>Amount</th></tr>",
+        "    </thead>",
+        "    <tbody>",
+        &body_rows.join("\n"),
+        "    </tbody>",
+        "  </table>",
+        "</body>",
+        "</html>",
+    ];
+
+    Ok(format!("{}\n", html.join("\n")))
+}
+
+/// `(budgeted, spent)` for each `(category_name, month)` row of a monthly report table.
+fn monthly_cells(df: &DataFrame) -> Result<IndexMap<(String, String), (f64, f64)>> {
+    let names = df
+        .column("category_name")
+        .context("category_name column")?
+        .str()
+        .context("category_name as str")?;
+    let months = df
+        .column("month")
+        .context("month column")?
+        .str()
+        .context("month as str")?;
+    let budgeted = df
+        .column("budgeted")
+        .context("budgeted column")?
+        .f64()
+        .context("budgeted as f64")?;
+    let spent = df
+        .column("spent")
+        .context("spent column")?
+        .f64()
+        .context("spent as f64")?;
+
+    Ok((0..df.height())
+        .filter_map(|i| {
+            Some((
+                (names.get(i)?.to_string(), months.get(i)?.to_string()),
+                (budgeted.get(i).unwrap_or(0.0), spent.get(i).unwrap_or(0.0)),
+            ))
+        })
+        .collect())
+}
+
+/// One table row: the label cell, then one outflow cell per month with the month's
+/// budget in its tooltip.
+fn monthly_row_html(label: &str, values: &[(f64, f64)], color: &str, is_total: bool) -> String {
+    let class_name = if is_total { "total" } else { "group" };
+    let cells: String = values
+        .iter()
+        .map(|(budgeted, spent)| {
+            format!(
+                r#"<td class="number" title="budgeted {}">{}</td>"#,
+                format_currency(*budgeted, true),
+                format_currency(-spent, is_total)
+            )
+        })
+        .collect();
+    format!(
+        r#"      <tr class="{class_name}" style="background-color: {color};"><td>{}</td>{cells}</tr>"#,
+        html_escape::encode_text(label)
+    )
+}
+
+/// Visual page for a monthly report table (see `report::build_monthly_report_table`),
+/// pivoted to one outflow column per entry of `months`, with group and overall totals.
+/// Categories that spent nothing in every month are left out unless `show_all_rows`;
+/// `extra_css` is styled in as for [`VisualSections::extra_css`].
+pub fn build_monthly_visual_html(
+    monthly_table: LazyFrame,
+    group_colors: &IndexMap<String, String>,
+    months: &[String],
+    title: &str,
+    show_all_rows: bool,
+    extra_css: Option<&str>,
+) -> Result<String> {
+    let df = monthly_table
+        .collect()
+        .context("collecting monthly report for visual")?;
+    let cells = monthly_cells(&df)?;
+    let values_for = |category: &str| -> Vec<(f64, f64)> {
+        months
+            .iter()
+            .map(|month| {
+                cells
+                    .get(&(category.to_string(), month.clone()))
+                    .copied()
+                    .unwrap_or((0.0, 0.0))
+            })
+            .collect()
+    };
+    let add = |totals: Vec<(f64, f64)>, values: &[(f64, f64)]| -> Vec<(f64, f64)> {
+        totals
+            .iter()
+            .zip(values)
+            .map(|((b1, s1), (b2, s2))| (b1 + b2, s1 + s2))
+            .collect()
+    };
+
+    let mut rows: Vec<String> = Vec::new();
+    let mut overall = vec![(0.0, 0.0); months.len()];
+    for group_name in group_colors.keys() {
+        let color = resolve_group_color(group_name, group_colors);
+        let mut categories: Vec<String> = df
+            .clone()
+            .lazy()
+            .filter(col("category_group_name").eq(lit(group_name.as_str())))
+            .select([col("category_name")])
+            .unique_stable(None, UniqueKeepStrategy::First)
+            .collect()
+            .context("collecting group categories")?
+            .column("category_name")
+            .context("category_name column")?
+            .str()
+            .context("category_name as str")?
+            .into_no_null_iter()
+            .map(str::to_string)
+            .collect();
+        if categories.is_empty() {
+            continue;
+        }
+        categories.sort();
+
+        let mut group_totals = vec![(0.0, 0.0); months.len()];
+        for category in &categories {
+            let values = values_for(category);
+            group_totals = add(group_totals, &values);
+            if show_all_rows || values.iter().any(|(_, spent)| *spent != 0.0) {
+                rows.push(monthly_row_html(category, &values, color, false));
+            }
+        }
+        overall = add(overall, &group_totals);
+        rows.push(monthly_row_html(
+            &format!("Total {group_name}"),
+            &group_totals,
+            &darken_hex(color, 0.85),
+            true,
+        ));
+    }
+    if !rows.is_empty() {
+        rows.push(monthly_row_html("Total", &overall, "#b7b7b7", true));
+    }
+
+    let escaped_title = html_escape::encode_text(title);
+    let month_headers: String = months
+        .iter()
+        .map(|month| format!("<th>{}</th>", html_escape::encode_text(month)))
+        .collect();
+    let body_rows = rows.join("\n");
+
+    let html = [
+        "<!DOCTYPE html>",
+        r#"<html lang="en">"#,
+        "<head>",
+        r#"  <meta charset="utf-8">"#,
+        r#"  <meta name="viewport" content="width=device-width, initial-scale=1">"#,
+        "  <title>Budget Monthly Report</title>",
+        "  <style>",
+        "    body {",
+        "      margin: 24px;",
+        r#"      font-family: "Alegreya Sans", "Trebuchet MS", sans-serif;"#,
+        "      color: #1f1f1f;",
+        "      background: linear-gradient(180deg, #fbf9f4 0%, #f3efe7 100%);",
+        "    }",
+        "    h1 {",
+        "      font-size: 20px;",
+        "      margin: 0 0 16px 0;",
+        "      letter-spacing: 0.02em;",
+        "      text-transform: uppercase;",
+        "    }",
+        "    table {",
+        "      width: 100%;",
+        "      border-collapse: collapse;",
+        "      background: #fffefc;",
+        "      box-shadow: 0 6px 24px rgba(0, 0, 0, 0.08);",
+        "    }",
+        "    th, td {",
+        "      border: 1px solid #d9d9d9;",
+        "      padding: 6px 8px;",
+        "      font-size: 13px;",
+        "    }",
+        "    th {",
+        "      background: #f7f3e9;",
+        "      text-align: left;",
+        "    }",
+        "    td.number {",
+        "      text-align: right;",
+        "      white-space: nowrap;",
+        "    }",
+        "    tr.total td {",
+        "      font-weight: 700;",
+        "      border-top: 2px solid #9a9a9a;",
+        "    }",
+        &format!("  </style>{}", extra_style_block(extra_css)),
+        "</head>",
+        "<body>",
+        &format!("  <h1>{escaped_title}</h1>"),
+        "  <table>",
+        "    <thead>",
+        &format!("      <tr><th>Category</th>{month_headers}</tr>"),
+        "    </thead>",
+        "    <tbody>",
+        &body_rows,
+        "    </tbody>",
+        "  </table>",
+        "</body>",
+        "</html>",
+    ];
+
     Ok(format!("{}\n", html.join("\n")))
 }
@@ -1,9 +1,21 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result};
 use chrono::{Datelike, NaiveDate};
-use futures::executor::block_on;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER, USER_AGENT};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
-use ynab_api::apis::configuration::{ApiKey, Configuration};
-use ynab_api::apis::{budgets_api, categories_api, transactions_api};
+use tokio::runtime::Runtime;
+use ynab_api::apis::configuration::Configuration;
+use ynab_api::apis::urlencode;
+use ynab_api::models;
+
+use crate::currency::CurrencyFormat;
+use crate::server_knowledge_cache::ServerKnowledgeCache;
+use crate::zip_archive;
 
 // --- API response types ---
 
@@ -11,6 +23,37 @@ use ynab_api::apis::{budgets_api, categories_api, transactions_api};
 pub struct BudgetSummary {
     pub id: String,
     pub name: String,
+    #[serde(default)]
+    pub last_modified_on: Option<String>,
+}
+
+/// YNAB's `goal_type` codes: target category balance (`TB`), target balance by date
+/// (`TBD`), monthly funding (`MF`), plan your spending (`NEED`) and debt payoff (`DEBT`).
+/// Codes YNAB adds later land in `Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GoalType {
+    Tb,
+    Tbd,
+    Mf,
+    Need,
+    Debt,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl GoalType {
+    /// The code as YNAB writes it.
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Tb => "TB",
+            Self::Tbd => "TBD",
+            Self::Mf => "MF",
+            Self::Need => "NEED",
+            Self::Debt => "DEBT",
+            Self::Unknown(code) => code,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -28,7 +71,13 @@ pub struct Category {
     #[serde(default)]
     pub goal_target: Option<i64>,
     #[serde(default)]
+    pub goal_percentage_complete: Option<i32>,
+    #[serde(default)]
+    pub goal_type: Option<GoalType>,
+    #[serde(default)]
     pub hidden: bool,
+    #[serde(default)]
+    pub deleted: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -45,6 +94,7 @@ pub struct CategoryGroup {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SubTransaction {
+    pub id: String,
     #[serde(default)]
     pub amount: i64,
     #[serde(default)]
@@ -67,6 +117,41 @@ pub struct Transaction {
     pub subtransactions: Vec<SubTransaction>,
 }
 
+/// A budget's `currency_format` setting, as YNAB sends it.
+#[derive(Debug, Clone, Deserialize)]
+struct YnabCurrencyFormat {
+    currency_symbol: String,
+    #[serde(default = "default_display_symbol")]
+    display_symbol: bool,
+    group_separator: String,
+    decimal_separator: String,
+    #[serde(default = "default_decimal_digits")]
+    decimal_digits: u8,
+}
+
+fn default_display_symbol() -> bool {
+    true
+}
+
+fn default_decimal_digits() -> u8 {
+    2
+}
+
+impl From<YnabCurrencyFormat> for CurrencyFormat {
+    fn from(format: YnabCurrencyFormat) -> Self {
+        Self {
+            symbol: if format.display_symbol {
+                format.currency_symbol
+            } else {
+                String::new()
+            },
+            group_separator: format.group_separator,
+            decimal_separator: format.decimal_separator,
+            precision: format.decimal_digits,
+        }
+    }
+}
+
 // --- API response envelopes ---
 
 #[derive(Debug, Deserialize)]
@@ -79,6 +164,22 @@ struct BudgetsResponse {
     data: BudgetsResponseData,
 }
 
+#[derive(Debug, Deserialize)]
+struct BudgetSettings {
+    #[serde(default)]
+    currency_format: Option<YnabCurrencyFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BudgetSettingsResponseData {
+    settings: BudgetSettings,
+}
+
+#[derive(Debug, Deserialize)]
+struct BudgetSettingsResponse {
+    data: BudgetSettingsResponseData,
+}
+
 #[derive(Debug, Deserialize)]
 struct CategoriesResponseData {
     category_groups: Vec<CategoryGroup>,
@@ -99,6 +200,21 @@ struct CategoryResponse {
     data: CategoryResponseData,
 }
 
+#[derive(Debug, Deserialize)]
+struct MonthDetail {
+    categories: Vec<Category>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MonthResponseData {
+    month: MonthDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct MonthResponse {
+    data: MonthResponseData,
+}
+
 #[derive(Debug, Deserialize)]
 struct TransactionsResponseData {
     transactions: Vec<Transaction>,
@@ -120,24 +236,272 @@ pub trait YnabApi {
         month: NaiveDate,
         category_id: &str,
     ) -> Result<Category>;
+    /// Every category's figures for `month` in one call. The default fetches the
+    /// category list and then each category individually, so implementations that can
+    /// read a whole month at once should override it.
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_month_categories_batch(
+        &self,
+        budget_id: &str,
+        month: NaiveDate,
+    ) -> Result<Vec<Category>> {
+        self.get_category_groups(budget_id)?
+            .iter()
+            .flat_map(|group| &group.categories)
+            .map(|category| self.get_month_category(budget_id, month, &category.id))
+            .collect()
+    }
     fn get_transactions(&self, budget_id: &str, since_date: NaiveDate) -> Result<Vec<Transaction>>;
+    /// How the budget writes amounts, from its `currency_format` setting. The default,
+    /// also used for budgets without that setting, is [`CurrencyFormat::gbp`].
+    fn get_budget_currency_format(&self, budget_id: &str) -> Result<CurrencyFormat> {
+        let _ = budget_id;
+        Ok(CurrencyFormat::default())
+    }
 }
 
 // --- HTTP implementation ---
 
+fn first_of_month(day: NaiveDate) -> Result<NaiveDate> {
+    NaiveDate::from_ymd_opt(day.year(), day.month(), 1)
+        .ok_or_else(|| anyhow::anyhow!("invalid month from {day}"))
+}
+
+/// Warn once fewer than this many requests remain in YNAB's hourly quota.
+pub const RATE_LIMIT_WARNING_THRESHOLD: u32 = 5;
+/// Pause used when a 429 response has no usable `Retry-After` header.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+
+/// Requests left this hour from YNAB's `X-Rate-Limit` header, formatted `<used>/<limit>`.
+#[tracing::instrument(level = "debug")]
+pub fn remaining_requests(rate_limit: &str) -> Option<u32> {
+    let (used, limit) = rate_limit.trim().split_once('/')?;
+    let used: u32 = used.trim().parse().ok()?;
+    let limit: u32 = limit.trim().parse().ok()?;
+    Some(limit.saturating_sub(used))
+}
+
+/// Delay requested by a `Retry-After` header given in seconds, or [`DEFAULT_RETRY_AFTER`].
+#[tracing::instrument(level = "debug")]
+pub fn retry_after(header: Option<&str>) -> Duration {
+    header
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map_or(DEFAULT_RETRY_AFTER, Duration::from_secs)
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+struct RawResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: String,
+}
+
 pub struct HttpYnabClient {
     configuration: Configuration,
+    runtime: Runtime,
+    rate_limit_pause: bool,
+    request_log: Option<PathBuf>,
+    server_knowledge_cache: Option<ServerKnowledgeCache>,
+}
+
+/// One NDJSON line in the [`HttpYnabClient::with_log_file`] request log.
+#[derive(Serialize)]
+struct RequestLogEntry<'a> {
+    endpoint: &'a str,
+    request_time_ms: u128,
+    status: u16,
+    /// The response body as JSON, or as a string when it doesn't parse.
+    body: serde_json::Value,
+}
+
+fn append_request_log(path: &Path, entry: &RequestLogEntry) -> Result<()> {
+    let line = serde_json::to_string(entry).context("serializing request log entry")?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening request log {path:?}"))?;
+    writeln!(file, "{line}").with_context(|| format!("appending to request log {path:?}"))
 }
 
 impl HttpYnabClient {
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn new(token: &str) -> Result<Self> {
         let mut configuration = Configuration::new();
-        configuration.api_key = Some(ApiKey {
-            prefix: Some("Bearer".to_string()),
-            key: token.to_string(),
+        configuration.bearer_access_token = Some(token.to_string());
+        Self::new_with_configuration(configuration)
+    }
+
+    /// Like [`HttpYnabClient::new`], but every request fails with a timeout error once it
+    /// has taken longer than `timeout_secs`.
+    #[tracing::instrument(level = "debug", skip(token))]
+    pub fn new_with_timeout(token: &str, timeout_secs: u64) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout_secs))
+            .build()
+            .context("building HTTP client for YNAB")?;
+        let mut configuration = Configuration::new();
+        configuration.bearer_access_token = Some(token.to_string());
+        configuration.client = client;
+        Self::new_with_configuration(configuration)
+    }
+
+    /// Sends requests with a caller-built `configuration`, for custom TLS roots, proxies
+    /// or timeouts. Start from `Configuration::new()`, set `bearer_access_token` to the
+    /// personal access token and `client` to a `reqwest::Client::builder()` result;
+    /// `base_path` and `user_agent` are used as given.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn new_with_configuration(configuration: Configuration) -> Result<Self> {
+        // reqwest needs a Tokio reactor, so requests are driven on a private runtime
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("building Tokio runtime for YNAB client")?;
+
+        Ok(Self {
+            configuration,
+            runtime,
+            rate_limit_pause: true,
+            request_log: None,
+            server_knowledge_cache: None,
+        })
+    }
+
+    /// Like [`HttpYnabClient::new`], but sends requests to `base_url` (for example a local
+    /// mock server) instead of the public YNAB API.
+    #[tracing::instrument(level = "debug", skip(token))]
+    pub fn with_base_url(token: &str, base_url: &str) -> Result<Self> {
+        let mut client = Self::new(token)?;
+        client.configuration.base_path = base_url.trim_end_matches('/').to_string();
+        Ok(client)
+    }
+
+    /// Whether a 429 response sleeps for `Retry-After` and retries (the default) or fails
+    /// immediately.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn with_rate_limit_pause(mut self, rate_limit_pause: bool) -> Self {
+        self.rate_limit_pause = rate_limit_pause;
+        self
+    }
+
+    /// Appends every response (endpoint, timing, status and body) to `path` as NDJSON,
+    /// keeping earlier runs' entries.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn with_log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.request_log = Some(path.into());
+        self
+    }
+
+    /// Keeps categories and transactions in a [`ServerKnowledgeCache`] at `path`, so
+    /// later requests (in this run or the next) ask YNAB only for what changed since
+    /// the cached `server_knowledge` and merge that into the snapshot.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn with_server_knowledge_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.server_knowledge_cache = Some(ServerKnowledgeCache::new(path));
+        self
+    }
+
+    fn send(&self, path: &str, query: &[(&str, String)]) -> Result<RawResponse> {
+        let url = format!("{}{path}", self.configuration.base_path);
+        let mut request = self.configuration.client.get(&url).query(query);
+        if let Some(user_agent) = &self.configuration.user_agent {
+            request = request.header(USER_AGENT, user_agent);
+        }
+        if let Some(token) = &self.configuration.bearer_access_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = self.runtime.block_on(async {
+            let response = request.send().await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.text().await?;
+            Ok::<_, reqwest::Error>(RawResponse {
+                status,
+                headers,
+                body,
+            })
         });
+        let timed_out = response.as_ref().is_err_and(reqwest::Error::is_timeout);
+        response.with_context(|| {
+            if timed_out {
+                format!("timeout requesting {url}")
+            } else {
+                format!("requesting {url}")
+            }
+        })
+    }
+
+    /// GETs `path`, warning when the hourly quota runs low and pausing on 429 responses,
+    /// then parses the body as the `ynab-api` model `TModel`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_model<TModel: DeserializeOwned>(
+        &self,
+        operation: &str,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<TModel> {
+        let mut retries = 0;
+        loop {
+            let started = Instant::now();
+            let response = self.send(path, query)?;
+            tracing::debug!(
+                status = response.status.as_u16(),
+                elapsed_ms = started.elapsed().as_millis(),
+                "YNAB response received"
+            );
+            if let Some(log_path) = &self.request_log {
+                append_request_log(
+                    log_path,
+                    &RequestLogEntry {
+                        endpoint: path,
+                        request_time_ms: started.elapsed().as_millis(),
+                        status: response.status.as_u16(),
+                        body: serde_json::from_str(&response.body)
+                            .unwrap_or_else(|_| serde_json::Value::String(response.body.clone())),
+                    },
+                )?;
+            }
+
+            if let Some(remaining) =
+                header_str(&response.headers, "x-rate-limit").and_then(remaining_requests)
+                && remaining < RATE_LIMIT_WARNING_THRESHOLD
+            {
+                eprintln!("Warning: only {remaining} YNAB API requests left this hour");
+            }
+
+            if response.status == StatusCode::TOO_MANY_REQUESTS {
+                let wait = retry_after(header_str(&response.headers, RETRY_AFTER.as_str()));
+                if !self.rate_limit_pause || retries >= MAX_RATE_LIMIT_RETRIES {
+                    anyhow::bail!(
+                        "{operation} hit the YNAB rate limit; retry after {}s",
+                        wait.as_secs()
+                    );
+                }
+                eprintln!(
+                    "Warning: YNAB rate limit reached, pausing {}s before retrying {operation}",
+                    wait.as_secs()
+                );
+                std::thread::sleep(wait);
+                retries += 1;
+                continue;
+            }
 
-        Ok(Self { configuration })
+            if !response.status.is_success() {
+                anyhow::bail!(
+                    "{operation} failed with status {}: {}",
+                    response.status,
+                    response.body
+                );
+            }
+
+            return serde_json::from_str(&response.body)
+                .with_context(|| format!("parsing {operation} response"));
+        }
     }
 
     fn map_model<TSrc, TDst>(&self, src: TSrc, name: &str) -> Result<TDst>
@@ -153,63 +517,497 @@ impl HttpYnabClient {
 }
 
 impl YnabApi for HttpYnabClient {
+    #[tracing::instrument(level = "debug", skip(self))]
     fn get_budgets(&self) -> Result<Vec<BudgetSummary>> {
-        let response = block_on(budgets_api::get_budgets(&self.configuration, None))
-            .map_err(|err| anyhow::anyhow!("get_budgets failed: {err:?}"))?;
+        let response: models::BudgetSummaryResponse =
+            self.get_model("get_budgets", "/budgets", &[])?;
         let resp: BudgetsResponse = self.map_model(response, "BudgetSummaryResponse")?;
         Ok(resp.data.budgets)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
     fn get_category_groups(&self, budget_id: &str) -> Result<Vec<CategoryGroup>> {
-        let response = block_on(categories_api::get_categories(
-            &self.configuration,
+        let knowledge = self
+            .server_knowledge_cache
+            .as_ref()
+            .map(|cache| cache.categories_knowledge(budget_id))
+            .transpose()?
+            .flatten();
+        let query: Vec<(&str, String)> = knowledge
+            .map(|knowledge| ("last_knowledge_of_server", knowledge.to_string()))
+            .into_iter()
+            .collect();
+        let response: models::CategoriesResponse = self
+            .get_model(
+                "get_categories",
+                &format!("/budgets/{}/categories", urlencode(budget_id)),
+                &query,
+            )
+            .with_context(|| format!("fetching categories for budget {budget_id}"))?;
+        let Some(cache) = &self.server_knowledge_cache else {
+            let resp: CategoriesResponse = self.map_model(response, "CategoriesResponse")?;
+            return Ok(resp.data.category_groups);
+        };
+        let category_groups = cache.sync_categories(
             budget_id,
-            None,
-        ))
-        .map_err(|err| anyhow::anyhow!("get_categories failed for budget {budget_id}: {err:?}"))?;
-        let resp: CategoriesResponse = self.map_model(response, "CategoriesResponse")?;
-        Ok(resp.data.category_groups)
+            response.data.server_knowledge,
+            self.map_model(response.data.category_groups, "CategoryGroupWithCategories")?,
+            knowledge.is_some(),
+        )?;
+        self.map_model(category_groups, "CategoryGroupWithCategories")
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
     fn get_month_category(
         &self,
         budget_id: &str,
         month: NaiveDate,
         category_id: &str,
     ) -> Result<Category> {
-        let first_of_month = NaiveDate::from_ymd_opt(month.year(), month.month(), 1)
-            .ok_or_else(|| anyhow::anyhow!("invalid month from {month}"))?;
-        let month_str = first_of_month.format("%Y-%m-%d").to_string();
-        let response = block_on(categories_api::get_month_category_by_id(
-            &self.configuration,
-            budget_id,
-            month_str.clone(),
-            category_id,
-        ))
-        .map_err(|err| {
-            anyhow::anyhow!(
-                "get_month_category_by_id failed for budget {budget_id}, month {month_str}, category {category_id}: {err:?}"
+        let month_str = first_of_month(month)?.format("%Y-%m-%d").to_string();
+        let response: models::CategoryResponse = self
+            .get_model(
+                "get_month_category_by_id",
+                &format!(
+                    "/budgets/{}/months/{month_str}/categories/{}",
+                    urlencode(budget_id),
+                    urlencode(category_id)
+                ),
+                &[],
             )
-        })?;
+            .with_context(|| {
+                format!("fetching budget {budget_id}, month {month_str}, category {category_id}")
+            })?;
         let resp: CategoryResponse = self.map_model(response, "CategoryResponse")?;
         Ok(resp.data.category)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_month_categories_batch(
+        &self,
+        budget_id: &str,
+        month: NaiveDate,
+    ) -> Result<Vec<Category>> {
+        let month_str = first_of_month(month)?.format("%Y-%m-%d").to_string();
+        let response: models::MonthDetailResponse = self
+            .get_model(
+                "get_budget_month",
+                &format!("/budgets/{}/months/{month_str}", urlencode(budget_id)),
+                &[],
+            )
+            .with_context(|| format!("fetching budget {budget_id}, month {month_str}"))?;
+        let resp: MonthResponse = self.map_model(response, "MonthDetailResponse")?;
+        Ok(resp.data.month.categories)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
     fn get_transactions(&self, budget_id: &str, since_date: NaiveDate) -> Result<Vec<Transaction>> {
-        let since = since_date.format("%Y-%m-%d").to_string();
-        let response = block_on(transactions_api::get_transactions(
-            &self.configuration,
-            budget_id,
-            Some(since.clone()),
-            None,
-            None,
-        ))
-        .map_err(|err| {
-            anyhow::anyhow!(
-                "get_transactions failed for budget {budget_id}, since_date {since}: {err:?}"
+        let knowledge = self
+            .server_knowledge_cache
+            .as_ref()
+            .map(|cache| cache.transactions_knowledge(budget_id, since_date))
+            .transpose()?
+            .flatten();
+        let fetched_since = knowledge.map_or(since_date, |(_, cached_since)| cached_since);
+        let since = fetched_since.format("%Y-%m-%d").to_string();
+        let query: Vec<(&str, String)> = std::iter::once(("since_date", since.clone()))
+            .chain(
+                knowledge.map(|(knowledge, _)| ("last_knowledge_of_server", knowledge.to_string())),
+            )
+            .collect();
+        let response: models::TransactionsResponse = self
+            .get_model(
+                "get_transactions",
+                &format!("/budgets/{}/transactions", urlencode(budget_id)),
+                &query,
             )
-        })?;
+            .with_context(|| {
+                format!("fetching transactions for budget {budget_id}, since_date {since}")
+            })?;
+        if let Some(cache) = &self.server_knowledge_cache {
+            tracing::debug!(
+                transactions = response.data.transactions.len(),
+                delta = knowledge.is_some(),
+                "fetched transactions"
+            );
+            let transactions = cache.sync_transactions(
+                budget_id,
+                fetched_since,
+                response.data.server_knowledge,
+                self.map_model(response.data.transactions, "TransactionDetail")?,
+                knowledge.is_some(),
+                since_date,
+            )?;
+            return self.map_model(transactions, "TransactionDetail");
+        }
         let resp: TransactionsResponse = self.map_model(response, "TransactionsResponse")?;
+        tracing::debug!(
+            transactions = resp.data.transactions.len(),
+            "fetched transactions"
+        );
         Ok(resp.data.transactions)
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_budget_currency_format(&self, budget_id: &str) -> Result<CurrencyFormat> {
+        let response: models::BudgetSettingsResponse = self
+            .get_model(
+                "get_budget_settings_by_id",
+                &format!("/budgets/{}/settings", urlencode(budget_id)),
+                &[],
+            )
+            .with_context(|| format!("fetching settings for budget {budget_id}"))?;
+        let resp: BudgetSettingsResponse = self.map_model(response, "BudgetSettingsResponse")?;
+        Ok(resp
+            .data
+            .settings
+            .currency_format
+            .map(CurrencyFormat::from)
+            .unwrap_or_default())
+    }
+}
+
+// --- Offline implementation ---
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+/// The budget file inside a zipped export.
+pub const ZIPPED_BUDGET_FILE: &str = "Budget.yfull";
+
+/// Serves a budget from a YNAB full-budget export (the `GET /budgets/{id}` response, or
+/// just its `budget` object), so reports can run without network access.
+///
+/// The export is normalised: transactions reference payees and categories by id and
+/// subtransactions live in their own list, so they are joined back up here.
+pub struct OfflineYnabClient {
+    budget_json: serde_json::Value,
+}
+
+impl OfflineYnabClient {
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn new(budget_json: serde_json::Value) -> Self {
+        Self { budget_json }
+    }
+
+    /// Reads a budget export, as a zip archive (see [`Self::from_zip`]) when the file
+    /// has a `.zip` extension or starts with the zip magic, otherwise as JSON.
+    #[tracing::instrument(level = "debug")]
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("reading offline budget export {path:?}"))?;
+        let is_zip = bytes.starts_with(ZIP_MAGIC)
+            || path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+        if is_zip {
+            Self::from_zip_bytes(&bytes, path)
+        } else {
+            Self::from_json_bytes(&bytes, path)
+        }
+    }
+
+    #[tracing::instrument(level = "debug")]
+    pub fn from_json(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("reading offline budget export {path:?}"))?;
+        Self::from_json_bytes(&bytes, path)
+    }
+
+    /// Reads the `Budget.yfull` file (at any depth) out of a zipped budget export.
+    #[tracing::instrument(level = "debug")]
+    pub fn from_zip(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("reading offline budget export {path:?}"))?;
+        Self::from_zip_bytes(&bytes, path)
+    }
+
+    fn from_zip_bytes(bytes: &[u8], path: &Path) -> Result<Self> {
+        let budget_file = zip_archive::read_entry(bytes, |name| {
+            name.rsplit('/').next() == Some(ZIPPED_BUDGET_FILE)
+        })
+        .with_context(|| format!("extracting {ZIPPED_BUDGET_FILE} from {path:?}"))?;
+        Self::from_json_bytes(&budget_file, path)
+    }
+
+    fn from_json_bytes(bytes: &[u8], path: &Path) -> Result<Self> {
+        let budget_json = serde_json::from_slice(bytes)
+            .with_context(|| format!("parsing offline budget export {path:?}"))?;
+        Ok(Self::new(budget_json))
+    }
+
+    fn budget(&self) -> Result<&serde_json::Map<String, serde_json::Value>> {
+        self.budget_json
+            .pointer("/data/budget")
+            .unwrap_or(&self.budget_json)
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("offline budget export is not a JSON object"))
+    }
+
+    fn field<'a>(
+        object: &'a serde_json::Map<String, serde_json::Value>,
+        key: &str,
+    ) -> Result<&'a serde_json::Value> {
+        object
+            .get(key)
+            .ok_or_else(|| anyhow::anyhow!("offline budget export is missing {key:?}"))
+    }
+
+    fn array<'a>(
+        object: &'a serde_json::Map<String, serde_json::Value>,
+        key: &str,
+    ) -> Result<&'a Vec<serde_json::Value>> {
+        Self::field(object, key)?
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("offline budget export {key:?} is not an array"))
+    }
+
+    /// The string `key` of the budget object itself.
+    fn budget_str<'a>(
+        object: &'a serde_json::Map<String, serde_json::Value>,
+        key: &str,
+    ) -> Result<&'a str> {
+        Self::field(object, key)?
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("offline budget export {key:?} is not a string"))
+    }
+
+    fn i64_field(value: &serde_json::Value, key: &str) -> Result<i64> {
+        value
+            .get(key)
+            .and_then(serde_json::Value::as_i64)
+            .ok_or_else(|| anyhow::anyhow!("offline budget export entry is missing {key:?}"))
+    }
+
+    fn str_field<'a>(value: &'a serde_json::Value, key: &str) -> Result<&'a str> {
+        value
+            .get(key)
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("offline budget export entry is missing {key:?}"))
+    }
+
+    /// Fails unless `budget_id` names the exported budget.
+    fn check_budget_id(
+        &self,
+        budget_id: &str,
+    ) -> Result<&serde_json::Map<String, serde_json::Value>> {
+        let budget = self.budget()?;
+        let exported_id = Self::budget_str(budget, "id")?;
+        if exported_id != budget_id {
+            anyhow::bail!("offline budget export holds budget {exported_id}, not {budget_id}");
+        }
+        Ok(budget)
+    }
+
+    /// `id` → `name` for the entries of the `key` array.
+    fn names_by_id(
+        budget: &serde_json::Map<String, serde_json::Value>,
+        key: &str,
+    ) -> Result<HashMap<String, String>> {
+        Self::array(budget, key)?
+            .iter()
+            .map(|entry| {
+                Ok((
+                    Self::str_field(entry, "id")?.to_string(),
+                    Self::str_field(entry, "name")?.to_string(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Deserializes an export category, filling in `category_group_name` from its
+    /// `category_group_id`.
+    fn category(
+        value: &serde_json::Value,
+        group_names: &HashMap<String, String>,
+    ) -> Result<Category> {
+        let mut value = value.clone();
+        let group_name = value
+            .get("category_group_id")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|id| group_names.get(id))
+            .cloned();
+        if let (Some(object), Some(group_name)) = (value.as_object_mut(), group_name) {
+            object
+                .entry("category_group_name")
+                .or_insert(serde_json::Value::String(group_name));
+        }
+        serde_json::from_value(value).context("deserializing offline budget export category")
+    }
+
+    /// The `categories` of the export's entry for `month`, with that month as
+    /// `YYYY-MM-01`.
+    fn month_categories(
+        budget: &serde_json::Map<String, serde_json::Value>,
+        month: NaiveDate,
+    ) -> Result<(String, &Vec<serde_json::Value>)> {
+        let month_str = first_of_month(month)?.format("%Y-%m-%d").to_string();
+        let categories = Self::array(budget, "months")?
+            .iter()
+            .find(|entry| {
+                entry.get("month").and_then(serde_json::Value::as_str) == Some(&month_str)
+            })
+            .ok_or_else(|| anyhow::anyhow!("offline budget export has no month {month_str}"))?
+            .get("categories")
+            .and_then(serde_json::Value::as_array)
+            .ok_or_else(|| {
+                anyhow::anyhow!("offline budget export month {month_str} has no categories")
+            })?;
+        Ok((month_str, categories))
+    }
+}
+
+fn is_deleted(value: &serde_json::Value) -> bool {
+    value
+        .get("deleted")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+impl YnabApi for OfflineYnabClient {
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_budgets(&self) -> Result<Vec<BudgetSummary>> {
+        let budget = self.budget()?;
+        Ok(vec![BudgetSummary {
+            id: Self::budget_str(budget, "id")?.to_string(),
+            name: Self::budget_str(budget, "name")?.to_string(),
+            last_modified_on: budget
+                .get("last_modified_on")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string),
+        }])
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_category_groups(&self, budget_id: &str) -> Result<Vec<CategoryGroup>> {
+        let budget = self.check_budget_id(budget_id)?;
+        let group_names = Self::names_by_id(budget, "category_groups")?;
+        let categories = Self::array(budget, "categories")?;
+
+        Self::array(budget, "category_groups")?
+            .iter()
+            .map(|group| {
+                let group_id = Self::str_field(group, "id")?;
+                let categories = categories
+                    .iter()
+                    .filter(|category| {
+                        category
+                            .get("category_group_id")
+                            .and_then(serde_json::Value::as_str)
+                            == Some(group_id)
+                    })
+                    .map(|category| Self::category(category, &group_names))
+                    .collect::<Result<Vec<_>>>()?;
+                let mut group: CategoryGroup = serde_json::from_value(group.clone())
+                    .context("deserializing offline budget export category group")?;
+                group.categories = categories;
+                Ok(group)
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_month_category(
+        &self,
+        budget_id: &str,
+        month: NaiveDate,
+        category_id: &str,
+    ) -> Result<Category> {
+        let budget = self.check_budget_id(budget_id)?;
+        let group_names = Self::names_by_id(budget, "category_groups")?;
+        let (month_str, categories) = Self::month_categories(budget, month)?;
+        let category = categories
+            .iter()
+            .find(|category| {
+                category.get("id").and_then(serde_json::Value::as_str) == Some(category_id)
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "offline budget export month {month_str} has no category {category_id}"
+                )
+            })?;
+        Self::category(category, &group_names)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_month_categories_batch(
+        &self,
+        budget_id: &str,
+        month: NaiveDate,
+    ) -> Result<Vec<Category>> {
+        let budget = self.check_budget_id(budget_id)?;
+        let group_names = Self::names_by_id(budget, "category_groups")?;
+        let (_, categories) = Self::month_categories(budget, month)?;
+        categories
+            .iter()
+            .map(|category| Self::category(category, &group_names))
+            .collect()
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_transactions(&self, budget_id: &str, since_date: NaiveDate) -> Result<Vec<Transaction>> {
+        let budget = self.check_budget_id(budget_id)?;
+        let payee_names = Self::names_by_id(budget, "payees")?;
+        let category_names = Self::names_by_id(budget, "categories")?;
+        let lookup = |value: &serde_json::Value, key: &str, names: &HashMap<String, String>| {
+            value
+                .get(key)
+                .and_then(serde_json::Value::as_str)
+                .and_then(|id| names.get(id))
+                .cloned()
+        };
+        let subtransactions = Self::array(budget, "subtransactions")?;
+        Self::array(budget, "transactions")?
+            .iter()
+            .filter(|txn| !is_deleted(txn))
+            .map(|txn| {
+                let id = Self::str_field(txn, "id")?;
+                let date = NaiveDate::parse_from_str(Self::str_field(txn, "date")?, "%Y-%m-%d")
+                    .with_context(|| format!("parsing date of offline transaction {id}"))?;
+                let subtransactions = subtransactions
+                    .iter()
+                    .filter(|sub| {
+                        !is_deleted(sub)
+                            && sub
+                                .get("transaction_id")
+                                .and_then(serde_json::Value::as_str)
+                                == Some(id)
+                    })
+                    .map(|sub| {
+                        let sub_id = Self::str_field(sub, "id")?;
+                        Ok(SubTransaction {
+                            id: sub_id.to_string(),
+                            amount: Self::i64_field(sub, "amount").with_context(|| {
+                                format!("reading offline subtransaction {sub_id}")
+                            })?,
+                            payee_name: lookup(sub, "payee_id", &payee_names),
+                            category_name: lookup(sub, "category_id", &category_names),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Transaction {
+                    id: id.to_string(),
+                    date,
+                    amount: Self::i64_field(txn, "amount")
+                        .with_context(|| format!("reading offline transaction {id}"))?,
+                    payee_name: lookup(txn, "payee_id", &payee_names),
+                    category_name: lookup(txn, "category_id", &category_names),
+                    subtransactions,
+                })
+            })
+            .filter(|txn| !matches!(txn, Ok(txn) if txn.date < since_date))
+            .collect()
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn get_budget_currency_format(&self, budget_id: &str) -> Result<CurrencyFormat> {
+        let budget = self.check_budget_id(budget_id)?;
+        budget
+            .get("currency_format")
+            .map(|format| {
+                serde_json::from_value::<YnabCurrencyFormat>(format.clone())
+                    .context("deserializing offline budget export currency_format")
+                    .map(CurrencyFormat::from)
+            })
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
 }
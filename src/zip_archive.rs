@@ -0,0 +1,41 @@
+//! Pulls one file out of a YNAB budget export's zip archive, via the `zip` crate
+//! (stored and deflated entries, CRC-32 checked as the entry is read).
+
+use std::io::{Cursor, Read};
+
+use anyhow::{Context, Result};
+
+/// Largest entry [`read_entry`] will extract. Budget exports run to a few megabytes, so
+/// anything declaring more is refused before it is inflated, and anything inflating to
+/// more than it declared is cut off.
+pub const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The contents of the first entry whose path (as stored, `/`-separated) satisfies
+/// `matches`.
+pub fn read_entry(archive: &[u8], matches: impl Fn(&str) -> bool) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive)).context("reading zip archive")?;
+    let name = archive
+        .file_names()
+        .filter_map(Result::ok)
+        .find(|name| matches(name))
+        .context("no matching file in zip archive")?
+        .into_owned();
+    let entry = archive
+        .by_name(&name)
+        .with_context(|| format!("reading zip entry {name}"))?;
+    let declared = entry.size();
+    if declared > MAX_ENTRY_SIZE {
+        anyhow::bail!(
+            "{name} declares {declared} bytes uncompressed, over the {MAX_ENTRY_SIZE}-byte limit"
+        );
+    }
+    let mut contents = Vec::new();
+    entry
+        .take(declared + 1)
+        .read_to_end(&mut contents)
+        .with_context(|| format!("extracting {name}"))?;
+    if contents.len() as u64 != declared {
+        anyhow::bail!("{name} does not match its declared size of {declared} bytes");
+    }
+    Ok(contents)
+}
@@ -0,0 +1,63 @@
+use crustynab::alerts::{SpendingAlert, over_budget_categories, spending_alerts};
+use polars::prelude::*;
+
+fn make_report_df(rows: Vec<(&str, f64, f64)>) -> DataFrame {
+    let names: Vec<&str> = rows.iter().map(|r| r.0).collect();
+    let budgeted: Vec<f64> = rows.iter().map(|r| r.1).collect();
+    let spent: Vec<f64> = rows.iter().map(|r| r.2).collect();
+
+    DataFrame::new(vec![
+        Column::new("category_name".into(), &names),
+        Column::new("budgeted".into(), &budgeted),
+        Column::new("spent".into(), &spent),
+    ])
+    .unwrap()
+}
+
+fn alert_lines(alerts: &[SpendingAlert]) -> String {
+    alerts
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn spending_alerts_flags_over_budget() {
+    let df = make_report_df(vec![
+        ("Groceries", 20.0, -23.5),
+        ("Rent", 100.0, -25.0),
+        ("Books", 10.0, -10.0),
+    ]);
+    let alerts = spending_alerts(&df, 100.0).unwrap();
+    insta::assert_snapshot!(alert_lines(&alerts));
+}
+
+#[test]
+fn spending_alerts_early_warning_threshold() {
+    let df = make_report_df(vec![
+        ("Groceries", 20.0, -17.0),
+        ("Rent", 100.0, -25.0),
+        ("Books", 10.0, -8.0),
+    ]);
+    let alerts = spending_alerts(&df, 80.0).unwrap();
+    insta::assert_snapshot!(alert_lines(&alerts));
+}
+
+#[test]
+fn spending_alerts_unbudgeted_spending() {
+    let df = make_report_df(vec![("Gifts", 0.0, -12.0), ("Games", 0.0, 0.0)]);
+    let alerts = spending_alerts(&df, 100.0).unwrap();
+    insta::assert_snapshot!(alert_lines(&alerts));
+}
+
+#[test]
+fn over_budget_categories_ignore_unbudgeted_spending() {
+    let df = make_report_df(vec![
+        ("Gifts", 0.0, -12.0),
+        ("Groceries", 20.0, -23.5),
+        ("Rent", 100.0, -25.0),
+    ]);
+    let over_budget = over_budget_categories(&df, 100.0).unwrap();
+    insta::assert_snapshot!(alert_lines(&over_budget));
+}
@@ -0,0 +1,47 @@
+use crustynab::ascii_report::{AsciiTableOptions, print_ascii_table, print_ascii_table_with};
+use polars::prelude::*;
+
+fn render(df: &DataFrame, options: &AsciiTableOptions) -> String {
+    let mut out = Vec::new();
+    print_ascii_table_with(df, &mut out, options).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn ascii_table_three_rows_four_columns() {
+    let df = df!(
+        "category_name" => ["Groceries", "Rent", "Cinema"],
+        "budgeted" => [50.0, 900.0, 12.5],
+        "spent" => [Some(-18.5), None, Some(-4.0)],
+        "goal_cadence" => ["monthly", "monthly", "annual"],
+    )
+    .unwrap();
+    let mut out = Vec::new();
+    print_ascii_table(&df, &mut out).unwrap();
+    insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn ascii_table_separates_total_row() {
+    let df = df!(
+        "category_group_name" => ["Essentials", "Fun", "Total"],
+        "budgeted" => [950.0, 12.5, 962.5],
+    )
+    .unwrap();
+    insta::assert_snapshot!(render(&df, &AsciiTableOptions::default()));
+}
+
+#[test]
+fn ascii_table_applies_column_width_limits() {
+    let df = df!(
+        "category_name" => ["Holiday savings for the summer", "Tea"],
+        "n" => [1_i64, 22],
+    )
+    .unwrap();
+    let options = AsciiTableOptions {
+        min_column_width: 6,
+        max_column_width: 10,
+        float_precision: 2,
+    };
+    insta::assert_snapshot!(render(&df, &options));
+}
@@ -0,0 +1,36 @@
+use std::io::Write;
+
+use crustynab::atomic_file::write_file_atomically;
+
+fn dir_entries(dir: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn write_file_atomically_replaces_the_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.html");
+    std::fs::write(&path, "old").unwrap();
+    write_file_atomically(&path, |writer| Ok(writer.write_all(b"new")?)).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+    assert_eq!(dir_entries(dir.path()), ["report.html"]);
+}
+
+#[test]
+fn write_file_atomically_removes_the_temp_file_when_writing_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.html");
+    std::fs::write(&path, "old").unwrap();
+    let result = write_file_atomically(&path, |writer| {
+        writer.write_all(b"partial")?;
+        anyhow::bail!("render failed")
+    });
+    assert!(result.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+    assert_eq!(dir_entries(dir.path()), ["report.html"]);
+}
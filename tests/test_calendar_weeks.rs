@@ -1,11 +1,14 @@
 use chrono::{Datelike, NaiveDate};
 use crustynab::calendar_weeks::{
-    month_week_for_date, month_weeks, partition_year_into_month_weeks,
+    Month, check_partition_year, complete_months_before, current_month_weeks, current_week,
+    month_week_for_date, month_weeks, next_week, partition_year_into_month_weeks, previous_week,
+    trailing_weeks,
 };
 
 #[test]
 fn partition_2024_first_week_starts_jan_1() {
-    let weeks = partition_year_into_month_weeks(2024);
+    let partition = partition_year_into_month_weeks(2024);
+    let weeks = partition.all_weeks();
     let first = &weeks[0];
     insta::assert_snapshot!(format!(
         "month={} start={} end={} week_number={}",
@@ -15,7 +18,8 @@ fn partition_2024_first_week_starts_jan_1() {
 
 #[test]
 fn partition_2024_covers_all_days() {
-    let weeks = partition_year_into_month_weeks(2024);
+    let partition = partition_year_into_month_weeks(2024);
+    let weeks = partition.all_weeks();
     let mut all_dates: Vec<NaiveDate> = weeks.iter().flat_map(|w| w.dates()).collect();
     all_dates.sort();
     all_dates.dedup();
@@ -33,7 +37,8 @@ fn partition_2024_covers_all_days() {
 
 #[test]
 fn partition_2024_no_duplicate_dates() {
-    let weeks = partition_year_into_month_weeks(2024);
+    let partition = partition_year_into_month_weeks(2024);
+    let weeks = partition.all_weeks();
     let all_dates: Vec<NaiveDate> = weeks.iter().flat_map(|w| w.dates()).collect();
     let mut sorted = all_dates.clone();
     sorted.sort();
@@ -44,9 +49,10 @@ fn partition_2024_no_duplicate_dates() {
 
 #[test]
 fn partition_2024_week_boundaries() {
-    let weeks = partition_year_into_month_weeks(2024);
+    let partition = partition_year_into_month_weeks(2024);
+    let weeks = partition.all_weeks();
     let mut issues = Vec::new();
-    for w in &weeks {
+    for w in weeks {
         let start_dow = w.week_start.weekday();
         let end_dow = w.week_end.weekday();
         let month_first = NaiveDate::from_ymd_opt(2024, w.month, 1).unwrap();
@@ -73,7 +79,7 @@ fn partition_2024_week_boundaries() {
 
 #[test]
 fn month_weeks_march_2024() {
-    let weeks = month_weeks(2024, 3);
+    let weeks = month_weeks(Month::try_from((2024, 3)).unwrap());
     let summary: Vec<String> = weeks
         .iter()
         .map(|w| format!("week {} {}-{}", w.week_number, w.week_start, w.week_end))
@@ -93,7 +99,7 @@ fn month_week_for_date_2024_03_13() {
 
 #[test]
 fn month_week_for_date_contains_date() {
-    let test_dates = vec![
+    let test_dates = [
         NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
         NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
@@ -104,7 +110,7 @@ fn month_week_for_date_contains_date() {
         .iter()
         .map(|&d| {
             let w = month_week_for_date(d).unwrap();
-            let contains = w.week_start <= d && d <= w.week_end;
+            let contains = w.contains(d);
             format!(
                 "{d} contained={contains} week={}-{}",
                 w.week_start, w.week_end
@@ -116,7 +122,8 @@ fn month_week_for_date_contains_date() {
 
 #[test]
 fn week_number_matches_partition_order() {
-    let weeks = partition_year_into_month_weeks(2024);
+    let partition = partition_year_into_month_weeks(2024);
+    let weeks = partition.all_weeks();
     let week_numbers: Vec<usize> = weeks.iter().map(|w| w.week_number).collect();
     let is_monotonic = week_numbers.windows(2).all(|pair| pair[0] <= pair[1]);
     insta::assert_snapshot!(format!(
@@ -127,3 +134,100 @@ fn week_number_matches_partition_order() {
     ));
     assert!(is_monotonic);
 }
+
+#[test]
+fn month_try_from_rejects_out_of_range() {
+    let results: Vec<String> = [(2024, 0), (2024, 1), (2024, 12), (2024, 13)]
+        .into_iter()
+        .map(|pair| match Month::try_from(pair) {
+            Ok(month) => format!("{pair:?} ok {month}"),
+            Err(err) => format!("{pair:?} err {err}"),
+        })
+        .collect();
+    insta::assert_snapshot!(results.join("\n"));
+}
+
+#[test]
+fn month_range_iterates_across_year_boundary() {
+    let start = Month::from(NaiveDate::from_ymd_opt(2024, 11, 20).unwrap());
+    let end = Month::try_from((2025, 2)).unwrap();
+    let months: Vec<String> = std::iter::successors(Some(start), |m| Some(m.succ()))
+        .take_while(|m| *m <= end)
+        .map(|m| m.to_string())
+        .collect();
+    insta::assert_snapshot!(months.join(", "));
+}
+
+#[test]
+fn current_week_contains_today() {
+    let today = chrono::Local::now().date_naive();
+    let week = current_week().unwrap();
+    insta::assert_snapshot!(week.contains(today));
+}
+
+#[test]
+fn current_month_weeks_matches_month_weeks_for_today() {
+    let today = chrono::Local::now().date_naive();
+    insta::assert_snapshot!(current_month_weeks() == month_weeks(Month::from(today)));
+}
+
+#[test]
+fn trailing_weeks_reach_into_previous_year() {
+    let week = month_week_for_date(NaiveDate::from_ymd_opt(2024, 1, 3).unwrap()).unwrap();
+    let weeks: Vec<String> = trailing_weeks(&week, 4)
+        .iter()
+        .map(|w| format!("{}..{}", w.week_start, w.week_end))
+        .collect();
+    insta::assert_snapshot!(weeks.join("\n"));
+}
+
+#[test]
+fn week_navigation_across_a_month_boundary() {
+    let week = month_week_for_date(NaiveDate::from_ymd_opt(2024, 3, 31).unwrap()).unwrap();
+    let weeks: Vec<String> = [
+        previous_week(&week).unwrap(),
+        week.clone(),
+        next_week(&week).unwrap(),
+    ]
+    .iter()
+    .map(|w| format!("{} {}..{}", w.week_number, w.week_start, w.week_end))
+    .collect();
+    insta::assert_snapshot!(weeks.join("\n"));
+}
+
+#[test]
+fn complete_months_before_cross_year_boundary() {
+    let months: Vec<String> =
+        complete_months_before(NaiveDate::from_ymd_opt(2024, 2, 10).unwrap(), 3)
+            .iter()
+            .map(|m| format!("{m} ({}..{})", m.first_day(), m.last_day()))
+            .collect();
+    insta::assert_snapshot!(months.join("\n"));
+}
+
+#[test]
+fn year_partition_accessors() {
+    let partition = partition_year_into_month_weeks(2024);
+    let february: Vec<String> = partition
+        .weeks_for_month(2)
+        .iter()
+        .map(|w| format!("{} {}..{}", w.week_number, w.week_start, w.week_end))
+        .collect();
+    let leap_day = partition
+        .week_for_date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap())
+        .map(|w| w.week_number);
+    let other_year = partition.week_for_date(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+    insta::assert_snapshot!(format!(
+        "year={}\nfebruary:\n{}\nleap day week={leap_day:?}\n2025-01-01={other_year:?}",
+        partition.year(),
+        february.join("\n")
+    ));
+}
+
+#[test]
+fn check_partition_year_rejects_years_chrono_cannot_represent() {
+    insta::assert_debug_snapshot!(
+        [2024, i32::MAX, 262_143, -262_144]
+            .map(|year| check_partition_year(year).map_err(|err| err.to_string()))
+    );
+}
@@ -1,6 +1,6 @@
 use chrono::{Datelike, Duration, NaiveDate, Weekday};
 use crustynab::calendar_weeks::{
-    month_week_for_date, month_weeks, partition_year_into_month_weeks,
+    month_week_for_date, month_weeks_raw, next_week, partition_year_into_month_weeks, previous_week,
 };
 use proptest::prelude::*;
 
@@ -32,7 +32,8 @@ proptest! {
 
     #[test]
     fn prop_partition_covers_year(year in 1900_i32..=2100_i32) {
-        let weeks = partition_year_into_month_weeks(year);
+        let partition = partition_year_into_month_weeks(year);
+        let weeks = partition.all_weeks();
         let all_days: Vec<NaiveDate> = weeks.iter().flat_map(|w| w.dates()).collect();
 
         let mut dedup = all_days.clone();
@@ -51,7 +52,7 @@ proptest! {
 
     #[test]
     fn prop_month_partition_covers_month(year in 1900_i32..=2100_i32, month in 1_u32..=12_u32) {
-        let weeks = month_weeks(year, month);
+        let weeks = month_weeks_raw(year, month);
         let all_days: Vec<NaiveDate> = weeks.iter().flat_map(|w| w.dates()).collect();
 
         let mut dedup = all_days.clone();
@@ -70,7 +71,7 @@ proptest! {
 
     #[test]
     fn prop_week_invariants(year in 1900_i32..=2100_i32, month in 1_u32..=12_u32) {
-        let weeks = month_weeks(year, month);
+        let weeks = month_weeks_raw(year, month);
         let month_first = date(year, month, 1);
         let month_last = month_last_day(year, month);
 
@@ -99,15 +100,16 @@ proptest! {
         let week = month_week_for_date(day).expect("date must resolve to month week");
 
         prop_assert_eq!(week.month, day.month());
-        prop_assert!(week.week_start <= day && day <= week.week_end);
+        prop_assert!(week.contains(day));
 
-        let month_partition = month_weeks(day.year(), day.month());
+        let month_partition = month_weeks_raw(day.year(), day.month());
         prop_assert!(month_partition.contains(&week));
     }
 
     #[test]
     fn prop_week_number_matches_partition_order(year in 1900_i32..=2100_i32) {
-        let weeks = partition_year_into_month_weeks(year);
+        let partition = partition_year_into_month_weeks(year);
+        let weeks = partition.all_weeks();
         let year_anchor = previous_sunday(date(year, 1, 1));
 
         for week in weeks {
@@ -116,4 +118,90 @@ proptest! {
             prop_assert_eq!(week.week_number, expected);
         }
     }
+
+    #[test]
+    fn prop_week_progress_fraction_spans_unit_interval(
+        year in 1900_i32..=2100_i32,
+        month in 1_u32..=12_u32,
+    ) {
+        let weeks = month_weeks_raw(year, month);
+        let fractions: Vec<f64> = weeks.iter().map(|w| w.week_progress_fraction(year)).collect();
+
+        prop_assert!(fractions.iter().all(|f| (0.0..=1.0).contains(f)));
+        prop_assert_eq!(fractions.first().copied(), Some(0.0));
+        prop_assert_eq!(fractions.last().copied(), Some(1.0));
+    }
+
+    #[test]
+    fn prop_all_weeks_have_positive_span(year in 1900_i32..=2100_i32) {
+        for week in partition_year_into_month_weeks(year).all_weeks() {
+            let span = (week.week_end - week.week_start).num_days();
+            prop_assert!(week.week_end >= week.week_start);
+            prop_assert!((0..=6).contains(&span), "span {} for {:?}", span, week);
+        }
+    }
+
+    #[test]
+    fn prop_month_weeks_no_overlap(year in 1900_i32..=2100_i32) {
+        let partition = partition_year_into_month_weeks(year);
+        let weeks = partition.all_weeks();
+        for (prev, next) in weeks.iter().zip(weeks.iter().skip(1)) {
+            if prev.month == next.month {
+                prop_assert_eq!(prev.week_end + Duration::days(1), next.week_start);
+            }
+        }
+    }
+
+    #[test]
+    fn prop_year_partition_week_for_date_contains_date(year in 1900_i32..=2100_i32, ordinal in 1_u32..=365_u32) {
+        let day = NaiveDate::from_yo_opt(year, ordinal).expect("valid ordinal");
+        let partition = partition_year_into_month_weeks(year);
+        prop_assert_eq!(partition.year(), year);
+        let week = partition.week_for_date(day).expect("every day has a week");
+        prop_assert!(week.contains(day));
+        prop_assert!(partition.weeks_for_month(day.month()).contains(&week));
+    }
+
+    #[test]
+    fn prop_overlaps_is_reflexive_and_symmetric(
+        first in full_date_strategy(date(1900, 1, 1), date(2100, 12, 31)),
+        second in full_date_strategy(date(1900, 1, 1), date(2100, 12, 31)),
+    ) {
+        let a = month_week_for_date(first).expect("date must resolve to month week");
+        let b = month_week_for_date(second).expect("date must resolve to month week");
+        prop_assert!(a.overlaps(&a));
+        prop_assert_eq!(a.overlaps(&b), b.overlaps(&a));
+    }
+
+    #[test]
+    fn prop_week_does_not_overlap_next_week(
+        day in full_date_strategy(date(1900, 1, 1), date(2100, 12, 30))
+    ) {
+        let week = month_week_for_date(day).expect("date must resolve to month week");
+        let next = month_week_for_date(week.week_end + Duration::days(1))
+            .expect("date must resolve to month week");
+        prop_assert!(!week.overlaps(&next));
+        prop_assert!(!next.overlaps(&week));
+    }
+
+    #[test]
+    fn prop_previous_and_next_week_are_inverses(
+        day in full_date_strategy(date(1900, 1, 8), date(2100, 12, 24))
+    ) {
+        let week = month_week_for_date(day).expect("date must resolve to month week");
+        let next = next_week(&week).expect("next week");
+        let previous = previous_week(&week).expect("previous week");
+        prop_assert_eq!(next.week_start, week.week_end + Duration::days(1));
+        prop_assert_eq!(previous.week_end, week.week_start - Duration::days(1));
+        prop_assert_eq!(&previous_week(&next).expect("previous of next"), &week);
+        prop_assert_eq!(&next_week(&previous).expect("next of previous"), &week);
+    }
+
+    #[test]
+    fn prop_week_navigation_crosses_year_boundaries(year in 1900_i32..=2099_i32) {
+        let last = month_week_for_date(date(year, 12, 31)).expect("last week of year");
+        let first = next_week(&last).expect("first week of next year");
+        prop_assert_eq!(first.week_start, date(year + 1, 1, 1));
+        prop_assert_eq!(&previous_week(&first).expect("back to the last week"), &last);
+    }
 }
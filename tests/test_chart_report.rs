@@ -0,0 +1,67 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+use crustynab::calendar_weeks::month_week_for_date;
+use crustynab::chart_report::{build_chart_data, build_chart_html, chart_data_json};
+use crustynab::report_metadata::ReportMetadata;
+
+fn make_weekly_spending() -> LazyFrame {
+    let week_numbers: Vec<u32> = vec![10, 10, 11];
+    let groups = vec!["Essentials", "Fun", "Essentials"];
+    let spent = vec![-12.5, -4.0, -25.0];
+
+    DataFrame::new(vec![
+        Column::new("week_number".into(), &week_numbers),
+        Column::new("category_group_name".into(), &groups),
+        Column::new("spent".into(), &spent),
+    ])
+    .unwrap()
+    .lazy()
+}
+
+fn make_group_colors() -> IndexMap<String, String> {
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    group_colors.insert("Fun".to_string(), "#f4dccb".to_string());
+    group_colors
+}
+
+fn make_week_labels() -> Vec<(usize, String)> {
+    vec![
+        (10, "Week 10 (Mar 3 - Mar 9)".to_string()),
+        (11, "Week 11 (Mar 10 - Mar 16)".to_string()),
+    ]
+}
+
+fn make_metadata() -> ReportMetadata {
+    let week = month_week_for_date(NaiveDate::from_ymd_opt(2024, 3, 14).unwrap()).unwrap();
+    let generated_at = Utc.with_ymd_and_hms(2024, 3, 16, 14, 30, 0).unwrap();
+    ReportMetadata::new(&week, "My Budget", generated_at)
+}
+
+#[test]
+fn chart_data_json_has_dataset_per_group() {
+    let chart = build_chart_data(
+        make_weekly_spending(),
+        &make_group_colors(),
+        &make_week_labels(),
+    )
+    .unwrap();
+    insta::assert_snapshot!(chart_data_json(&chart, &make_metadata()).unwrap());
+}
+
+#[test]
+fn chart_html_inlines_data() {
+    let chart = build_chart_data(
+        make_weekly_spending(),
+        &make_group_colors(),
+        &make_week_labels(),
+    )
+    .unwrap();
+    let title = NaiveDate::from_ymd_opt(2024, 3, 13)
+        .unwrap()
+        .format("%B %Y spending")
+        .to_string();
+    insta::assert_snapshot!(build_chart_html(&chart, &title).unwrap());
+}
@@ -2,13 +2,13 @@ use std::collections::HashSet;
 
 use anyhow::Result;
 use chrono::NaiveDate;
-use indexmap::IndexMap;
 use polars::prelude::*;
 
-use crustynab::calendar_weeks::month_week_for_date;
+use crustynab::CsvRenderer;
+use crustynab::calendar_weeks::{month_week_for_date, week_position_in_month};
 use crustynab::config::{Config, OutputFormat, SimpleOutputFormat};
 use crustynab::report;
-use crustynab::visual_report::build_visual_report_html;
+use crustynab::visual_report::{VisualSections, build_visual_report_html};
 use crustynab::ynab::{Category, SubTransaction, Transaction};
 
 fn make_categories() -> Vec<Category> {
@@ -21,7 +21,10 @@ fn make_categories() -> Vec<Category> {
             balance: 31500,
             goal_cadence: Some(1),
             goal_target: Some(60000),
+            goal_percentage_complete: Some(53),
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
         Category {
             id: "cat-rent".into(),
@@ -29,9 +32,12 @@ fn make_categories() -> Vec<Category> {
             category_group_name: Some("Essentials".into()),
             budgeted: 100000,
             balance: 75000,
-            goal_cadence: Some(12),
+            goal_cadence: Some(13),
             goal_target: Some(120000),
+            goal_percentage_complete: Some(50),
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
         Category {
             id: "cat-books".into(),
@@ -41,7 +47,10 @@ fn make_categories() -> Vec<Category> {
             balance: 6000,
             goal_cadence: Some(1),
             goal_target: None,
+            goal_percentage_complete: None,
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
         Category {
             id: "cat-games".into(),
@@ -51,7 +60,10 @@ fn make_categories() -> Vec<Category> {
             balance: 17000,
             goal_cadence: Some(1),
             goal_target: None,
+            goal_percentage_complete: None,
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
     ]
 }
@@ -74,11 +86,13 @@ fn make_transactions() -> Vec<Transaction> {
             category_name: Some("Split".into()),
             subtransactions: vec![
                 SubTransaction {
+                    id: "txn-4-a".into(),
                     amount: -6000,
                     payee_name: None,
                     category_name: Some("Groceries".into()),
                 },
                 SubTransaction {
+                    id: "txn-4-b".into(),
                     amount: -4000,
                     payee_name: None,
                     category_name: Some("Books".into()),
@@ -105,18 +119,13 @@ fn make_transactions() -> Vec<Transaction> {
 }
 
 fn make_config(show_all_rows: bool) -> Config {
-    let mut watch_list = IndexMap::new();
-    watch_list.insert("Essentials".to_string(), "#dfe7f5".to_string());
-    watch_list.insert("Fun".to_string(), "#f4dccb".to_string());
-
-    Config {
-        budget_name: "Test Budget".to_string(),
-        personal_access_token: "token".to_string(),
-        category_group_watch_list: watch_list,
-        resolution_date: Some(NaiveDate::from_ymd_opt(2024, 3, 13).unwrap()),
-        show_all_rows,
-        output_format: OutputFormat::Simple(SimpleOutputFormat::PolarsPrint),
-    }
+    Config::builder("Test Budget", "token")
+        .watch_group("Essentials", "#dfe7f5")
+        .watch_group("Fun", "#f4dccb")
+        .resolution_date(NaiveDate::from_ymd_opt(2024, 3, 13).unwrap())
+        .show_all_rows(show_all_rows)
+        .output_format(OutputFormat::Simple(SimpleOutputFormat::PolarsPrint))
+        .build()
 }
 
 fn run_report(cfg: &Config) -> Result<(String, LazyFrame, LazyFrame)> {
@@ -128,14 +137,21 @@ fn run_report(cfg: &Config) -> Result<(String, LazyFrame, LazyFrame)> {
     let report_start = report_week.week_start;
     let report_end = report_week.week_end;
 
-    let categories_budgeted = report::categories_to_polars(&categories)?;
+    let categories_budgeted = report::with_free_balance(report::categories_to_polars(&categories)?);
     let transactions_frame = report::transactions_to_polars(&transactions)?;
     let transactions_frame =
         report::relevant_transactions(transactions_frame, report_start, report_end);
 
     let cat_names: HashSet<String> = categories.iter().map(|c| c.name.clone()).collect();
+    let report_table = report::build_report_table(
+        categories_budgeted,
+        transactions_frame,
+        &cat_names,
+        report::TransactionSide::All,
+    )?;
+    let (week_of_month, weeks_in_month) = week_position_in_month(&report_week);
     let report_table =
-        report::build_report_table(categories_budgeted, transactions_frame, &cat_names)?;
+        report::with_weighted_percent_spent(report_table, week_of_month, weeks_in_month)?;
 
     let report_table_full = report_table.clone();
     let report_table_display = if cfg.show_all_rows {
@@ -144,8 +160,10 @@ fn run_report(cfg: &Config) -> Result<(String, LazyFrame, LazyFrame)> {
         report_table.filter(col("spent").neq(lit(0.0)))
     };
 
-    let category_group_totals =
-        report::build_category_group_totals_table(report_table_full.clone())?;
+    let category_group_totals = report::build_category_group_totals_table(
+        report_table_full.clone(),
+        report::GroupTotalsSortOrder::default(),
+    )?;
 
     let week_year = report_week.week_start.year();
     let week_number = report_week.week_number;
@@ -160,17 +178,15 @@ fn run_report(cfg: &Config) -> Result<(String, LazyFrame, LazyFrame)> {
 }
 
 fn write_csv_string(df: &mut DataFrame) -> String {
-    let mut buf = Vec::new();
-    CsvWriter::new(&mut buf).finish(df).unwrap();
-    String::from_utf8(buf).unwrap()
+    CsvRenderer::render(df).unwrap()
 }
 
 fn format_short_date(date: NaiveDate) -> String {
     let formatted = date.format("%b %d").to_string();
     if let Some(space_pos) = formatted.rfind(' ') {
         let (prefix, day_part) = formatted.split_at(space_pos + 1);
-        if day_part.starts_with('0') {
-            return format!("{}{}", prefix, &day_part[1..]);
+        if let Some(stripped) = day_part.strip_prefix('0') {
+            return format!("{prefix}{stripped}");
         }
     }
     formatted
@@ -249,7 +265,8 @@ fn golden_visual_output() {
 
     let categories = make_categories();
     let transactions = make_transactions();
-    let categories_budgeted = report::categories_to_polars(&categories).unwrap();
+    let categories_budgeted =
+        report::with_free_balance(report::categories_to_polars(&categories).unwrap());
     let transactions_frame = report::transactions_to_polars(&transactions).unwrap();
     let transactions_frame = report::relevant_transactions(
         transactions_frame,
@@ -257,8 +274,16 @@ fn golden_visual_output() {
         report_week.week_end,
     );
     let cat_names: HashSet<String> = categories.iter().map(|c| c.name.clone()).collect();
+    let report_table = report::build_report_table(
+        categories_budgeted,
+        transactions_frame,
+        &cat_names,
+        report::TransactionSide::All,
+    )
+    .unwrap();
+    let (week_of_month, weeks_in_month) = week_position_in_month(&report_week);
     let report_table =
-        report::build_report_table(categories_budgeted, transactions_frame, &cat_names).unwrap();
+        report::with_weighted_percent_spent(report_table, week_of_month, weeks_in_month).unwrap();
 
     use chrono::Datelike;
     let week_number = report_week.week_number;
@@ -272,6 +297,7 @@ fn golden_visual_output() {
         &week_label,
         report_week.week_start.year(),
         true,
+        VisualSections::default(),
     )
     .unwrap();
     insta::assert_snapshot!(html);
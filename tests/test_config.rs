@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+
+use crustynab::config::{
+    Config, ConfigOverride, dump_config, load_config, load_config_with_overlay,
+    load_config_with_overrides, watch_list_from_lookup,
+};
+
+fn lookup_from(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |key| vars.get(key).cloned()
+}
+
+#[test]
+fn watch_list_from_lookup_stops_at_first_gap() {
+    let lookup = lookup_from(&[
+        ("CRUSTYNAB_GROUP_0_NAME", "Essentials"),
+        ("CRUSTYNAB_GROUP_0_COLOR", "#dfe7f5"),
+        ("CRUSTYNAB_GROUP_1_NAME", "Fun"),
+        ("CRUSTYNAB_GROUP_1_COLOR", "#f4dccb"),
+        ("CRUSTYNAB_GROUP_3_NAME", "Skipped"),
+        ("CRUSTYNAB_GROUP_3_COLOR", "#000000"),
+    ]);
+    let watch_list = watch_list_from_lookup(lookup).unwrap();
+    insta::assert_snapshot!(format!("{watch_list:?}"));
+}
+
+#[test]
+fn watch_list_from_lookup_rejects_invalid_color() {
+    let lookup = lookup_from(&[
+        ("CRUSTYNAB_GROUP_0_NAME", "Essentials"),
+        ("CRUSTYNAB_GROUP_0_COLOR", "blue"),
+    ]);
+    let err = watch_list_from_lookup(lookup).err().unwrap();
+    insta::assert_snapshot!(err.to_string());
+}
+
+// The only test in this binary that touches the process environment.
+#[test]
+fn load_config_falls_back_to_env_watch_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r#"{"budgetName": "Test", "personalAccessToken": "token", "categoryGroupWatchList": {}}"#,
+    )
+    .unwrap();
+
+    // SAFETY: no other test in this binary reads or writes the environment
+    unsafe {
+        std::env::set_var("CRUSTYNAB_GROUP_0_NAME", "Essentials");
+        std::env::set_var("CRUSTYNAB_GROUP_0_COLOR", "#dfe7f5");
+    }
+    let config = load_config(&path);
+    // SAFETY: as above
+    unsafe {
+        std::env::remove_var("CRUSTYNAB_GROUP_0_NAME");
+        std::env::remove_var("CRUSTYNAB_GROUP_0_COLOR");
+    }
+
+    insta::assert_snapshot!(format!("{:?}", config.unwrap().category_group_watch_list));
+}
+
+#[test]
+fn dump_config_redacts_token() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r##"{
+            "budgetName": "Test",
+            "personalAccessToken": "super-secret",
+            "categoryGroupWatchList": {"Fun": "#f4dccb", "Essentials": "#dfe7f5"},
+            "resolutionDate": "2024-03-13",
+            "outputFormat": {"csv_output": "report.csv"}
+        }"##,
+    )
+    .unwrap();
+    let config = load_config(&path).unwrap();
+    insta::assert_snapshot!(dump_config(&config).unwrap());
+}
+
+#[test]
+fn load_config_keeps_watch_list_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r##"{
+            "budgetName": "Test",
+            "personalAccessToken": "token",
+            "categoryGroupWatchList": {"Savings": "#a", "Essentials": "#b", "Fun": "#c"},
+            "showAllRows": false
+        }"##,
+    )
+    .unwrap();
+    let with_override: ConfigOverride = "showAllRows=true".parse().unwrap();
+    let orders: Vec<Vec<String>> = [
+        load_config(&path).unwrap(),
+        load_config_with_overrides(&path, &[with_override]).unwrap(),
+    ]
+    .into_iter()
+    .map(|config| config.category_group_watch_list.into_keys().collect())
+    .collect();
+    insta::assert_debug_snapshot!(orders);
+}
+
+const OVERRIDE_BASE_CONFIG: &str = r##"{
+    "budgetName": "Test",
+    "personalAccessToken": "token",
+    "categoryGroupWatchList": {"Fun": "#f4dccb"},
+    "showAllRows": false
+}"##;
+
+fn load_with_overrides(overrides: &[&str]) -> anyhow::Result<crustynab::config::Config> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, OVERRIDE_BASE_CONFIG).unwrap();
+    let overrides: Vec<ConfigOverride> = overrides.iter().map(|o| o.parse().unwrap()).collect();
+    load_config_with_overrides(&path, &overrides)
+}
+
+#[test]
+fn config_override_sets_budget_name() {
+    let config = load_with_overrides(&["budget_name=2024"]).unwrap();
+    insta::assert_snapshot!(config.budget_name);
+}
+
+#[test]
+fn config_override_sets_show_all_rows() {
+    let config = load_with_overrides(&["showAllRows=true"]).unwrap();
+    insta::assert_snapshot!(config.show_all_rows);
+}
+
+#[test]
+fn config_override_sets_resolution_date() {
+    let config = load_with_overrides(&["resolution_date=2024-03-13"]).unwrap();
+    insta::assert_debug_snapshot!(config.resolution_date);
+}
+
+#[test]
+fn config_override_sets_nested_output_format() {
+    let config = load_with_overrides(&["output_format.csv_output=/tmp/report.csv"]).unwrap();
+    insta::assert_debug_snapshot!(config.output_format);
+}
+
+#[test]
+fn config_override_sets_on_missing_category() {
+    let config = load_with_overrides(&["on_missing_category=warn"]).unwrap();
+    insta::assert_debug_snapshot!(config.on_missing_category);
+}
+
+#[test]
+fn output_format_csv_append_parses_to_append_variant() {
+    let config = load_with_overrides(&["output_format.csv_append=/tmp/log.csv"]).unwrap();
+    insta::assert_debug_snapshot!(config.output_format);
+}
+
+#[test]
+fn config_override_rejects_unknown_key() {
+    let err = load_with_overrides(&["budget_nmae=Typo"]).unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn config_override_requires_equals_sign() {
+    let err = "budget_name".parse::<ConfigOverride>().unwrap_err();
+    insta::assert_snapshot!(err.to_string());
+}
+
+#[test]
+fn exclude_system_groups_defaults_to_true() {
+    let cfg = load_with_overrides(&[]).unwrap();
+    insta::assert_snapshot!(cfg.exclude_system_groups);
+}
+
+#[test]
+fn exclude_transfers_defaults_to_true() {
+    let cfg = load_with_overrides(&[]).unwrap();
+    insta::assert_snapshot!(cfg.exclude_transfers);
+}
+
+#[test]
+fn for_budget_matches_deserialized_defaults() {
+    let parsed: Config = serde_json::from_value(serde_json::json!({
+        "budgetName": "Household",
+        "personalAccessToken": "token",
+        "categoryGroupWatchList": {}
+    }))
+    .unwrap();
+    let built = Config::for_budget("Household", "token");
+    assert_eq!(format!("{built:?}"), format!("{parsed:?}"));
+}
+
+#[test]
+fn builder_sets_optional_fields() {
+    let cfg = Config::builder("Household", "token")
+        .watch_group("Essentials", "#dfe7f5")
+        .show_all_rows(true)
+        .alert_threshold_percent(80.0)
+        .csv_delimiter(';')
+        .build();
+    insta::assert_snapshot!(dump_config(&cfg).unwrap());
+}
+
+#[test]
+fn merge_takes_overlay_show_all_rows() {
+    let base = Config::builder("Household", "token")
+        .show_all_rows(false)
+        .build();
+    let overlay = Config::builder("Household", "token")
+        .show_all_rows(true)
+        .build();
+    insta::assert_snapshot!(Config::merge(base, overlay).show_all_rows);
+}
+
+#[test]
+fn merge_overrides_and_appends_watch_list_groups() {
+    let base = Config::builder("Household", "base-token")
+        .watch_group("Essentials", "#dfe7f5")
+        .watch_group("Fun", "#f4dccb")
+        .build();
+    let overlay = Config::builder("Household", "env-token")
+        .watch_group("Fun", "#000000")
+        .watch_group("Travel", "#cfe2f3")
+        .build();
+    insta::assert_snapshot!(format!(
+        "{:?}",
+        Config::merge(base, overlay).category_group_watch_list
+    ));
+}
+
+fn load_with_overlay(overlay: &str) -> anyhow::Result<Config> {
+    let dir = tempfile::tempdir().unwrap();
+    let base_path = dir.path().join("base.json");
+    let overlay_path = dir.path().join("env.json");
+    std::fs::write(
+        &base_path,
+        r##"{
+            "budgetName": "Household",
+            "personalAccessToken": "base-token",
+            "categoryGroupWatchList": {"Essentials": "#dfe7f5", "Fun": "#f4dccb"},
+            "showAllRows": true,
+            "outputFormat": "polars_print"
+        }"##,
+    )
+    .unwrap();
+    std::fs::write(&overlay_path, overlay).unwrap();
+    load_config_with_overlay(&base_path, Some(&overlay_path), &[])
+}
+
+#[test]
+fn overlay_file_replaces_only_the_keys_it_sets() {
+    let cfg = load_with_overlay(
+        r##"{
+            "personalAccessToken": "env-token",
+            "categoryGroupWatchList": {"Fun": "#000000", "Travel": "#cfe2f3"},
+            "outputFormat": {"csv_output": "/srv/reports/week.csv"}
+        }"##,
+    )
+    .unwrap();
+    insta::assert_snapshot!(dump_config(&cfg).unwrap());
+}
+
+#[test]
+fn overlay_file_appends_new_watch_list_groups() {
+    let cfg = load_with_overlay(
+        r##"{"categoryGroupWatchList": {"Archive": "#eeeeee", "Fun": "#000000"}}"##,
+    )
+    .unwrap();
+    insta::assert_debug_snapshot!(cfg.category_group_watch_list);
+}
+
+#[test]
+fn overlay_file_must_be_an_object() {
+    let err = load_with_overlay("[]").unwrap_err();
+    insta::assert_snapshot!(err.root_cause().to_string());
+}
+
+fn display_config() -> Config {
+    Config::builder("Household", "super-secret-token")
+        .watch_group("Essentials", "#dfe7f5")
+        .watch_group("Fun", "#f4dccb")
+        .timezone("Europe/London")
+        .budget_override("Groceries", 75.0)
+        .build()
+}
+
+#[test]
+fn display_shows_settings_one_per_line() {
+    insta::assert_snapshot!(display_config().to_string());
+}
+
+#[test]
+fn display_has_every_config_key_and_never_the_token() {
+    let cfg = display_config();
+    let shown = cfg.to_string();
+    let keys: Vec<String> = serde_json::to_value(&cfg)
+        .unwrap()
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect();
+    let missing: Vec<&String> = keys
+        .iter()
+        .filter(|key| {
+            !shown
+                .lines()
+                .any(|line| line.starts_with(&format!("{key}: ")))
+        })
+        .collect();
+    assert!(missing.is_empty(), "missing keys {missing:?} in\n{shown}");
+    assert!(!shown.contains("super-secret-token"), "{shown}");
+}
+
+#[test]
+fn debug_redacts_the_token() {
+    let debug = format!("{:?}", display_config());
+    assert!(!debug.contains("super-secret-token"), "{debug}");
+    assert!(
+        debug.contains(&format!(
+            "personal_access_token: {:?}",
+            crustynab::config::REDACTED
+        )),
+        "{debug}"
+    );
+}
+
+/// 01:00 UTC on New Year's Day 2025.
+fn new_year_utc() -> chrono::DateTime<chrono::Utc> {
+    chrono::NaiveDate::from_ymd_opt(2025, 1, 1)
+        .and_then(|day| day.and_hms_opt(1, 0, 0))
+        .unwrap()
+        .and_utc()
+}
+
+#[test]
+fn timezone_resolves_date_in_that_zone() {
+    let cfg = Config::builder("Household", "token")
+        .timezone("America/New_York")
+        .build();
+    insta::assert_snapshot!(cfg.date_at(new_year_utc()).unwrap());
+}
+
+#[test]
+fn timezone_rejects_unknown_zone() {
+    let cfg = Config::builder("Household", "token")
+        .timezone("Mars/Olympus_Mons")
+        .build();
+    insta::assert_snapshot!(format!("{:#}", cfg.date_at(new_year_utc()).unwrap_err()));
+}
+
+#[test]
+fn slack_webhook_is_redacted_in_dump_display_and_debug() {
+    let cfg = Config::builder("Household", "token")
+        .output_format(crustynab::config::OutputFormat::SlackBlocks {
+            slack_webhook: "https://hooks.slack.com/services/T000/B000/secret".into(),
+        })
+        .build();
+    let shown = [
+        dump_config(&cfg).unwrap(),
+        cfg.to_string(),
+        format!("{cfg:?}"),
+    ];
+    insta::assert_debug_snapshot!(
+        shown
+            .iter()
+            .map(|text| text.contains("secret"))
+            .collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,118 @@
+use chrono::NaiveDate;
+use crustynab::CsvRenderer;
+use crustynab::config::Config;
+use crustynab::csv_report::CsvOptions;
+use polars::prelude::*;
+
+fn spending_frame() -> DataFrame {
+    let days: Vec<NaiveDate> = [12, 14]
+        .into_iter()
+        .map(|day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap())
+        .collect();
+    DataFrame::new(vec![
+        Column::new("category_name".into(), &["Groceries", "Books, used"]),
+        Column::new("date".into(), days),
+        Column::new("spent".into(), &[-18.5, -4.0]),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn render_uses_commas_and_a_header() {
+    insta::assert_snapshot!(CsvRenderer::render(&mut spending_frame()).unwrap());
+}
+
+#[test]
+fn render_with_options_applies_delimiter_header_and_date_format() {
+    let opts = CsvOptions {
+        delimiter: b';',
+        include_header: false,
+        date_format: Some("%d/%m/%Y".to_string()),
+    };
+    insta::assert_snapshot!(
+        CsvRenderer::render_with_options(&mut spending_frame(), &opts).unwrap()
+    );
+}
+
+#[test]
+fn render_frame_uses_held_options() {
+    let cfg = Config::builder("Household", "token")
+        .csv_delimiter('\t')
+        .build();
+    let renderer = CsvRenderer::new(CsvOptions::from_config(&cfg).unwrap());
+    insta::assert_snapshot!(renderer.render_frame(&mut spending_frame()).unwrap());
+}
+
+#[test]
+fn csv_options_reject_non_ascii_delimiter() {
+    let cfg = Config::builder("Household", "token")
+        .csv_delimiter('§')
+        .build();
+    insta::assert_snapshot!(CsvOptions::from_config(&cfg).unwrap_err());
+}
+
+fn weekly_frames() -> Vec<DataFrame> {
+    [(-18.5, -4.0), (-22.0, 0.0), (-9.75, -31.2)]
+        .into_iter()
+        .map(|(groceries, books)| {
+            DataFrame::new(vec![
+                Column::new("category_name".into(), &["Groceries", "Books, used"]),
+                Column::new("spent".into(), &[groceries, books]),
+            ])
+            .unwrap()
+        })
+        .collect()
+}
+
+fn append_weekly_frames(path: &std::path::Path) {
+    let renderer = CsvRenderer::default();
+    for (week, mut df) in (10..).zip(weekly_frames()) {
+        renderer
+            .append_section(path, &mut df, &format!("Week {week}, 2024-03-03"))
+            .unwrap();
+    }
+}
+
+#[test]
+fn append_section_writes_header_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log.csv");
+    append_weekly_frames(&path);
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn append_section_sections_parse_back_to_the_original_frames() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log.csv");
+    append_weekly_frames(&path);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let sections: Vec<Vec<&str>> = text.lines().fold(Vec::new(), |mut sections, line| {
+        if line.starts_with("# ") {
+            sections.push(Vec::new());
+        } else if let Some(section) = sections.last_mut() {
+            section.push(line);
+        }
+        sections
+    });
+    let header = sections[0][0];
+    let parsed: Vec<DataFrame> = sections
+        .iter()
+        .enumerate()
+        .map(|(i, rows)| {
+            let rows = if i == 0 { &rows[1..] } else { &rows[..] };
+            let csv = format!("{header}\n{}\n", rows.join("\n"));
+            CsvReadOptions::default()
+                .with_has_header(true)
+                .into_reader_with_file_handle(std::io::Cursor::new(csv.into_bytes()))
+                .finish()
+                .unwrap()
+        })
+        .collect();
+
+    assert_eq!(parsed.len(), 3);
+    for (parsed, original) in parsed.iter().zip(weekly_frames()) {
+        assert!(parsed.equals_missing(&original), "{parsed} != {original}");
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use crustynab::goals::GoalTracker;
+use crustynab::ynab::Category;
+
+fn category(budgeted: i64, goal_cadence: Option<i32>, goal_target: Option<i64>) -> Category {
+    Category {
+        id: "c1".into(),
+        name: "Holidays".into(),
+        category_group_name: Some("Fun".into()),
+        budgeted,
+        balance: 0,
+        goal_cadence,
+        goal_target,
+        goal_percentage_complete: None,
+        goal_type: None,
+        hidden: false,
+        deleted: false,
+    }
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn planned_annual_scales_monthly_budgets_to_a_year() {
+    insta::assert_debug_snapshot!((
+        GoalTracker::planned_annual(&category(50_000, Some(1), Some(50_000))),
+        GoalTracker::planned_annual(&category(1_200_000, Some(13), Some(1_200_000))),
+        GoalTracker::planned_annual(&category(30_000, None, None)),
+    ));
+}
+
+#[test]
+fn planned_per_month_is_a_twelfth_of_the_year() {
+    insta::assert_debug_snapshot!((
+        GoalTracker::planned_per_month(&category(50_000, Some(1), Some(50_000))),
+        GoalTracker::planned_per_month(&category(1_200_000, Some(13), Some(1_200_000))),
+    ));
+}
+
+#[test]
+fn percent_of_month_elapsed_clamps_outside_the_month() {
+    insta::assert_debug_snapshot!([
+        GoalTracker::percent_of_month_elapsed(3, 2024, date(2024, 2, 28)),
+        GoalTracker::percent_of_month_elapsed(2, 2024, date(2024, 2, 15)),
+        GoalTracker::percent_of_month_elapsed(3, 2024, date(2024, 3, 31)),
+        GoalTracker::percent_of_month_elapsed(3, 2024, date(2024, 5, 1)),
+        GoalTracker::percent_of_month_elapsed(13, 2024, date(2024, 5, 1)),
+    ]);
+}
+
+#[test]
+fn is_on_track_allows_the_goal_progress_tolerance() {
+    // A £1,200 annual goal expects £300 of outflow by the end of March.
+    let holidays = category(1_200_000, Some(13), Some(1_200_000));
+    let as_of = date(2024, 3, 20);
+    insta::assert_debug_snapshot!([
+        GoalTracker::is_on_track(&holidays, -300.0, as_of),
+        GoalTracker::is_on_track(&holidays, -275.0, as_of),
+        GoalTracker::is_on_track(&holidays, -260.0, as_of),
+        GoalTracker::is_on_track(&holidays, -340.0, as_of),
+    ]);
+}
+
+#[test]
+fn is_on_track_holds_for_categories_without_an_annual_goal() {
+    let books = category(10_000, None, None);
+    let groceries = category(50_000, Some(1), Some(50_000));
+    insta::assert_debug_snapshot!([
+        GoalTracker::is_on_track(&books, -2.5, date(2024, 3, 1)),
+        GoalTracker::is_on_track(&groceries, -400.0, date(2024, 3, 1)),
+    ]);
+}
+
+#[test]
+fn monthly_target_prorates_annual_and_quarterly_goals() {
+    insta::assert_debug_snapshot!([
+        GoalTracker::monthly_target(1200.0, "annual"),
+        GoalTracker::monthly_target(1200.0, "quarterly"),
+        GoalTracker::monthly_target(1200.0, "monthly"),
+        GoalTracker::monthly_target(1200.0, "unknown"),
+    ]);
+}
+
+#[test]
+fn goal_status_classifies_against_the_expected_share() {
+    let expected = GoalTracker::expected_ytd(1200.0, 3);
+    insta::assert_debug_snapshot!((
+        expected,
+        [
+            GoalTracker::goal_status(300.0, expected),
+            GoalTracker::goal_status(260.0, expected),
+            GoalTracker::goal_status(340.0, expected),
+        ]
+    ));
+}
@@ -0,0 +1,27 @@
+use crustynab::json_report::write_ndjson_rows;
+use polars::prelude::*;
+
+#[test]
+fn write_ndjson_rows_tags_each_row_with_its_table() {
+    let df = df!(
+        "category_name" => ["Groceries", "Books"],
+        "spent" => [Some(-18.5), None],
+        "goal_percent_complete" => [Some(53), None],
+    )
+    .unwrap();
+    let mut out = Vec::new();
+    write_ndjson_rows(&df, "report", &mut out).unwrap();
+    insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn write_ndjson_rows_writes_dates_as_iso_strings_and_nan_as_null() {
+    let df = df!(
+        "date" => [chrono::NaiveDate::from_ymd_opt(2024, 3, 10).unwrap()],
+        "percent_spent" => [f64::NAN],
+    )
+    .unwrap();
+    let mut out = Vec::new();
+    write_ndjson_rows(&df, "totals", &mut out).unwrap();
+    insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
@@ -0,0 +1,96 @@
+use std::sync::{Arc, Barrier, Mutex};
+
+use crustynab::logging::subscriber;
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedBuffer {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+}
+
+/// The lines a subscriber filtering by `spec` writes for a fixed set of events.
+fn logged(spec: &str) -> Vec<String> {
+    let buffer = SharedBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = subscriber(spec, move || writer.clone()).unwrap();
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::debug!(target: "crustynab::report", "report debug");
+        tracing::trace!(target: "crustynab::report", "report trace");
+        tracing::info!(target: "crustynab::ynab", "ynab info");
+        tracing::debug!(target: "crustynab::ynab", "ynab debug");
+        tracing::error!(target: "hyper::client", "hyper error");
+        let _span =
+            tracing::debug_span!(target: "crustynab::report", "build_report_table").entered();
+        tracing::trace!(target: "crustynab::report", "in span trace");
+    });
+    buffer.lines()
+}
+
+#[test]
+fn subscriber_filters_by_rust_log_directives() {
+    let lines: Vec<String> = [
+        "debug",
+        "warn",
+        "warn,crustynab::report=debug",
+        "crustynab=trace",
+        "[build_report_table]=trace",
+        "off",
+    ]
+    .into_iter()
+    .map(|spec| format!("{spec}:\n  {}", logged(spec).join("\n  ")))
+    .collect();
+    insta::assert_snapshot!(lines.join("\n"));
+}
+
+#[test]
+fn subscriber_rejects_unknown_level() {
+    let err = subscriber("crustynab=loud", std::io::sink).err().unwrap();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn span_paths_only_include_the_logging_threads_spans() {
+    let buffer = SharedBuffer::default();
+    let writer = buffer.clone();
+    let dispatch = tracing::Dispatch::new(subscriber("debug", move || writer.clone()).unwrap());
+    let both_entered = Arc::new(Barrier::new(2));
+    let handles: Vec<_> = ["first", "second"]
+        .into_iter()
+        .map(|worker| {
+            let dispatch = dispatch.clone();
+            let both_entered = Arc::clone(&both_entered);
+            std::thread::spawn(move || {
+                tracing::dispatcher::with_default(&dispatch, || {
+                    let _span = tracing::debug_span!("worker", name = worker).entered();
+                    both_entered.wait();
+                    tracing::debug!("working");
+                });
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut lines = buffer.lines();
+    lines.sort();
+    insta::assert_snapshot!(lines.join("\n"));
+}
@@ -0,0 +1,26 @@
+use crustynab::names::sanitize_category_name;
+
+#[test]
+fn sanitize_strips_deleted_suffix() {
+    insta::assert_snapshot!(sanitize_category_name("Phone Plan (deleted)"));
+}
+
+#[test]
+fn sanitize_strips_year_suffix() {
+    insta::assert_snapshot!(sanitize_category_name("Holiday (2019)"));
+}
+
+#[test]
+fn sanitize_strips_stacked_suffixes() {
+    insta::assert_snapshot!(sanitize_category_name("Holiday (2019) (deleted)"));
+}
+
+#[test]
+fn sanitize_trims_whitespace() {
+    insta::assert_snapshot!(sanitize_category_name("  Groceries \t"));
+}
+
+#[test]
+fn sanitize_keeps_non_year_parentheses() {
+    insta::assert_snapshot!(sanitize_category_name("Kids (school) (12345)"));
+}
@@ -0,0 +1,14 @@
+use crustynab::names::sanitize_category_name;
+use proptest::prelude::*;
+use proptest::string::string_regex;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(96))]
+
+    #[test]
+    fn prop_sanitize_leaves_plain_names_unchanged(
+        name in string_regex("[A-Za-z0-9&'][A-Za-z0-9 &'-]{0,20}[A-Za-z0-9&']").expect("regex"),
+    ) {
+        prop_assert_eq!(sanitize_category_name(&name), name.as_str());
+    }
+}
@@ -1,18 +1,26 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use chrono::NaiveDate;
-use crustynab::report;
-use crustynab::ynab::{BudgetSummary, Category, CategoryGroup, SubTransaction, Transaction};
+use crustynab::calendar_weeks::{Month, month_weeks};
+use crustynab::goals;
+use crustynab::report::{self, CategoryFrame, TransactionFrame};
+use crustynab::server_knowledge_cache::KnownCategory;
+use crustynab::ynab::{
+    BudgetSummary, Category, CategoryGroup, GoalType, SubTransaction, Transaction,
+};
+use polars::prelude::{Column, DataFrame, IntoLazy, col};
 
 fn make_budget_summaries() -> Vec<BudgetSummary> {
     vec![
         BudgetSummary {
             id: "b1".into(),
             name: "Budget A".into(),
+            last_modified_on: Some("2024-03-14T09:30:00+00:00".into()),
         },
         BudgetSummary {
             id: "b2".into(),
             name: "Budget B".into(),
+            last_modified_on: None,
         },
     ]
 }
@@ -33,7 +41,10 @@ fn make_category_groups() -> Vec<CategoryGroup> {
                     balance: 31500,
                     goal_cadence: Some(1),
                     goal_target: Some(60000),
+                    goal_percentage_complete: Some(53),
+                    goal_type: None,
                     hidden: false,
+                    deleted: false,
                 },
                 Category {
                     id: "c2".into(),
@@ -41,9 +52,12 @@ fn make_category_groups() -> Vec<CategoryGroup> {
                     category_group_name: Some("Essentials".into()),
                     budgeted: 100000,
                     balance: 75000,
-                    goal_cadence: Some(12),
+                    goal_cadence: Some(13),
                     goal_target: Some(120000),
+                    goal_percentage_complete: Some(100),
+                    goal_type: None,
                     hidden: false,
+                    deleted: false,
                 },
             ],
         },
@@ -61,7 +75,10 @@ fn make_category_groups() -> Vec<CategoryGroup> {
                     balance: 6000,
                     goal_cadence: Some(1),
                     goal_target: None,
+                    goal_percentage_complete: None,
+                    goal_type: None,
                     hidden: false,
+                    deleted: false,
                 },
                 Category {
                     id: "c4".into(),
@@ -71,7 +88,10 @@ fn make_category_groups() -> Vec<CategoryGroup> {
                     balance: 17000,
                     goal_cadence: Some(1),
                     goal_target: None,
+                    goal_percentage_complete: None,
+                    goal_type: None,
                     hidden: false,
+                    deleted: false,
                 },
             ],
         },
@@ -123,11 +143,13 @@ fn make_transactions() -> Vec<Transaction> {
             category_name: Some("Split".into()),
             subtransactions: vec![
                 SubTransaction {
+                    id: "t4-a".into(),
                     amount: -6000,
                     payee_name: None,
                     category_name: Some("Groceries".into()),
                 },
                 SubTransaction {
+                    id: "t4-b".into(),
                     amount: -4000,
                     payee_name: None,
                     category_name: Some("Books".into()),
@@ -167,6 +189,37 @@ fn get_budget_id_missing_returns_none() {
     insta::assert_snapshot!(format!("{:?}", result));
 }
 
+#[test]
+fn check_budget_id_accepts_matching_name() {
+    let summaries = make_budget_summaries();
+    let result = report::check_budget_id(&summaries, "b1", "Budget A");
+    insta::assert_snapshot!(format!("{:?}", result.ok()));
+}
+
+#[test]
+fn check_budget_id_rejects_other_budgets_name() {
+    let summaries = make_budget_summaries();
+    let err = report::check_budget_id(&summaries, "b2", "Budget A").unwrap_err();
+    insta::assert_snapshot!(err);
+}
+
+#[test]
+fn check_budget_id_rejects_unknown_id() {
+    let summaries = make_budget_summaries();
+    let err = report::check_budget_id(&summaries, "b9", "Budget A").unwrap_err();
+    insta::assert_snapshot!(err);
+}
+
+#[test]
+fn get_budget_last_modified_reads_matching_budget() {
+    let budgets = make_budget_summaries();
+    let modified: Vec<Option<String>> = ["Budget A", "Budget B", "Nope"]
+        .into_iter()
+        .map(|name| report::get_budget_last_modified(&budgets, name))
+        .collect();
+    insta::assert_debug_snapshot!(modified);
+}
+
 #[test]
 fn get_missing_category_groups_detects_missing() {
     let groups = make_category_groups();
@@ -184,7 +237,9 @@ fn get_categories_to_watch_filters_correctly() {
     let groups = make_category_groups();
     let mut watch_list = indexmap::IndexMap::new();
     watch_list.insert("Essentials".into(), "#fff".into());
-    let cats = report::get_categories_to_watch(&groups, &watch_list);
+    let cats = report::get_categories_to_watch(&groups, &watch_list, None, true, false)
+        .unwrap()
+        .categories;
     let mut names: Vec<&str> = cats.iter().map(|c| c.name.as_str()).collect();
     names.sort();
     insta::assert_snapshot!(format!("{:?}", names));
@@ -198,6 +253,145 @@ fn transactions_to_polars_expands_splits() {
     insta::assert_snapshot!(dataframe_snapshot(&df));
 }
 
+fn split_without_subtransactions() -> Vec<Transaction> {
+    vec![Transaction {
+        id: "t9".into(),
+        date: NaiveDate::from_ymd_opt(2024, 3, 14).unwrap(),
+        amount: -42000,
+        payee_name: Some("Hardware Store".into()),
+        category_name: Some("Split".into()),
+        subtransactions: vec![],
+    }]
+}
+
+#[test]
+fn transactions_to_polars_keeps_split_without_subtransactions() {
+    let df = report::transactions_to_polars(&split_without_subtransactions())
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn empty_split_transactions_lists_splits_without_subtransactions() {
+    let transactions = [split_without_subtransactions(), make_transactions()].concat();
+    assert_eq!(report::empty_split_transactions(&transactions), ["t9"]);
+}
+
+fn overflowing_transactions() -> Vec<Transaction> {
+    let date = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+    vec![
+        Transaction {
+            id: "t1".into(),
+            date,
+            amount: i64::MAX,
+            payee_name: Some("Bank Glitch".into()),
+            category_name: Some("Groceries".into()),
+            subtransactions: vec![],
+        },
+        Transaction {
+            id: "t2".into(),
+            date,
+            amount: -12_340,
+            payee_name: Some("Corner Shop".into()),
+            category_name: Some("Groceries".into()),
+            subtransactions: vec![],
+        },
+    ]
+}
+
+#[test]
+fn transactions_to_polars_zeroes_amounts_beyond_exact_range() {
+    let df = report::transactions_to_polars(&overflowing_transactions())
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn invalid_transaction_amounts_lists_overflowing_ids() {
+    insta::assert_debug_snapshot!(report::invalid_transaction_amounts(
+        &overflowing_transactions()
+    ));
+}
+
+fn category_frame_height(cf: CategoryFrame) -> usize {
+    cf.0.collect().unwrap().height()
+}
+
+#[test]
+fn category_frame_filter_by_group_keeps_group_rows() {
+    let cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&cats).unwrap();
+    insta::assert_debug_snapshot!((
+        category_frame_height(CategoryFrame::filter_by_group(cf.clone(), "Essentials")),
+        category_frame_height(cf.filter_by_group("Missing")),
+    ));
+}
+
+#[test]
+fn category_frame_filter_by_category_names_keeps_named_rows() {
+    let cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&cats).unwrap();
+    let names: HashSet<String> = ["Groceries", "Books", "Missing"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    insta::assert_debug_snapshot!(category_frame_height(cf.filter_by_category_names(&names)));
+}
+
+#[test]
+fn categories_to_polars_with_metadata_stamps_fetch_date() {
+    let groups = make_category_groups();
+    let cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let fetch_date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let df = report::categories_to_polars_with_metadata(&cats, fetch_date)
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+    let column = df.column("fetch_date").unwrap();
+    let dates: Vec<Option<NaiveDate>> = column.date().unwrap().as_date_iter().collect();
+    assert_eq!(column.dtype(), &polars::prelude::DataType::Date);
+    assert!(!dates.is_empty());
+    assert!(dates.iter().all(|date| *date == Some(fetch_date)));
+}
+
+#[test]
+fn categories_to_polars_writes_goal_type_codes() {
+    let cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .zip([
+            Some(GoalType::Need),
+            Some(GoalType::Tbd),
+            Some(GoalType::Unknown("SAVE".into())),
+            None,
+        ])
+        .map(|(category, goal_type)| Category {
+            goal_type,
+            ..category
+        })
+        .collect();
+    let df = report::categories_to_polars(&cats)
+        .unwrap()
+        .0
+        .select([col("category_name"), col("goal_type")])
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
 #[test]
 fn categories_to_polars_converts_milliunits() {
     let groups = make_category_groups();
@@ -231,7 +425,8 @@ fn build_report_table_sums_spent() {
     let tf = report::relevant_transactions(tf, start, end);
 
     let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
-    let report = report::build_report_table(cf, tf, &cat_names).unwrap();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
     let df = report.collect().unwrap();
     insta::assert_snapshot!(dataframe_snapshot(&df));
 }
@@ -249,12 +444,96 @@ fn category_group_totals_match_rows() {
     let tf = report::relevant_transactions(tf, start, end);
 
     let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
-    let report = report::build_report_table(cf, tf, &cat_names).unwrap();
-    let totals = report::build_category_group_totals_table(report).unwrap();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+    let totals =
+        report::build_category_group_totals_table(report, report::GroupTotalsSortOrder::default())
+            .unwrap();
     let df = totals.collect().unwrap();
     insta::assert_snapshot!(dataframe_snapshot(&df));
 }
 
+fn group_totals_sort_fixture() -> polars::prelude::LazyFrame {
+    use polars::prelude::*;
+    df!(
+        "category_name" => ["Rent", "Groceries", "Cinema", "Fuel"],
+        "category_group_name" => ["Bills", "Food", "Fun", "Travel"],
+        "budgeted" => [900.0, 300.0, 50.0, 120.0],
+        "spent" => [-100.0, -250.0, -60.0, 30.0],
+        "balance" => [800.0, 50.0, -10.0, 150.0],
+    )
+    .unwrap()
+    .lazy()
+}
+
+fn sorted_group_totals(sort_order: report::GroupTotalsSortOrder) -> String {
+    let totals =
+        report::build_category_group_totals_table(group_totals_sort_fixture(), sort_order).unwrap();
+    dataframe_snapshot(&totals.collect().unwrap())
+}
+
+#[test]
+fn category_group_totals_sorted_alphabetically() {
+    insta::assert_snapshot!(sorted_group_totals(
+        report::GroupTotalsSortOrder::Alphabetical
+    ));
+}
+
+#[test]
+fn category_group_totals_sorted_by_spent_magnitude() {
+    insta::assert_snapshot!(sorted_group_totals(
+        report::GroupTotalsSortOrder::BySpentDesc
+    ));
+}
+
+#[test]
+fn category_group_totals_sorted_by_budgeted() {
+    insta::assert_snapshot!(sorted_group_totals(
+        report::GroupTotalsSortOrder::ByBudgetedDesc
+    ));
+}
+
+#[test]
+fn diff_category_frames_covers_changed_added_and_removed_categories() {
+    use polars::prelude::*;
+    let frame = |names: [&str; 2], budgeted: [f64; 2]| {
+        CategoryFrame(
+            df!(
+                "category_group_name" => ["Essentials", "Fun"],
+                "category_name" => names,
+                "budgeted" => budgeted,
+            )
+            .unwrap()
+            .lazy(),
+        )
+    };
+    let delta = report::diff_category_frames(
+        frame(["Groceries", "Cinema"], [300.0, 40.0]),
+        frame(["Groceries", "Books"], [350.0, 25.0]),
+    )
+    .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&delta.0.collect().unwrap()));
+}
+
+#[test]
+fn diff_category_frames_merges_categories_sharing_a_sanitized_name() {
+    let rent = make_category_groups()[0].categories[1].clone();
+    let frame = |budgeted: [i64; 2]| {
+        let categories =
+            [("Rent", budgeted[0]), ("Rent (2023)", budgeted[1])].map(|(name, budgeted)| {
+                Category {
+                    id: name.into(),
+                    name: name.into(),
+                    budgeted,
+                    ..rent.clone()
+                }
+            });
+        report::categories_to_polars(&categories).unwrap()
+    };
+    let delta = report::diff_category_frames(frame([100000, 20000]), frame([150000, 0])).unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&delta.0.collect().unwrap()));
+}
+
 #[test]
 fn category_group_totals_include_balance_without_spending() {
     let categories = vec![
@@ -266,7 +545,10 @@ fn category_group_totals_include_balance_without_spending() {
             balance: 30000,
             goal_cadence: Some(1),
             goal_target: Some(60000),
+            goal_percentage_complete: None,
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
         Category {
             id: "c2".into(),
@@ -276,7 +558,10 @@ fn category_group_totals_include_balance_without_spending() {
             balance: 90000,
             goal_cadence: Some(1),
             goal_target: Some(60000),
+            goal_percentage_complete: None,
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
     ];
     let cf = report::categories_to_polars(&categories).unwrap();
@@ -295,8 +580,11 @@ fn category_group_totals_include_balance_without_spending() {
     let tf = report::relevant_transactions(tf, start, end);
 
     let cat_names: HashSet<String> = categories.iter().map(|c| c.name.clone()).collect();
-    let report = report::build_report_table(cf, tf, &cat_names).unwrap();
-    let totals = report::build_category_group_totals_table(report).unwrap();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+    let totals =
+        report::build_category_group_totals_table(report, report::GroupTotalsSortOrder::default())
+            .unwrap();
     let df = totals.collect().unwrap();
     insta::assert_snapshot!(dataframe_snapshot(&df));
 }
@@ -312,7 +600,10 @@ fn split_transactions_are_counted_per_category() {
             balance: 30000,
             goal_cadence: Some(1),
             goal_target: Some(60000),
+            goal_percentage_complete: None,
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
         Category {
             id: "c2".into(),
@@ -322,7 +613,10 @@ fn split_transactions_are_counted_per_category() {
             balance: 90000,
             goal_cadence: Some(1),
             goal_target: Some(60000),
+            goal_percentage_complete: None,
+            goal_type: None,
             hidden: false,
+            deleted: false,
         },
     ];
     let cf = report::categories_to_polars(&categories).unwrap();
@@ -335,11 +629,13 @@ fn split_transactions_are_counted_per_category() {
         category_name: Some("Split".into()),
         subtransactions: vec![
             SubTransaction {
+                id: "t2-a".into(),
                 amount: -12500,
                 payee_name: None,
                 category_name: Some("Groceries".into()),
             },
             SubTransaction {
+                id: "t2-b".into(),
                 amount: -7500,
                 payee_name: None,
                 category_name: Some("Savings".into()),
@@ -352,20 +648,78 @@ fn split_transactions_are_counted_per_category() {
     let tf = report::relevant_transactions(tf, start, end);
 
     let cat_names: HashSet<String> = categories.iter().map(|c| c.name.clone()).collect();
-    let report = report::build_report_table(cf, tf, &cat_names).unwrap();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
     let df = report.collect().unwrap();
     insta::assert_snapshot!(dataframe_snapshot(&df));
 }
 
+fn transactions_with_one_uncategorized() -> Vec<Transaction> {
+    vec![
+        Transaction {
+            id: "t1".into(),
+            date: NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
+            amount: -5000,
+            payee_name: Some("Shop".into()),
+            category_name: None,
+            subtransactions: vec![],
+        },
+        Transaction {
+            id: "t2".into(),
+            date: NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
+            amount: -3000,
+            payee_name: Some("Store".into()),
+            category_name: Some("Groceries".into()),
+            subtransactions: vec![],
+        },
+    ]
+}
+
 #[test]
 fn transactions_with_no_category_are_filtered() {
+    let tf = report::transactions_to_polars(&transactions_with_one_uncategorized()).unwrap();
+    let df = tf.0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn transactions_with_no_category_are_kept_when_including_uncategorized() {
+    let options = report::TransactionExpandOptions {
+        include_uncategorized: true,
+    };
+    let tf = report::transactions_to_polars_with(&transactions_with_one_uncategorized(), options)
+        .unwrap();
+    let df = tf.0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn filter_by_payee_regex_anchored_pattern() {
+    let transactions = make_transactions();
+    let tf = report::transactions_to_polars(&transactions).unwrap();
+    let filtered = report::filter_by_payee_regex(tf, "^Mar").unwrap();
+    let df = filtered.0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn filter_by_payee_regex_case_insensitive() {
+    let transactions = make_transactions();
+    let tf = report::transactions_to_polars(&transactions).unwrap();
+    let filtered = report::filter_by_payee_regex(tf, "(?i)LANDLORD").unwrap();
+    let df = filtered.0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn filter_by_payee_regex_null_payee_matches_empty() {
     let transactions = vec![
         Transaction {
             id: "t1".into(),
             date: NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
             amount: -5000,
-            payee_name: Some("Shop".into()),
-            category_name: None,
+            payee_name: None,
+            category_name: Some("Groceries".into()),
             subtransactions: vec![],
         },
         Transaction {
@@ -378,6 +732,1435 @@ fn transactions_with_no_category_are_filtered() {
         },
     ];
     let tf = report::transactions_to_polars(&transactions).unwrap();
-    let df = tf.0.collect().unwrap();
+    let filtered = report::filter_by_payee_regex(tf, "^$").unwrap();
+    let df = filtered.0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn filter_by_payee_regex_invalid_pattern_errors() {
+    let transactions = make_transactions();
+    let tf = report::transactions_to_polars(&transactions).unwrap();
+    let err = report::filter_by_payee_regex(tf, "(unclosed")
+        .err()
+        .unwrap();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn weekly_group_spending_covers_each_week() {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let weeks = month_weeks(Month::try_from((2024, 3)).unwrap());
+
+    let weekly = report::build_weekly_group_spending(cf, tf, &cat_names, &weeks).unwrap();
+    let df = weekly.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+fn weighted_pace_for_week(current_week_number: usize) -> polars::prelude::DataFrame {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+    report::with_weighted_percent_spent(report, current_week_number, 5)
+        .unwrap()
+        .collect()
+        .unwrap()
+}
+
+#[test]
+fn weighted_percent_spent_week_1_of_5() {
+    insta::assert_snapshot!(dataframe_snapshot(&weighted_pace_for_week(1)));
+}
+
+#[test]
+fn weighted_percent_spent_week_4_of_5() {
+    insta::assert_snapshot!(dataframe_snapshot(&weighted_pace_for_week(4)));
+}
+
+fn reversed_watch_list() -> indexmap::IndexMap<String, String> {
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Fun".into(), "#f4dccb".into());
+    watch_list.insert("Essentials".into(), "#dfe7f5".into());
+    watch_list
+}
+
+#[test]
+fn order_groups_by_watch_list_follows_insertion_order() {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+
+    let ordered = report::order_groups_by_watch_list(report, &reversed_watch_list()).unwrap();
+    let df = ordered.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn order_groups_by_watch_list_keeps_total_last() {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+    let totals =
+        report::build_category_group_totals_table(report, report::GroupTotalsSortOrder::default())
+            .unwrap();
+
+    let ordered = report::order_groups_by_watch_list(totals, &reversed_watch_list()).unwrap();
+    let df = ordered.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+fn category_groups_with_deleted() -> Vec<CategoryGroup> {
+    let mut groups = make_category_groups();
+    groups[0].categories.push(Category {
+        id: "c5".into(),
+        name: "Old Phone Plan".into(),
+        category_group_name: Some("Essentials".into()),
+        budgeted: 0,
+        balance: 0,
+        goal_cadence: None,
+        goal_target: None,
+        goal_percentage_complete: None,
+        goal_type: None,
+        hidden: false,
+        deleted: true,
+    });
+    groups
+}
+
+#[test]
+fn get_categories_to_watch_skips_deleted() {
+    let groups = category_groups_with_deleted();
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Essentials".into(), "#fff".into());
+    let cats = report::get_categories_to_watch(&groups, &watch_list, None, true, false)
+        .unwrap()
+        .categories;
+    let mut names: Vec<&str> = cats.iter().map(|c| c.name.as_str()).collect();
+    names.sort();
+    insta::assert_snapshot!(format!("{:?}", names));
+}
+
+#[test]
+fn get_deleted_known_categories_reports_previously_seen() {
+    let groups = category_groups_with_deleted();
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Essentials".into(), "#fff".into());
+    let known: HashSet<String> = ["Groceries", "Old Phone Plan"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let deleted = report::get_deleted_known_categories(&groups, &watch_list, &known);
+    insta::assert_snapshot!(format!("{:?}", deleted));
+}
+
+#[test]
+fn get_vanished_categories_lists_watched_ids_no_longer_current() {
+    let groups = make_category_groups();
+    let current = report::known_categories(&groups);
+    let mut previous = current.clone();
+    let known = |group: &str, name: &str| KnownCategory {
+        group: group.into(),
+        name: name.into(),
+    };
+    previous.insert("c-old".into(), known("Essentials", "Old Phone Plan"));
+    previous.insert("c-bus".into(), known("Transport", "Bus Pass"));
+    if let Some(renamed) = previous.values_mut().find(|c| c.name == "Groceries") {
+        renamed.name = "Food".into();
+    }
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Essentials".into(), "#fff".into());
+    insta::assert_snapshot!(format!(
+        "{:?}",
+        report::get_vanished_categories(&previous, &current, &watch_list)
+    ));
+}
+
+#[test]
+fn known_categories_keeps_hidden_and_skips_deleted() {
+    let mut groups = category_groups_with_deleted();
+    groups[1].categories[0].hidden = true;
+    let names: Vec<String> = report::known_categories(&groups)
+        .into_values()
+        .map(|c| format!("{}/{}", c.group, c.name))
+        .collect();
+    insta::assert_snapshot!(format!("{names:?}"));
+}
+
+#[test]
+fn infer_goal_cadence_maps_known_codes() {
+    let lines: Vec<String> = std::iter::once(None)
+        .chain((0..=14).map(Some))
+        .map(|code| {
+            format!(
+                "{code:?}: with_target={} without_target={}",
+                goals::infer_goal_cadence(code, Some(1000)),
+                goals::infer_goal_cadence(code, None)
+            )
+        })
+        .collect();
+    insta::assert_snapshot!(lines.join("\n"));
+}
+
+#[test]
+fn with_budget_change_computes_month_over_month_delta() {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+
+    let previous: Vec<Category> = all_cats
+        .iter()
+        .filter(|c| c.name != "Games")
+        .map(|c| Category {
+            budgeted: match c.name.as_str() {
+                "Groceries" => 40000,
+                "Rent" => 120000,
+                _ => c.budgeted,
+            },
+            ..c.clone()
+        })
+        .collect();
+    let previous_frame = report::categories_to_polars(&previous).unwrap();
+
+    let df = report::with_budget_change(report, previous_frame)
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn with_budget_change_merges_previous_categories_sharing_a_sanitized_name() {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let report =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+
+    let mut previous = all_cats.clone();
+    let archived = previous
+        .iter()
+        .find(|c| c.name == "Rent")
+        .map(|rent| Category {
+            id: "c-rent-2023".into(),
+            name: "Rent (2023)".into(),
+            budgeted: 30000,
+            ..rent.clone()
+        })
+        .unwrap();
+    previous.push(archived);
+    let previous_frame = report::categories_to_polars(&previous).unwrap();
+
+    let df = report::with_budget_change(report, previous_frame)
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+fn write_csv_string(df: &mut polars::prelude::DataFrame) -> String {
+    crustynab::CsvRenderer::render(df).unwrap()
+}
+
+fn first_row_snapshot(
+    original: &polars::prelude::DataFrame,
+    parsed: &polars::prelude::DataFrame,
+) -> String {
+    format!(
+        "original_columns={} parsed_columns={}\noriginal_row0={:?}\nparsed_row0={:?}",
+        original.width(),
+        parsed.width(),
+        original.get(0).unwrap(),
+        parsed.get(0).unwrap()
+    )
+}
+
+#[test]
+fn category_frame_from_csv_round_trips() {
+    let groups = make_category_groups();
+    let cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let mut original = report::categories_to_polars(&cats)
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("categories.csv");
+    std::fs::write(&path, write_csv_string(&mut original)).unwrap();
+
+    let parsed = CategoryFrame::from_csv(&path).unwrap().0.collect().unwrap();
+    insta::assert_snapshot!(first_row_snapshot(&original, &parsed));
+}
+
+#[test]
+fn transaction_frame_from_csv_round_trips() {
+    let mut original = report::transactions_to_polars(&make_transactions())
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transactions.csv");
+    std::fs::write(&path, write_csv_string(&mut original)).unwrap();
+
+    let parsed = TransactionFrame::from_csv(&path)
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(first_row_snapshot(&original, &parsed));
+}
+
+#[test]
+fn schema_check_accepts_converted_frames() {
+    let cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    insta::assert_debug_snapshot!((cf.schema_check().ok(), tf.schema_check().ok()));
+}
+
+#[test]
+fn category_frame_schema_check_lists_missing_and_wrong_typed_columns() {
+    let cf = CategoryFrame(
+        DataFrame::new(vec![
+            Column::new("category_name".into(), &["Groceries"]),
+            Column::new("budgeted".into(), &["100"]),
+            Column::new("balance".into(), &[10.0]),
+        ])
+        .unwrap()
+        .lazy(),
+    );
+    insta::assert_snapshot!(cf.schema_check().unwrap_err());
+}
+
+#[test]
+fn transaction_frame_schema_check_lists_missing_and_wrong_typed_columns() {
+    let tf = amounts_frame(&[Some(1.0)]);
+    insta::assert_snapshot!(tf.schema_check().unwrap_err());
+}
+
+fn amounts_frame(amounts: &[Option<f64>]) -> TransactionFrame {
+    let categories: Vec<&str> = (0..amounts.len())
+        .map(|i| if i % 2 == 0 { "Groceries" } else { "Rent" })
+        .collect();
+    TransactionFrame(
+        DataFrame::new(vec![
+            Column::new("category_name".into(), &categories),
+            Column::new("amount".into(), amounts),
+        ])
+        .unwrap()
+        .lazy(),
+    )
+}
+
+#[test]
+fn transaction_frame_total_spent_of_empty_frame_is_zero() {
+    let tf = amounts_frame(&[]);
+    insta::assert_debug_snapshot!((
+        tf.total_spent().unwrap(),
+        tf.total_for_category("Groceries").unwrap(),
+    ));
+}
+
+#[test]
+fn transaction_frame_total_spent_sums_positive_amounts() {
+    let tf = amounts_frame(&[Some(1.5), Some(2.25), Some(4.0)]);
+    insta::assert_debug_snapshot!((
+        tf.total_spent().unwrap(),
+        tf.total_for_category("Groceries").unwrap(),
+    ));
+}
+
+#[test]
+fn transaction_frame_total_spent_sums_mixed_signs() {
+    let tf = amounts_frame(&[Some(-10.0), Some(3.5), Some(-2.5), Some(20.0)]);
+    insta::assert_debug_snapshot!((
+        tf.total_spent().unwrap(),
+        tf.total_for_category("Groceries").unwrap(),
+        tf.total_for_category("Rent").unwrap(),
+        tf.total_for_category("Missing").unwrap(),
+    ));
+}
+
+#[test]
+fn transaction_frame_total_spent_counts_null_amount_as_zero() {
+    let tf = amounts_frame(&[None]);
+    insta::assert_debug_snapshot!((
+        tf.total_spent().unwrap(),
+        tf.total_for_category("Groceries").unwrap(),
+    ));
+}
+
+#[test]
+fn transaction_frame_from_csv_rejects_missing_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transactions.csv");
+    std::fs::write(&path, "date,amount\n2024-03-12,-12.5\n").unwrap();
+
+    let err = TransactionFrame::from_csv(&path).err().unwrap();
+    insta::assert_snapshot!(
+        err.to_string()
+            .replace(&*dir.path().to_string_lossy(), "<tmp>")
+    );
+}
+
+#[test]
+fn annotate_with_week_nulls_other_months() {
+    let mut transactions = make_transactions();
+    transactions.push(Transaction {
+        id: "t5".into(),
+        date: NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+        amount: -7000,
+        payee_name: Some("Market".into()),
+        category_name: Some("Groceries".into()),
+        subtransactions: vec![],
+    });
+    transactions.push(Transaction {
+        id: "t6".into(),
+        date: NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        amount: -1500,
+        payee_name: Some("Arcade".into()),
+        category_name: Some("Games".into()),
+        subtransactions: vec![],
+    });
+    let tf = report::transactions_to_polars(&transactions).unwrap();
+    let march = Month::try_from((2024, 3)).unwrap();
+    let annotated = report::annotate_with_week(tf, march).unwrap();
+    let df = annotated.0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn with_free_balance_subtracts_prorated_goal_target() {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::with_free_balance(report::categories_to_polars(&all_cats).unwrap());
+    let df = cf.0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn relevant_transactions_in_groups_keeps_groups_apart() {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let name_to_group = report::category_name_to_group(&cf).unwrap();
+    let start = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let end = NaiveDate::from_ymd_opt(2024, 3, 16).unwrap();
+
+    let categories_for = |group: &str| -> BTreeSet<String> {
+        let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+        let filter = HashSet::from([group.to_string()]);
+        let df =
+            report::relevant_transactions_in_groups(tf, start, end, Some(&filter), &name_to_group)
+                .0
+                .collect()
+                .unwrap();
+        df.column("category_name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let essentials = categories_for("Essentials");
+    let fun = categories_for("Fun");
+    assert!(essentials.is_disjoint(&fun));
+    insta::assert_debug_snapshot!((essentials, fun));
+}
+
+fn built_report_table() -> polars::prelude::DataFrame {
+    let groups = make_category_groups();
+    let all_cats: Vec<Category> = groups.into_iter().flat_map(|g| g.categories).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All)
+        .unwrap()
+        .collect()
+        .unwrap()
+}
+
+fn report_with_meta() -> report::ReportTableWithMeta {
+    let march = Month::try_from((2024, 3)).unwrap();
+    let week = month_weeks(march).into_iter().nth(2).unwrap();
+    report::collect_with_meta(built_report_table().lazy(), &week).unwrap()
+}
+
+#[test]
+fn collect_with_meta_labels_the_week() {
+    insta::assert_snapshot!(report_with_meta().period_label());
+}
+
+#[test]
+fn collect_with_meta_keeps_every_report_row() {
+    insta::assert_snapshot!(dataframe_snapshot(&report_with_meta().frame));
+}
+
+#[test]
+fn report_with_meta_serializes_the_period() {
+    insta::assert_json_snapshot!(report_with_meta());
+}
+
+#[test]
+fn validate_report_table_accepts_built_report() {
+    let df = built_report_table();
+    let height = df.height();
+    insta::assert_debug_snapshot!(report::validate_report_table(&df, height).is_ok());
+}
+
+#[test]
+fn validate_report_table_lists_every_violation() {
+    use polars::prelude::*;
+    let df = df!(
+        "category_group_name" => [Some("Essentials"), None, Some("Fun")],
+        "category_name" => ["Groceries", "Refunds", "Books"],
+        "budgeted" => [50.0, -10.0, 10.0],
+        "spent" => [-18.5, 12.0, -4.0],
+        "balance" => [Some(31.5), Some(2.0), None],
+    )
+    .unwrap();
+    let err = report::validate_report_table(&df, 4).unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn validate_report_table_accepts_refunds_and_negative_budgets() {
+    use polars::prelude::*;
+    let df = df!(
+        "category_group_name" => ["Essentials", "Essentials"],
+        "category_name" => ["Groceries", "Refunds"],
+        "budgeted" => [50.0, -10.0],
+        "spent" => [12.0, -4.0],
+        "balance" => [62.0, -14.0],
+    )
+    .unwrap();
+    insta::assert_debug_snapshot!(report::validate_report_table(&df, 2).is_ok());
+}
+
+/// The warnings logged while validating `df`, and whether it passed.
+fn validate_logging_warnings(df: &polars::prelude::DataFrame, category_count: usize) -> String {
+    let logged = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let writer = std::sync::Arc::clone(&logged);
+    let subscriber =
+        crustynab::logging::subscriber("warn", move || LogBuffer(writer.clone())).unwrap();
+    let result = tracing::subscriber::with_default(subscriber, || {
+        report::validate_report_table(df, category_count)
+    });
+    let logged = String::from_utf8(logged.lock().unwrap().clone()).unwrap();
+    format!(
+        "result: {:?}\n{}",
+        result.map_err(|err| format!("{err:#}")),
+        logged.trim_end()
+    )
+}
+
+struct LogBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn validate_report_table_warns_about_positive_spent_but_fails_on_null_names() {
+    use polars::prelude::*;
+    let refund = df!(
+        "category_group_name" => ["Essentials"],
+        "category_name" => [Some("Groceries")],
+        "budgeted" => [50.0],
+        "spent" => [12.0],
+        "balance" => [62.0],
+    )
+    .unwrap();
+    let unnamed = df!(
+        "category_group_name" => ["Essentials"],
+        "category_name" => [None::<&str>],
+        "budgeted" => [50.0],
+        "spent" => [-12.0],
+        "balance" => [38.0],
+    )
+    .unwrap();
+    insta::assert_snapshot!(format!(
+        "{}\n---\n{}",
+        validate_logging_warnings(&refund, 1),
+        validate_logging_warnings(&unnamed, 1)
+    ));
+}
+
+fn mapped_payees(mapping: &[(&str, &str)]) -> Vec<Option<String>> {
+    let transactions: Vec<Transaction> = [
+        Some("Amazon Prime"),
+        Some("Amazon Fresh"),
+        Some("Landlord"),
+        Some("Corner Shop"),
+        None,
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, payee)| Transaction {
+        id: format!("t{i}"),
+        date: NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
+        amount: -1000,
+        payee_name: payee.map(str::to_string),
+        category_name: Some("Groceries".into()),
+        subtransactions: vec![],
+    })
+    .collect();
+    let mapping: indexmap::IndexMap<String, String> = mapping
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    let tf = report::transactions_to_polars(&transactions).unwrap();
+    let df = report::apply_payee_mapping(tf, &mapping)
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+    df.column("payee_name")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .map(|payee| payee.map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn apply_payee_mapping_exact_match() {
+    insta::assert_debug_snapshot!(mapped_payees(&[("Landlord", "Rent Co")]));
+}
+
+#[test]
+fn apply_payee_mapping_glob_match() {
+    insta::assert_debug_snapshot!(mapped_payees(&[("Amazon*", "Amazon")]));
+}
+
+#[test]
+fn apply_payee_mapping_no_match_passes_through() {
+    insta::assert_debug_snapshot!(mapped_payees(&[("Tesco*", "Tesco")]));
+}
+
+fn transactions_with_refund() -> Vec<Transaction> {
+    make_transactions()
+        .into_iter()
+        .chain([Transaction {
+            id: "t9".into(),
+            date: NaiveDate::from_ymd_opt(2024, 3, 14).unwrap(),
+            amount: 4500,
+            payee_name: Some("Market".into()),
+            category_name: Some("Groceries".into()),
+            subtransactions: vec![],
+        }])
+        .collect()
+}
+
+fn report_for_side(side: report::TransactionSide) -> polars::prelude::DataFrame {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&transactions_with_refund()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    report::build_report_table(cf, tf, &cat_names, side)
+        .unwrap()
+        .collect()
+        .unwrap()
+}
+
+#[test]
+fn split_transactions_by_sign_separates_inflows() {
+    let tf = report::transactions_to_polars(&transactions_with_refund()).unwrap();
+    let (income, expenses) = report::split_transactions_by_sign(tf);
+    let income = income.0.collect().unwrap();
+    let expenses = expenses.0.collect().unwrap();
+    insta::assert_snapshot!(format!(
+        "income:\n{}\nexpenses:\n{}",
+        dataframe_snapshot(&income),
+        dataframe_snapshot(&expenses)
+    ));
+}
+
+#[test]
+fn build_report_table_expense_side_ignores_refunds() {
+    insta::assert_snapshot!(dataframe_snapshot(&report_for_side(
+        report::TransactionSide::Expense
+    )));
+}
+
+#[test]
+fn build_report_table_all_side_nets_refunds() {
+    insta::assert_snapshot!(dataframe_snapshot(&report_for_side(
+        report::TransactionSide::All
+    )));
+}
+
+#[test]
+fn build_income_table_lists_categories_with_inflows() {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&transactions_with_refund()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let df = report::build_income_table(cf, tf, &cat_names)
+        .unwrap()
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+fn hash_of<T: std::hash::Hash>(value: &T) -> u64 {
+    use std::hash::{DefaultHasher, Hasher};
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn simple_transaction_frame(rows: &[(&str, f64)]) -> TransactionFrame {
+    let names: Vec<&str> = rows.iter().map(|(name, _)| *name).collect();
+    let amounts: Vec<f64> = rows.iter().map(|(_, amount)| *amount).collect();
+    TransactionFrame(
+        DataFrame::new(vec![
+            Column::new("category_name".into(), &names),
+            Column::new("amount".into(), &amounts),
+        ])
+        .unwrap()
+        .lazy(),
+    )
+}
+
+#[test]
+fn transaction_frames_equal_regardless_of_row_order() {
+    let left = simple_transaction_frame(&[("Groceries", -12.5), ("Rent", -25.0)]);
+    let right = simple_transaction_frame(&[("Rent", -25.0), ("Groceries", -12.5)]);
+    assert_eq!(left, right);
+    assert_eq!(hash_of(&left), hash_of(&right));
+}
+
+#[test]
+fn transaction_frames_equal_within_float_tolerance() {
+    let left = simple_transaction_frame(&[("Groceries", 0.1 + 0.2)]);
+    let right = simple_transaction_frame(&[("Groceries", 0.3)]);
+    assert_eq!(left, right);
+    assert_eq!(hash_of(&left), hash_of(&right));
+}
+
+#[test]
+fn transaction_frames_treat_negative_zero_as_zero() {
+    let left = simple_transaction_frame(&[("Groceries", -0.0), ("Rent", -1e-9)]);
+    let right = simple_transaction_frame(&[("Groceries", 0.0), ("Rent", 0.0)]);
+    assert_eq!(left, right);
+    assert_eq!(hash_of(&left), hash_of(&right));
+}
+
+#[test]
+fn transaction_frames_differ_on_values() {
+    let left = simple_transaction_frame(&[("Groceries", -12.5)]);
+    let right = simple_transaction_frame(&[("Groceries", -12.0)]);
+    assert_ne!(left, right);
+    assert_ne!(hash_of(&left), hash_of(&right));
+}
+
+#[test]
+// LazyFrame's interior mutability is plan caching; it never changes the hashed content
+#[allow(clippy::mutable_key_type)]
+fn category_frames_work_as_hash_map_keys() {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let reversed: Vec<Category> = all_cats.iter().rev().cloned().collect();
+
+    let mut cache = std::collections::HashMap::new();
+    cache.insert(report::categories_to_polars(&all_cats).unwrap(), "cached");
+    assert_eq!(
+        cache.get(&report::categories_to_polars(&reversed).unwrap()),
+        Some(&"cached")
+    );
+}
+
+#[test]
+fn category_group_totals_for_zero_categories_is_a_zero_total_row() {
+    let cf = report::categories_to_polars(&[]).unwrap();
+    let tf = report::transactions_to_polars(&[]).unwrap();
+    let report =
+        report::build_report_table(cf, tf, &HashSet::new(), report::TransactionSide::All).unwrap();
+    let totals =
+        report::build_category_group_totals_table(report, report::GroupTotalsSortOrder::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&totals));
+}
+
+fn category_groups_with_credit_card_payments() -> Vec<CategoryGroup> {
+    let mut groups = make_category_groups();
+    groups.push(CategoryGroup {
+        id: "g-cc".into(),
+        name: "Credit Card Payments".into(),
+        hidden: false,
+        deleted: false,
+        categories: vec![Category {
+            id: "c-visa".into(),
+            name: "Visa".into(),
+            category_group_name: Some("Credit Card Payments".into()),
+            budgeted: 0,
+            balance: 42000,
+            goal_cadence: None,
+            goal_target: None,
+            goal_percentage_complete: None,
+            goal_type: None,
+            hidden: false,
+            deleted: false,
+        }],
+    });
+    groups
+}
+
+#[test]
+fn is_system_group_matches_exact_and_prefix_names() {
+    let names = [
+        "Credit Card Payments",
+        "Internal Master Category",
+        "Hidden Categories (legacy)",
+        "Essentials",
+        "My Credit Card Payments",
+    ];
+    let flags: Vec<(&str, bool)> = names
+        .iter()
+        .map(|name| (*name, report::is_system_group_name(name)))
+        .collect();
+    insta::assert_snapshot!(format!("{flags:?}"));
+}
+
+fn watched_names_with_system_groups(exclude_system_groups: bool) -> Vec<String> {
+    let groups = category_groups_with_credit_card_payments();
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Essentials".into(), "#fff".into());
+    watch_list.insert("Credit Card Payments".into(), "#000".into());
+    let cats =
+        report::get_categories_to_watch(&groups, &watch_list, None, exclude_system_groups, false)
+            .unwrap()
+            .categories;
+    let mut names: Vec<String> = cats.into_iter().map(|c| c.name).collect();
+    names.sort();
+    names
+}
+
+fn category_groups_with_hidden_savings() -> Vec<CategoryGroup> {
+    let mut groups = make_category_groups();
+    groups.push(CategoryGroup {
+        id: "g-savings".into(),
+        name: "Savings".into(),
+        hidden: false,
+        deleted: false,
+        categories: vec![Category {
+            id: "c-rainy-day".into(),
+            name: "Rainy Day".into(),
+            category_group_name: Some("Savings".into()),
+            budgeted: 0,
+            balance: 0,
+            goal_cadence: None,
+            goal_target: None,
+            goal_percentage_complete: None,
+            goal_type: None,
+            hidden: true,
+            deleted: false,
+        }],
+    });
+    groups
+}
+
+fn essentials_and_savings() -> indexmap::IndexMap<String, String> {
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Essentials".into(), "#fff".into());
+    watch_list.insert("Savings".into(), "#0f0".into());
+    watch_list
+}
+
+#[test]
+fn get_categories_to_watch_skips_fully_hidden_group() {
+    let cats = report::get_categories_to_watch(
+        &category_groups_with_hidden_savings(),
+        &essentials_and_savings(),
+        None,
+        true,
+        false,
+    )
+    .unwrap()
+    .categories;
+    let mut names: Vec<&str> = cats.iter().map(|c| c.name.as_str()).collect();
+    names.sort();
+    insta::assert_snapshot!(format!("{:?}", names));
+}
+
+#[test]
+fn get_categories_to_watch_strict_mode_rejects_fully_hidden_group() {
+    let err = report::get_categories_to_watch(
+        &category_groups_with_hidden_savings(),
+        &essentials_and_savings(),
+        None,
+        true,
+        true,
+    )
+    .err()
+    .unwrap();
+    insta::assert_snapshot!(err.to_string());
+}
+
+#[test]
+fn get_categories_to_watch_excludes_system_groups() {
+    insta::assert_snapshot!(format!("{:?}", watched_names_with_system_groups(true)));
+}
+
+#[test]
+fn get_categories_to_watch_returns_warnings() {
+    let mut watch_list = essentials_and_savings();
+    watch_list.insert("Credit Card Payments".into(), "#000".into());
+    let mut groups = category_groups_with_deleted();
+    groups.extend(category_groups_with_hidden_savings().pop());
+    groups.extend(category_groups_with_credit_card_payments().pop());
+    let known: HashSet<String> = ["Old Phone Plan".to_string()].into_iter().collect();
+    let watched =
+        report::get_categories_to_watch(&groups, &watch_list, Some(&known), true, false).unwrap();
+    insta::assert_debug_snapshot!(watched.warnings);
+}
+
+#[test]
+fn get_categories_to_watch_keeps_system_groups_when_disabled() {
+    insta::assert_snapshot!(format!("{:?}", watched_names_with_system_groups(false)));
+}
+
+#[test]
+fn running_total_last_row_equals_amount_sum() {
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let amount_sum: f64 =
+        tf.0.clone()
+            .collect()
+            .unwrap()
+            .column("amount")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .sum();
+    let annotated = report::annotate_with_running_total(tf)
+        .unwrap()
+        .0
+        .collect()
+        .unwrap();
+    let running = annotated.column("running_total").unwrap().f64().unwrap();
+    let last = running.get(running.len() - 1).unwrap();
+    assert!((last - amount_sum).abs() < 1e-9, "{last} != {amount_sum}");
+}
+
+#[test]
+fn running_total_sorted_by_date() {
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let annotated = report::annotate_with_running_total(tf)
+        .unwrap()
+        .0
+        .select([
+            polars::prelude::col("date"),
+            polars::prelude::col("amount"),
+            polars::prelude::col("running_total"),
+        ])
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(annotated);
+}
+
+/// `count` single-category transactions cycling through `category_names`, one day
+/// apart, each spending $1.
+fn synthetic_transactions(count: usize, category_names: &[String]) -> Vec<Transaction> {
+    let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    (0..count)
+        .map(|i| Transaction {
+            id: format!("t{i}"),
+            date: start + chrono::Duration::days((i % 365) as i64),
+            amount: -1000,
+            payee_name: Some(format!("Payee {}", i % 50)),
+            category_name: Some(category_names[i % category_names.len()].clone()),
+            subtransactions: vec![],
+        })
+        .collect()
+}
+
+#[test]
+fn build_report_table_sums_ten_thousand_transactions() {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cat_names: Vec<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&synthetic_transactions(10_000, &cat_names)).unwrap();
+    let names: HashSet<String> = cat_names.into_iter().collect();
+
+    let report = report::build_report_table(cf, tf, &names, report::TransactionSide::All)
+        .unwrap()
+        .select([
+            polars::prelude::col("category_name"),
+            polars::prelude::col("spent"),
+        ])
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(report);
+}
+
+#[test]
+fn select_watched_month_categories_keeps_watch_order_and_fills_groups() {
+    let watched: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let mut month: Vec<Category> = watched
+        .iter()
+        .rev()
+        .cloned()
+        .map(|mut category| {
+            category.category_group_name = None;
+            category
+        })
+        .collect();
+    month.push(Category {
+        id: "c-unwatched".into(),
+        name: "Unwatched".into(),
+        ..watched[0].clone()
+    });
+
+    let selected = report::select_watched_month_categories(month, &watched).unwrap();
+    let summary: Vec<(String, Option<String>)> = selected
+        .into_iter()
+        .map(|c| (c.name, c.category_group_name))
+        .collect();
+    insta::assert_snapshot!(format!("{summary:?}"));
+}
+
+#[test]
+fn select_watched_month_categories_reports_missing_category() {
+    let watched: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let err = report::select_watched_month_categories(watched[1..].to_vec(), &watched).unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn build_weekly_trend_table_has_every_category_each_week() {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let weeks: Vec<_> = month_weeks(Month::try_from((2024, 3)).unwrap())
+        .into_iter()
+        .skip(1)
+        .take(2)
+        .collect();
+    let trend = report::build_weekly_trend_table(cf, tf, &names, &weeks)
+        .unwrap()
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(trend);
+}
+
+#[test]
+fn build_monthly_report_table_buckets_spending_by_month() {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let months = [
+        Month::try_from((2024, 2)).unwrap(),
+        Month::try_from((2024, 3)).unwrap(),
+    ];
+    let month_categories: Vec<(Month, CategoryFrame)> = months
+        .iter()
+        .map(|month| (*month, report::categories_to_polars(&all_cats).unwrap()))
+        .collect();
+
+    let mut transactions = make_transactions();
+    transactions.push(Transaction {
+        id: "t5".into(),
+        date: NaiveDate::from_ymd_opt(2024, 2, 20).unwrap(),
+        amount: -8000,
+        payee_name: Some("Market".into()),
+        category_name: Some("Groceries".into()),
+        subtransactions: vec![],
+    });
+    // outside both months, so it is not counted
+    transactions.push(Transaction {
+        id: "t6".into(),
+        date: NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(),
+        amount: -1000,
+        payee_name: Some("Market".into()),
+        category_name: Some("Groceries".into()),
+        subtransactions: vec![],
+    });
+    let tf = report::transactions_to_polars(&transactions).unwrap();
+
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let df = report::build_monthly_report_table(month_categories, tf, &cat_names)
+        .unwrap()
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn report_table_to_wide_format_pivots_groups_to_columns() {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let report_table =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+
+    let df = report::report_table_to_wide_format(report_table)
+        .unwrap()
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn report_table_to_wide_format_keeps_one_row_per_category() {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let cf = report::categories_to_polars(&all_cats).unwrap();
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = all_cats.iter().map(|c| c.name.clone()).collect();
+    let report_table =
+        report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All).unwrap();
+    let distinct_categories = report_table
+        .clone()
+        .collect()
+        .unwrap()
+        .column("category_name")
+        .unwrap()
+        .n_unique()
+        .unwrap();
+
+    let wide = report::report_table_to_wide_format(report_table)
+        .unwrap()
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(format!(
+        "wide rows={} distinct categories={distinct_categories}",
+        wide.height()
+    ));
+}
+
+#[test]
+fn filter_transfers_drops_both_sides_of_an_uncategorized_transfer() {
+    use polars::prelude::*;
+    let tf = TransactionFrame(
+        df!(
+            "payee_name" => [
+                Some("Transfer : Savings"),
+                Some("Transfer : Checking"),
+                Some("Transfer : Mortgage"),
+                Some("Transfer Station"),
+                Some("Market"),
+                None,
+            ],
+            "category_name" => [None, None, Some("Housing"), Some("Groceries"), Some("Groceries"), Some("Groceries")],
+            "amount" => [-20.0, 20.0, -90.0, -1.5, -3.0, -0.5],
+        )
+        .unwrap()
+        .lazy(),
+    );
+    let df = report::filter_transfers(tf).0.collect().unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+fn category_frame_with_override() -> CategoryFrame {
+    let all_cats: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    let overrides = HashMap::from([("Groceries".to_string(), 20.0)]);
+    report::apply_budget_override(report::categories_to_polars(&all_cats).unwrap(), &overrides)
+}
+
+#[test]
+fn apply_budget_override_leaves_other_categories_unchanged() {
+    let df = category_frame_with_override()
+        .0
+        .select([
+            polars::prelude::col("category_name"),
+            polars::prelude::col("budgeted"),
+            polars::prelude::col("budget_override_applied"),
+        ])
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn budget_override_drives_percent_spent() {
+    let tf = report::transactions_to_polars(&make_transactions()).unwrap();
+    let cat_names: HashSet<String> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .map(|c| c.name)
+        .collect();
+    let report = report::build_report_table(
+        category_frame_with_override(),
+        tf,
+        &cat_names,
+        report::TransactionSide::All,
+    )
+    .unwrap();
+    let df = report::with_weighted_percent_spent(report, 4, 5)
+        .unwrap()
+        .select([
+            polars::prelude::col("category_name"),
+            polars::prelude::col("budgeted"),
+            polars::prelude::col("spent"),
+            polars::prelude::col("percent_spent"),
+            polars::prelude::col("budget_override_applied"),
+        ])
+        .collect()
+        .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn wow_comparison_fills_categories_missing_from_a_week() {
+    let week = |rows: &[(&str, f64)]| {
+        DataFrame::new(vec![
+            Column::new("category_group_name".into(), vec!["Essentials"; rows.len()]),
+            Column::new(
+                "category_name".into(),
+                rows.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "spent".into(),
+                rows.iter().map(|(_, spent)| *spent).collect::<Vec<_>>(),
+            ),
+        ])
+        .unwrap()
+        .lazy()
+    };
+    let df = report::build_wow_comparison_table(
+        week(&[("Groceries", -18.5), ("Rent", -25.0)]),
+        week(&[("Groceries", -30.0), ("Utilities", -12.0)]),
+    )
+    .unwrap()
+    .collect()
+    .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn resolve_group_color_fetches_watch_list_entry() {
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    insta::assert_snapshot!(report::resolve_group_color("Essentials", &watch_list));
+}
+
+#[test]
+fn resolve_group_color_falls_back_for_missing_or_blank_colors() {
+    let mut watch_list = indexmap::IndexMap::new();
+    watch_list.insert("Fun".to_string(), " ".to_string());
+    let colors = [
+        report::resolve_group_color("Savings", &watch_list),
+        report::resolve_group_color("Fun", &watch_list),
+    ];
+    insta::assert_snapshot!(colors.join("\n"));
+}
+
+fn annual_goal_category(name: &str, goal_target: i64) -> Category {
+    Category {
+        id: name.to_lowercase(),
+        name: name.into(),
+        category_group_name: Some("Essentials".into()),
+        budgeted: 10000,
+        balance: 0,
+        goal_cadence: Some(13),
+        goal_target: Some(goal_target),
+        goal_percentage_complete: None,
+        goal_type: None,
+        hidden: false,
+        deleted: false,
+    }
+}
+
+#[test]
+fn goal_progress_classifies_annual_goals_in_march() {
+    let mut categories: Vec<Category> = make_category_groups()
+        .into_iter()
+        .flat_map(|g| g.categories)
+        .collect();
+    categories.push(annual_goal_category("Insurance", 600000));
+    categories.push(annual_goal_category("Gifts", 240000));
+    let year_to_date_spent = DataFrame::new(vec![
+        Column::new("category_name".into(), &["Rent", "Insurance", "Groceries"]),
+        Column::new("spent".into(), &[-29.0, -300.0, -150.0]),
+    ])
+    .unwrap()
+    .lazy();
+
+    let df = report::build_goal_progress_table(
+        report::categories_to_polars(&categories).unwrap(),
+        year_to_date_spent,
+        3,
+    )
+    .unwrap()
+    .collect()
+    .unwrap();
+    insta::assert_snapshot!(dataframe_snapshot(&df));
+}
+
+#[test]
+fn goal_progress_rejects_month_out_of_range() {
+    let categories = report::categories_to_polars(&[annual_goal_category("Gifts", 240000)]);
+    let spent = DataFrame::new(vec![
+        Column::new("category_name".into(), Vec::<&str>::new()),
+        Column::new("spent".into(), Vec::<f64>::new()),
+    ])
+    .unwrap()
+    .lazy();
+    let result = report::build_goal_progress_table(categories.unwrap(), spent, 13);
+    insta::assert_snapshot!(result.err().unwrap());
+}
+
+fn report_diff() -> report::CsvDiff {
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    report::diff_csv_reports(
+        &fixtures.join("report_before.csv"),
+        &fixtures.join("report_after.csv"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn diff_csv_reports_added_rows() {
+    insta::assert_snapshot!(report_diff().added);
+}
+
+#[test]
+fn diff_csv_reports_removed_rows() {
+    insta::assert_snapshot!(report_diff().removed);
+}
+
+#[test]
+fn diff_csv_reports_changed_rows_ignore_rounding() {
+    insta::assert_snapshot!(write_csv_string(&mut report_diff().changed));
+}
+
+#[test]
+fn csv_diff_table_lists_each_difference() {
+    let mut table = report::csv_diff_table(&report_diff()).unwrap();
+    insta::assert_snapshot!(write_csv_string(&mut table));
+}
+
+#[test]
+fn format_csv_diff_plain_text() {
+    insta::assert_snapshot!(report::format_csv_diff(&report_diff(), false).unwrap());
+}
+
+#[test]
+fn format_csv_diff_colors_lines() {
+    insta::assert_debug_snapshot!(report::format_csv_diff(&report_diff(), true).unwrap());
+}
+
+#[test]
+fn diff_csv_reports_requires_category_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.csv");
+    std::fs::write(&path, "category_group_name,spent\nBills,-10.0\n").unwrap();
+
+    let err = report::diff_csv_reports(&path, &path).err().unwrap();
+    insta::assert_snapshot!(
+        err.to_string()
+            .replace(&*dir.path().to_string_lossy(), "<tmp>")
+    );
+}
+
+#[test]
+fn build_report_table_merges_categories_sharing_a_sanitized_name() {
+    let phone = |id: &str, name: &str, budgeted: i64| Category {
+        id: id.into(),
+        name: name.into(),
+        category_group_name: Some("Essentials".into()),
+        budgeted,
+        balance: 1000,
+        goal_cadence: None,
+        goal_target: None,
+        goal_percentage_complete: None,
+        goal_type: None,
+        hidden: false,
+        deleted: false,
+    };
+    let categories = vec![
+        phone("c1", "Phone", 30000),
+        phone("c2", "Phone (2019)", 5000),
+    ];
+    let transaction = |id: &str, category: &str, amount: i64| Transaction {
+        id: id.into(),
+        date: NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
+        amount,
+        payee_name: Some("Carrier".into()),
+        category_name: Some(category.into()),
+        subtransactions: vec![],
+    };
+    let transactions = vec![
+        transaction("t1", "Phone", -20000),
+        transaction("t2", "Phone (2019)", -4000),
+    ];
+    let cf = report::categories_to_polars(&categories).unwrap();
+    let tf = report::transactions_to_polars(&transactions).unwrap();
+    let cat_names: HashSet<String> = ["Phone".to_string()].into_iter().collect();
+    let df = report::build_report_table(cf, tf, &cat_names, report::TransactionSide::All)
+        .unwrap()
+        .collect()
+        .unwrap();
     insta::assert_snapshot!(dataframe_snapshot(&df));
 }
@@ -0,0 +1,76 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+use crustynab::calendar_weeks::month_week_for_date;
+use crustynab::chart_report::{build_chart_data, chart_data_json};
+use crustynab::report_metadata::ReportMetadata;
+
+fn make_metadata() -> ReportMetadata {
+    let week = month_week_for_date(NaiveDate::from_ymd_opt(2024, 1, 17).unwrap()).unwrap();
+    let generated_at = Utc
+        .with_ymd_and_hms(2024, 1, 20, 14, 30, 0)
+        .unwrap()
+        .checked_add_signed(chrono::Duration::milliseconds(250))
+        .unwrap();
+    ReportMetadata::new(&week, "My Budget", generated_at)
+}
+
+#[test]
+fn csv_header_lines() {
+    insta::assert_snapshot!(make_metadata().header_lines("# "));
+}
+
+#[test]
+fn csv_header_round_trips_before_data() {
+    let metadata = make_metadata();
+    let csv = format!(
+        "{}category_name,spent\nGroceries,-18.5\n",
+        metadata.header_lines("# ")
+    );
+    let parsed = ReportMetadata::from_header_lines(&csv, "# ").unwrap();
+    assert_eq!(parsed, metadata);
+}
+
+#[test]
+fn sql_header_round_trips() {
+    let metadata = make_metadata();
+    let sql = format!(
+        "{}CREATE TABLE IF NOT EXISTS t (x INTEGER);\n",
+        metadata.header_lines("-- ")
+    );
+    let parsed = ReportMetadata::from_header_lines(&sql, "-- ").unwrap();
+    assert_eq!(parsed, metadata);
+}
+
+#[test]
+fn chart_json_metadata_round_trips() {
+    let metadata = make_metadata();
+    let weekly = DataFrame::new(vec![
+        Column::new("week_number".into(), &[3_u32]),
+        Column::new("category_group_name".into(), &["Essentials"]),
+        Column::new("spent".into(), &[-12.5]),
+    ])
+    .unwrap()
+    .lazy();
+    let mut colors = IndexMap::new();
+    colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    let chart = build_chart_data(weekly, &colors, &[(3, "Week 3".to_string())]).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&chart_data_json(&chart, &metadata).unwrap()).unwrap();
+    let parsed: ReportMetadata = serde_json::from_value(json["metadata"].clone()).unwrap();
+    assert_eq!(parsed, metadata);
+}
+
+#[test]
+fn missing_header_field_errors() {
+    let header = make_metadata().header_lines("# ");
+    let without_budget: String = header
+        .lines()
+        .filter(|line| !line.starts_with("# budget_name"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let err = ReportMetadata::from_header_lines(&without_budget, "# ").unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
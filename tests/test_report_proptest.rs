@@ -1,8 +1,9 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 use chrono::{Duration, NaiveDate};
+use crustynab::goals;
 use crustynab::report::{self, CategoryFrame, TransactionFrame};
-use crustynab::ynab::{BudgetSummary, CategoryGroup, SubTransaction, Transaction};
+use crustynab::ynab::{BudgetSummary, Category, CategoryGroup, SubTransaction, Transaction};
 use polars::prelude::*;
 use proptest::prelude::*;
 use proptest::string::string_regex;
@@ -279,6 +280,42 @@ fn categories_and_transactions_strategy() -> impl Strategy<Value = (Vec<Category
     })
 }
 
+/// Payees mixing plain names, YNAB transfer payees and nulls.
+fn payee_with_transfers_strategy() -> impl Strategy<Value = Option<String>> {
+    prop_oneof![
+        short_text_strategy().prop_map(Some),
+        short_text_strategy().prop_map(|account| Some(format!("Transfer : {account}"))),
+        short_text_strategy().prop_map(|name| Some(format!("Transfer{name}"))),
+        Just(None),
+    ]
+}
+
+/// A report-shaped frame (`category_group_name`, `category_name`, `spent`) with one row
+/// per entry of `spent`, all in one group.
+fn spent_report_frame(spent: &BTreeMap<String, f64>) -> LazyFrame {
+    let names: Vec<&str> = spent.keys().map(String::as_str).collect();
+    let groups = vec!["Essentials"; names.len()];
+    let values: Vec<f64> = spent.values().copied().collect();
+    DataFrame::new(vec![
+        Column::new("category_group_name".into(), groups),
+        Column::new("category_name".into(), names),
+        Column::new("spent".into(), values),
+    ])
+    .expect("spent report frame")
+    .lazy()
+}
+
+/// Spending per category for one week, over a small shared pool of category names so
+/// two weeks overlap in some categories and not others.
+fn weekly_spent_strategy() -> impl Strategy<Value = BTreeMap<String, f64>> {
+    prop::collection::btree_map(
+        prop::sample::select(vec!["Books", "Games", "Groceries", "Rent", "Utilities"])
+            .prop_map(str::to_string),
+        (-100_000_i64..=100_000_i64).prop_map(|milli| milli as f64 / 1000.0),
+        0..=5,
+    )
+}
+
 fn transaction_rows_any_strategy() -> impl Strategy<Value = Vec<TxRow>> {
     let start = date(2000, 1, 1);
     let end = date(2030, 12, 31);
@@ -312,6 +349,7 @@ fn budget_summaries_strategy() -> impl Strategy<Value = Vec<BudgetSummary>> {
             .map(|(idx, name)| BudgetSummary {
                 id: format!("budget-{idx}"),
                 name,
+                last_modified_on: None,
             })
             .collect()
     })
@@ -329,11 +367,13 @@ fn budget_summaries_and_target_strategy() -> impl Strategy<Value = (Vec<BudgetSu
 
 fn subtransaction_strategy() -> impl Strategy<Value = SubTransaction> {
     (
+        short_id_strategy(),
         -1_000_000_i64..=1_000_000_i64,
         prop::option::of(short_text_strategy()),
         prop::option::of(short_text_strategy()),
     )
-        .prop_map(|(amount, payee_name, category_name)| SubTransaction {
+        .prop_map(|(id, amount, payee_name, category_name)| SubTransaction {
+            id,
             amount,
             payee_name,
             category_name,
@@ -373,9 +413,77 @@ fn transaction_details_strategy() -> impl Strategy<Value = Vec<Transaction>> {
     prop::collection::vec(transaction_strategy(), 0..=15)
 }
 
+fn goal_categories_strategy() -> impl Strategy<Value = Vec<Category>> {
+    prop::collection::vec(
+        (
+            short_text_strategy(),
+            -5_000_000_i64..5_000_000_i64,
+            prop::option::of(prop::sample::select(vec![0_i32, 1, 2, 4, 13, 14])),
+            prop::option::of(-1_000_000_i64..10_000_000_i64),
+            prop::option::of(-50_i32..200_i32),
+        ),
+        1..8,
+    )
+    .prop_map(|rows| {
+        rows.into_iter()
+            .enumerate()
+            .map(
+                |(i, (name, balance, goal_cadence, goal_target, goal_percentage_complete))| {
+                    Category {
+                        id: format!("c{i}"),
+                        name,
+                        category_group_name: Some("Group".to_string()),
+                        budgeted: 0,
+                        balance,
+                        goal_cadence,
+                        goal_target,
+                        goal_percentage_complete,
+                        goal_type: None,
+                        hidden: false,
+                        deleted: false,
+                    }
+                },
+            )
+            .collect()
+    })
+}
+
+fn hash_of<T: std::hash::Hash>(value: &T) -> u64 {
+    use std::hash::{DefaultHasher, Hasher};
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn amount_frame(amount: f64) -> TransactionFrame {
+    TransactionFrame(df!("amount" => [amount]).expect("amount frame").lazy())
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(96))]
 
+    #[test]
+    fn prop_equal_frames_hash_alike(
+        amount in -1e9f64..1e9,
+        nudges in prop::collection::vec(-1e-6f64..1e-6, 2),
+    ) {
+        let frames = [
+            amount_frame(amount),
+            amount_frame(amount + nudges[0]),
+            amount_frame(amount + nudges[1]),
+        ];
+        for left in &frames {
+            for right in &frames {
+                if left == right {
+                    prop_assert_eq!(hash_of(left), hash_of(right));
+                }
+            }
+        }
+        if frames[0] == frames[1] && frames[1] == frames[2] {
+            prop_assert_eq!(&frames[0], &frames[2]);
+        }
+    }
+
     #[test]
     fn prop_get_missing_category_groups(
         group_names in prop::collection::hash_set(short_text_strategy(), 0..=8),
@@ -436,7 +544,7 @@ proptest! {
         let categories_frame = category_frame(&categories);
         let transactions_frame = transaction_frame(&transactions);
 
-        let report_df = report::build_report_table(categories_frame, transactions_frame, &category_names)
+        let report_df = report::build_report_table(categories_frame, transactions_frame, &category_names, report::TransactionSide::All)
             .expect("build_report_table")
             .collect()
             .expect("collect report");
@@ -487,6 +595,33 @@ proptest! {
         prop_assert_eq!(actual_rows, expected_rows);
     }
 
+    #[test]
+    fn prop_relevant_transactions_matches_day_number_filter(
+        rows in transaction_rows_any_strategy(),
+        start in date_strategy(date(2000, 1, 1), date(2030, 12, 31)),
+        end in date_strategy(date(2000, 1, 1), date(2030, 12, 31)),
+    ) {
+        let day_number_filter = transaction_frame(&rows)
+            .0
+            .filter(
+                col("date")
+                    .cast(DataType::Int32)
+                    .gt_eq(lit(date_to_polars_days(start)))
+                    .and(col("date").cast(DataType::Int32).lt_eq(lit(date_to_polars_days(end)))),
+            )
+            .collect()
+            .expect("collect day-number filter");
+        let date_filter = report::relevant_transactions(transaction_frame(&rows), start, end)
+            .0
+            .collect()
+            .expect("collect filtered");
+
+        prop_assert_eq!(
+            transaction_multiset(&date_filter),
+            transaction_multiset(&day_number_filter)
+        );
+    }
+
     #[test]
     fn prop_category_group_totals_match_rows((categories, transactions) in categories_and_transactions_strategy()) {
         let category_names = categories
@@ -498,14 +633,18 @@ proptest! {
             category_frame(&categories),
             transaction_frame(&transactions),
             &category_names,
+            report::TransactionSide::All,
         )
         .expect("build_report_table");
 
         let report_df = report_table.clone().collect().expect("collect report table");
-        let totals_df = report::build_category_group_totals_table(report_table)
-            .expect("build totals")
-            .collect()
-            .expect("collect totals");
+        let totals_df = report::build_category_group_totals_table(
+            report_table,
+            report::GroupTotalsSortOrder::default(),
+        )
+        .expect("build totals")
+        .collect()
+        .expect("collect totals");
 
         let groups = report_df
             .column("category_group_name")
@@ -575,6 +714,11 @@ proptest! {
                     }
                 }
             } else if let Some(category_name) = &transaction.category_name {
+                let category_name = if category_name == report::SPLIT_CATEGORY_NAME {
+                    report::UNCATEGORIZED_SPLIT_CATEGORY
+                } else {
+                    category_name
+                };
                 let payee = transaction.payee_name.as_deref().unwrap_or("<none>");
                 expected_rows.push(format!(
                     "{}|{}|{}|{}",
@@ -621,4 +765,452 @@ proptest! {
 
         prop_assert_eq!(actual_rows, expected_rows);
     }
+
+    #[test]
+    fn prop_transactions_to_polars_rows_have_exactly_one_id(transactions in transaction_details_strategy()) {
+        let df = report::transactions_to_polars(&transactions)
+            .expect("transactions_to_polars")
+            .0
+            .collect()
+            .expect("collect transaction frame");
+
+        let ids = df.column("id").expect("id").str().expect("id str");
+        let subtransaction_ids = df
+            .column("subtransaction_id")
+            .expect("subtransaction_id")
+            .str()
+            .expect("subtransaction_id str");
+
+        for idx in 0..df.height() {
+            let has_id = ids.get(idx).is_some();
+            let has_subtransaction_id = subtransaction_ids.get(idx).is_some();
+            prop_assert!(has_id != has_subtransaction_id);
+        }
+    }
+
+    #[test]
+    fn prop_group_totals_match_totals_table((categories, transactions) in categories_and_transactions_strategy()) {
+        let category_names = categories
+            .iter()
+            .map(|row| row.category_name.clone())
+            .collect::<HashSet<_>>();
+
+        let report_table = report::build_report_table(
+            category_frame(&categories),
+            transaction_frame(&transactions),
+            &category_names,
+            report::TransactionSide::All,
+        )
+        .expect("build_report_table");
+
+        let totals_df = report::build_category_group_totals_table(
+            report_table.clone(),
+            report::GroupTotalsSortOrder::default(),
+        )
+        .expect("build totals")
+        .collect()
+        .expect("collect totals");
+        let expected = report_totals_map(&totals_df);
+
+        for (group, (budgeted, spent, balance)) in &expected {
+            let actual = if group == "Total" {
+                report::overall_totals(&report_table).expect("overall_totals")
+            } else {
+                report::group_totals(&report_table, group).expect("group_totals")
+            };
+            prop_assert!(close(actual.budgeted, *budgeted));
+            prop_assert!(close(actual.spent, *spent));
+            prop_assert!(close(actual.balance, *balance));
+        }
+    }
+
+    #[test]
+    fn prop_free_balance_never_exceeds_balance(categories in goal_categories_strategy()) {
+        let frame = report::with_free_balance(report::categories_to_polars(&categories).expect("frame"));
+        let df = frame.0.collect().expect("collect");
+        let balance = df.column("balance").expect("balance").f64().expect("balance f64");
+        let free_balance = df.column("free_balance").expect("free_balance").f64().expect("free f64");
+
+        for (i, category) in categories.iter().enumerate() {
+            let balance = balance.get(i).expect("balance value");
+            let free_balance = free_balance.get(i).expect("free_balance value");
+            prop_assert!(free_balance <= balance);
+            if category.goal_target.is_none() {
+                prop_assert_eq!(free_balance, balance);
+            }
+        }
+    }
+
+    #[test]
+    fn prop_goal_percent_complete_within_bounds(categories in goal_categories_strategy()) {
+        let df = report::categories_to_polars(&categories).expect("frame").0.collect().expect("collect");
+        let percent = df
+            .column("goal_percent_complete")
+            .expect("goal_percent_complete")
+            .i32()
+            .expect("goal_percent_complete i32");
+
+        for (value, category) in percent.iter().zip(&categories) {
+            prop_assert_eq!(value.is_none(), category.goal_percentage_complete.is_none());
+            prop_assert!(value.is_none_or(|percent| (0..=100).contains(&percent)));
+        }
+    }
+
+    #[test]
+    fn prop_split_transactions_by_sign_partitions(rows in transaction_rows_any_strategy()) {
+        let indexed = TransactionFrame(
+            transaction_frame(&rows).0.with_row_index("row_id", None),
+        );
+        let (income, expenses) = report::split_transactions_by_sign(indexed);
+        let collect_side = |frame: TransactionFrame| {
+            let df = frame.0.collect().expect("collect side");
+            let ids: Vec<u32> = df
+                .column("row_id")
+                .expect("row_id")
+                .u32()
+                .expect("row_id u32")
+                .into_no_null_iter()
+                .collect();
+            let amounts: Vec<f64> = df
+                .column("amount")
+                .expect("amount")
+                .f64()
+                .expect("amount f64")
+                .into_no_null_iter()
+                .collect();
+            (ids, amounts)
+        };
+        let (income_ids, income_amounts) = collect_side(income);
+        let (expense_ids, expense_amounts) = collect_side(expenses);
+
+        prop_assert!(income_amounts.iter().all(|amount| *amount > 0.0));
+        prop_assert!(expense_amounts.iter().all(|amount| *amount <= 0.0));
+
+        let income_set: HashSet<u32> = income_ids.iter().copied().collect();
+        prop_assert!(expense_ids.iter().all(|id| !income_set.contains(id)));
+
+        let mut union: Vec<u32> = income_ids.into_iter().chain(expense_ids).collect();
+        union.sort_unstable();
+        prop_assert_eq!(union, (0..rows.len() as u32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn prop_pct_of_group_budgeted_sums_to_100((categories, transactions) in categories_and_transactions_strategy()) {
+        let category_names = categories
+            .iter()
+            .map(|row| row.category_name.clone())
+            .collect::<HashSet<_>>();
+        let df = report::build_report_table(
+            category_frame(&categories),
+            transaction_frame(&transactions),
+            &category_names,
+            report::TransactionSide::All,
+        )
+        .expect("build_report_table")
+        .collect()
+        .expect("collect report");
+
+        let groups = df.column("category_group_name").expect("group").str().expect("group str");
+        let pct = df
+            .column("pct_of_group_budgeted")
+            .expect("pct_of_group_budgeted")
+            .f64()
+            .expect("pct f64");
+
+        let mut by_group: HashMap<&str, Vec<Option<f64>>> = HashMap::new();
+        for (group, pct) in groups.iter().zip(pct.iter()) {
+            by_group.entry(group.expect("group name")).or_default().push(pct);
+        }
+        for (group, percentages) in by_group {
+            let budgeted: f64 = categories
+                .iter()
+                .filter(|row| row.category_group_name == group)
+                .map(|row| row.budgeted)
+                .sum();
+            if budgeted == 0.0 {
+                prop_assert!(percentages.iter().all(Option::is_none));
+                continue;
+            }
+            let percentages: Vec<f64> = percentages.into_iter().map(|p| p.expect("pct")).collect();
+            let total: f64 = percentages.iter().sum();
+            // negative budgets can make shares large, so scale the tolerance with them
+            let scale: f64 = percentages.iter().map(|p| p.abs()).sum::<f64>().max(100.0);
+            prop_assert!((total - 100.0).abs() <= 1e-9 * scale, "group {group} sums to {total}");
+        }
+    }
+
+    #[test]
+    fn prop_running_total_is_sorted_prefix_sum(rows in transaction_rows_any_strategy()) {
+        let df = report::annotate_with_running_total(transaction_frame(&rows))
+            .expect("annotate")
+            .0
+            .collect()
+            .expect("collect running total");
+        let dates: Vec<i32> = df
+            .column("date")
+            .expect("date")
+            .cast(&DataType::Int32)
+            .expect("date as i32")
+            .i32()
+            .expect("date i32")
+            .into_no_null_iter()
+            .collect();
+        let amounts: Vec<f64> = df
+            .column("amount")
+            .expect("amount")
+            .f64()
+            .expect("amount f64")
+            .into_no_null_iter()
+            .collect();
+        let running: Vec<f64> = df
+            .column("running_total")
+            .expect("running_total")
+            .f64()
+            .expect("running_total f64")
+            .into_no_null_iter()
+            .collect();
+
+        prop_assert!(dates.windows(2).all(|pair| pair[0] <= pair[1]));
+        let mut expected = 0.0;
+        for (amount, total) in amounts.iter().zip(&running) {
+            expected += amount;
+            prop_assert!((expected - total).abs() <= 1e-6, "{total} != {expected}");
+        }
+    }
+
+    #[test]
+    fn prop_wide_format_has_one_row_per_category((categories, transactions) in categories_and_transactions_strategy()) {
+        let category_names = categories
+            .iter()
+            .map(|row| row.category_name.clone())
+            .collect::<HashSet<_>>();
+        let group_names = categories
+            .iter()
+            .map(|row| row.category_group_name.clone())
+            .collect::<HashSet<_>>();
+
+        let report_table = report::build_report_table(
+            category_frame(&categories),
+            transaction_frame(&transactions),
+            &category_names,
+            report::TransactionSide::All,
+        )
+        .expect("build_report_table");
+        let wide = report::report_table_to_wide_format(report_table)
+            .expect("report_table_to_wide_format")
+            .collect()
+            .expect("collect wide");
+
+        prop_assert_eq!(wide.height(), category_names.len());
+        prop_assert_eq!(wide.width(), group_names.len() + 1);
+    }
+
+    #[test]
+    fn prop_filter_transfers_leaves_no_uncategorized_transfers(
+        rows in prop::collection::vec(
+            (payee_with_transfers_strategy(), prop::option::of(Just("Groceries".to_string())), -1_000_000_i64..=1_000_000_i64),
+            0..=25,
+        )
+    ) {
+        let is_uncategorized_transfer = |payee: Option<&str>, category: Option<&str>| {
+            payee.is_some_and(|p| p.starts_with("Transfer : ")) && category.is_none()
+        };
+        let expected_rows = rows
+            .iter()
+            .filter(|(payee, category, _)| !is_uncategorized_transfer(payee.as_deref(), category.as_deref()))
+            .count();
+        let payees: Vec<Option<&str>> = rows.iter().map(|(payee, _, _)| payee.as_deref()).collect();
+        let category_names: Vec<Option<&str>> = rows.iter().map(|(_, category, _)| category.as_deref()).collect();
+        let amounts: Vec<f64> = rows.iter().map(|(_, _, amount)| *amount as f64 / 1000.0).collect();
+        let tf = TransactionFrame(
+            DataFrame::new(vec![
+                Column::new("payee_name".into(), &payees),
+                Column::new("category_name".into(), &category_names),
+                Column::new("amount".into(), &amounts),
+            ])
+            .expect("transaction frame")
+            .lazy(),
+        );
+
+        let df = report::filter_transfers(tf).0.collect().expect("collect filtered");
+        let payees = df.column("payee_name").expect("payee_name").str().expect("payee_name str");
+        let categories = df.column("category_name").expect("category_name").str().expect("category_name str");
+
+        prop_assert!(payees.into_iter().zip(categories).all(|(payee, category)| !is_uncategorized_transfer(payee, category)));
+        prop_assert_eq!(df.height(), expected_rows);
+    }
+
+    #[test]
+    fn prop_wow_comparison_deltas_fill_missing_weeks_with_zero(
+        this_week in weekly_spent_strategy(),
+        last_week in weekly_spent_strategy(),
+    ) {
+        let df = report::build_wow_comparison_table(
+            spent_report_frame(&this_week),
+            spent_report_frame(&last_week),
+        )
+        .expect("comparison table")
+        .collect()
+        .expect("collect comparison");
+
+        let names: HashSet<&String> = this_week.keys().chain(last_week.keys()).collect();
+        prop_assert_eq!(df.height(), names.len());
+
+        let categories = df.column("category_name").expect("category_name").str().expect("category_name str");
+        let last_spent = df.column("last_week_spent").expect("last_week_spent").f64().expect("last_week_spent f64");
+        let delta = df.column("delta_spent").expect("delta_spent").f64().expect("delta_spent f64");
+        let improved = df.column("improved").expect("improved").bool().expect("improved bool");
+        for idx in 0..df.height() {
+            let category = categories.get(idx).expect("category");
+            let expected_this = this_week.get(category).copied().unwrap_or(0.0);
+            let expected_last = last_week.get(category).copied().unwrap_or(0.0);
+            let delta = delta.get(idx).expect("delta value");
+            prop_assert!(close(last_spent.get(idx).expect("last week value"), expected_last));
+            prop_assert!(close(delta, expected_this - expected_last));
+            prop_assert_eq!(improved.get(idx), Some(delta > 0.0));
+        }
+    }
+
+    #[test]
+    fn prop_goal_status_matches_expected_share(
+        goal_target in 1_i64..=5_000_000_i64,
+        outflow_milli in 0_i64..=5_000_000_i64,
+        month in 1_u32..=12,
+    ) {
+        let category = Category {
+            id: "c1".to_string(),
+            name: "Insurance".to_string(),
+            category_group_name: Some("Essentials".to_string()),
+            budgeted: 0,
+            balance: 0,
+            goal_cadence: Some(13),
+            goal_target: Some(goal_target),
+            goal_percentage_complete: None,
+            goal_type: None,
+            hidden: false,
+            deleted: false,
+        };
+        let spent = DataFrame::new(vec![
+            Column::new("category_name".into(), &["Insurance"]),
+            Column::new("spent".into(), &[-(outflow_milli as f64) / 1000.0]),
+        ])
+        .expect("spent frame")
+        .lazy();
+
+        let df = report::build_goal_progress_table(
+            report::categories_to_polars(&[category]).expect("category frame"),
+            spent,
+            month,
+        )
+        .expect("goal progress table")
+        .collect()
+        .expect("collect goal progress");
+
+        let expected = goal_target as f64 / 1000.0 * f64::from(month) / 12.0;
+        let actual = outflow_milli as f64 / 1000.0;
+        let margin = expected * goals::GOAL_PROGRESS_TOLERANCE;
+        let status = df.column("goal_status").expect("goal_status").str().expect("goal_status str").get(0);
+        let expected_ytd = df.column("expected_ytd").expect("expected_ytd").f64().expect("expected_ytd f64").get(0);
+        // skip values too close to a boundary for float rounding to decide
+        prop_assume!((actual - (expected - margin)).abs() > 1e-6 && (actual - (expected + margin)).abs() > 1e-6);
+        let expected_status = if actual < expected - margin {
+            "behind"
+        } else if actual > expected + margin {
+            "ahead"
+        } else {
+            "on track"
+        };
+        prop_assert!(close(expected_ytd.expect("expected_ytd value"), expected));
+        prop_assert_eq!(status, Some(expected_status));
+    }
+
+    #[test]
+    fn prop_diff_csv_reports_of_identical_files_is_empty(
+        rows in prop::collection::btree_map("[A-Za-z ]{1,12}", -10_000.0_f64..10_000.0, 0..12)
+    ) {
+        let names: Vec<&str> = rows.keys().map(String::as_str).collect();
+        let spent: Vec<f64> = rows.values().copied().collect();
+        let mut df = DataFrame::new(vec![
+            Column::new("category_name".into(), names),
+            Column::new("spent".into(), spent),
+        ])
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.csv");
+        std::fs::write(&path, crustynab::CsvRenderer::render(&mut df).unwrap()).unwrap();
+
+        let diff = report::diff_csv_reports(&path, &path).unwrap();
+        prop_assert_eq!(diff.added.height(), 0);
+        prop_assert_eq!(diff.removed.height(), 0);
+        prop_assert_eq!(diff.changed.height(), 0);
+    }
+
+    #[test]
+    fn prop_transactions_to_polars_keeps_every_unsplit_transaction(
+        transactions in transaction_details_strategy(),
+        drop_subtransactions in prop::collection::vec(any::<bool>(), 15),
+    ) {
+        // some splits lose their subtransactions, as malformed YNAB entries do
+        let transactions: Vec<Transaction> = transactions
+            .into_iter()
+            .zip(drop_subtransactions)
+            .map(|(mut transaction, drop)| {
+                if drop {
+                    transaction.subtransactions.clear();
+                }
+                transaction
+            })
+            .collect();
+        let df = report::transactions_to_polars(&transactions)
+            .expect("transactions_to_polars")
+            .0
+            .collect()
+            .expect("collect transaction frame");
+        let ids: Vec<Option<&str>> = df
+            .column("id")
+            .expect("id")
+            .str()
+            .expect("id str")
+            .into_iter()
+            .collect();
+        let unsplit = |transaction: &Transaction| {
+            transaction.subtransactions.is_empty() && transaction.category_name.is_some()
+        };
+
+        for transaction in &transactions {
+            if unsplit(transaction) {
+                let rows = ids
+                    .iter()
+                    .filter(|id| **id == Some(transaction.id.as_str()))
+                    .count();
+                let same_id = transactions
+                    .iter()
+                    .filter(|other| other.id == transaction.id && unsplit(other))
+                    .count();
+                prop_assert_eq!(rows, same_id);
+            }
+        }
+    }
+
+    #[test]
+    fn prop_category_deltas_sum_to_budgeted_difference(
+        before in category_rows_strategy(),
+        after in category_rows_strategy(),
+    ) {
+        let total = |rows: &[CategoryRow]| rows.iter().map(|row| row.budgeted).sum::<f64>();
+        let deltas = report::diff_category_frames(category_frame(&before), category_frame(&after))
+            .expect("diff_category_frames")
+            .0
+            .select([col("budgeted_delta").sum()])
+            .collect()
+            .expect("collect deltas");
+        let delta_sum = deltas
+            .column("budgeted_delta")
+            .expect("budgeted_delta")
+            .f64()
+            .expect("budgeted_delta f64")
+            .get(0)
+            .unwrap_or(0.0);
+        prop_assert!(close(delta_sum, total(&after) - total(&before)));
+    }
 }
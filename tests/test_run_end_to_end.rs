@@ -0,0 +1,812 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crustynab::app::{RunOptions, StageLog, run};
+use crustynab::config::Config;
+use crustynab::logging::subscriber;
+use crustynab::ynab::{BudgetSummary, Category, CategoryGroup, Transaction, YnabApi};
+
+/// Serves canned YNAB API responses from `tests/fixtures/`.
+struct FileYnabClient {
+    dir: PathBuf,
+}
+
+impl FileYnabClient {
+    fn new() -> Self {
+        Self {
+            dir: Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"),
+        }
+    }
+
+    /// Reads `file` and deserializes the value at `pointer` within its `data` envelope.
+    fn read<T: DeserializeOwned>(&self, file: &str, pointer: &str) -> Result<T> {
+        let path = self.dir.join(file);
+        let contents =
+            std::fs::read_to_string(&path).with_context(|| format!("reading {path:?}"))?;
+        let response: serde_json::Value =
+            serde_json::from_str(&contents).with_context(|| format!("parsing {path:?}"))?;
+        let value = response
+            .pointer(&format!("/data/{pointer}"))
+            .with_context(|| format!("{path:?} has no data/{pointer}"))?;
+        serde_json::from_value(value.clone()).with_context(|| format!("deserializing {path:?}"))
+    }
+}
+
+impl YnabApi for FileYnabClient {
+    fn get_budgets(&self) -> Result<Vec<BudgetSummary>> {
+        self.read("budgets.json", "budgets")
+    }
+
+    fn get_category_groups(&self, _budget_id: &str) -> Result<Vec<CategoryGroup>> {
+        self.read("categories.json", "category_groups")
+    }
+
+    fn get_month_category(
+        &self,
+        budget_id: &str,
+        _month: NaiveDate,
+        category_id: &str,
+    ) -> Result<Category> {
+        self.get_category_groups(budget_id)?
+            .into_iter()
+            .flat_map(|group| group.categories)
+            .find(|category| category.id == category_id)
+            .with_context(|| format!("no fixture category {category_id}"))
+    }
+
+    fn get_transactions(
+        &self,
+        _budget_id: &str,
+        since_date: NaiveDate,
+    ) -> Result<Vec<Transaction>> {
+        let transactions: Vec<Transaction> = self.read("transactions.json", "transactions")?;
+        Ok(transactions
+            .into_iter()
+            .filter(|txn| txn.date >= since_date)
+            .collect())
+    }
+}
+
+fn make_config(output_format: serde_json::Value) -> Config {
+    make_config_watching(
+        json!({"Essentials": "#dfe7f5", "Fun": "#f4dccb"}),
+        output_format,
+    )
+}
+
+fn make_config_watching(watch_list: serde_json::Value, output_format: serde_json::Value) -> Config {
+    serde_json::from_value(json!({
+        "budgetName": "Household",
+        "personalAccessToken": "unused",
+        "categoryGroupWatchList": watch_list,
+        "resolutionDate": "2024-03-13",
+        "showAllRows": false,
+        "outputFormat": output_format
+    }))
+    .unwrap()
+}
+
+fn run_to_string(cfg: &Config) -> String {
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        cfg,
+        &RunOptions::default(),
+        &mut out,
+    )
+    .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// Replaces the run timestamp in metadata headers and chart JSON so snapshots are stable.
+fn redact_generated_at(output: &str) -> String {
+    output
+        .lines()
+        .map(|line| {
+            if let Some((prefix, _)) = line.split_once("generated_at: ") {
+                format!("{prefix}generated_at: [timestamp]")
+            } else if let Some((prefix, _)) = line.split_once("\"generated_at\": ") {
+                format!("{prefix}\"generated_at\": \"[timestamp]\",")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_run_end_to_end_polars_print() {
+    let output = run_to_string(&make_config(json!("polars_print")));
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_run_end_to_end_ascii_table() {
+    let output = run_to_string(&make_config(json!("ascii_table")));
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn stdout_json_writes_a_line_per_report_and_totals_row() {
+    let output = run_to_string(&make_config(json!("stdout_json")));
+    let tables: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["_table"].clone())
+        .collect();
+    insta::assert_debug_snapshot!((
+        output.lines().count(),
+        tables.iter().filter(|table| *table == "report").count(),
+        tables.iter().filter(|table| *table == "totals").count(),
+    ));
+}
+
+#[test]
+fn test_run_end_to_end_csv_print() {
+    let output = run_to_string(&make_config(json!("csv_print")));
+    insta::assert_snapshot!(redact_generated_at(&output));
+}
+
+#[test]
+fn test_run_end_to_end_visual_html() {
+    let dir = tempfile::tempdir().unwrap();
+    let visual_path = dir.path().join("report.html");
+    run_to_string(&make_config(json!({"visual_output": visual_path})));
+    insta::assert_snapshot!(std::fs::read_to_string(&visual_path).unwrap());
+}
+
+/// Runs with an empty watch list, so no categories are reported.
+fn run_without_categories(output_format: serde_json::Value) -> String {
+    run_to_string(&make_config_watching(json!({}), output_format))
+}
+
+/// Runs a file output format with no categories and returns the written file.
+fn file_output_without_categories(key: &str, file_name: &str) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(file_name);
+    run_without_categories(json!({ key: path }));
+    redact_generated_at(&std::fs::read_to_string(&path).unwrap())
+}
+
+#[test]
+fn zero_categories_polars_print() {
+    insta::assert_snapshot!(run_without_categories(json!("polars_print")));
+}
+
+#[test]
+fn zero_categories_csv_print() {
+    insta::assert_snapshot!(redact_generated_at(&run_without_categories(json!(
+        "csv_print"
+    ))));
+}
+
+#[test]
+fn zero_categories_csv_file() {
+    insta::assert_snapshot!(file_output_without_categories("csv_output", "report.csv"));
+}
+
+#[test]
+fn zero_categories_visual_html() {
+    insta::assert_snapshot!(file_output_without_categories(
+        "visual_output",
+        "report.html"
+    ));
+}
+
+#[test]
+fn zero_categories_sql() {
+    insta::assert_snapshot!(file_output_without_categories("sql_output", "report.sql"));
+}
+
+#[test]
+fn zero_categories_summary() {
+    insta::assert_snapshot!(file_output_without_categories(
+        "summary_output",
+        "summary.txt"
+    ));
+}
+
+#[test]
+fn zero_categories_chart_data() {
+    insta::assert_snapshot!(file_output_without_categories("chart_output", "chart.json"));
+}
+
+fn make_semicolon_config() -> Config {
+    let mut cfg = make_config(json!("csv_print"));
+    cfg.csv_delimiter = Some(';');
+    cfg
+}
+
+#[test]
+fn csv_delimiter_semicolon_csv_print() {
+    let output = run_to_string(&make_semicolon_config());
+    insta::assert_snapshot!(redact_generated_at(&output));
+}
+
+#[test]
+fn csv_delimiter_semicolon_rows_split_evenly() {
+    let output = run_to_string(&make_semicolon_config());
+    // field count per line of the report table, from its header to the totals marker
+    let field_counts: Vec<usize> = output
+        .lines()
+        .skip_while(|line| !line.starts_with("category_group_name"))
+        .take_while(|line| *line != "category_group_totals")
+        .map(|line| line.split(';').count())
+        .collect();
+    insta::assert_snapshot!(format!("{field_counts:?}"));
+}
+
+#[test]
+fn csv_delimiter_rejects_non_ascii() {
+    let mut cfg = make_config(json!("csv_print"));
+    cfg.csv_delimiter = Some('§');
+    let err = run(
+        &FileYnabClient::new(),
+        &cfg,
+        &RunOptions::default(),
+        &mut Vec::new(),
+    )
+    .unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn running_total_csv_print() {
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &make_config(json!("csv_print")),
+        &RunOptions {
+            running_total: true,
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    insta::assert_snapshot!(redact_generated_at(&String::from_utf8(out).unwrap()));
+}
+
+#[test]
+fn sparklines_visual_html_trend_cells() {
+    let dir = tempfile::tempdir().unwrap();
+    let visual_path = dir.path().join("report.html");
+    let mut cfg = make_config(json!({"visual_output": visual_path}));
+    cfg.show_sparklines = true;
+    run_to_string(&cfg);
+    let html = std::fs::read_to_string(&visual_path).unwrap();
+    let trend_cells: Vec<&str> = html
+        .lines()
+        .filter(|line| line.contains("trend-cell"))
+        .map(str::trim)
+        .collect();
+    insta::assert_snapshot!(trend_cells.join("\n"));
+}
+
+/// Runs the two complete months before 2024-04-02 (February and March 2024).
+fn run_months_to_string(output_format: serde_json::Value) -> String {
+    let mut cfg = make_config(output_format);
+    cfg.resolution_date = NaiveDate::from_ymd_opt(2024, 4, 2);
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &cfg,
+        &RunOptions {
+            months: Some(2),
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn months_polars_print() {
+    insta::assert_snapshot!(run_months_to_string(json!("polars_print")));
+}
+
+#[test]
+fn months_csv_print() {
+    insta::assert_snapshot!(run_months_to_string(json!("csv_print")));
+}
+
+#[test]
+fn months_visual_html_pivots_by_month() {
+    let dir = tempfile::tempdir().unwrap();
+    let visual_path = dir.path().join("report.html");
+    run_months_to_string(json!({"visual_output": visual_path}));
+    insta::assert_snapshot!(std::fs::read_to_string(&visual_path).unwrap());
+}
+
+#[test]
+fn default_months_applies_without_flag() {
+    let mut cfg = make_config(json!("csv_print"));
+    cfg.resolution_date = NaiveDate::from_ymd_opt(2024, 4, 2);
+    cfg.default_months = Some(2);
+    insta::assert_snapshot!(run_to_string(&cfg));
+}
+
+#[test]
+fn months_rejects_unsupported_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cfg = make_config(json!({"sql_output": dir.path().join("report.sql")}));
+    cfg.default_months = Some(2);
+    let err = run(
+        &FileYnabClient::new(),
+        &cfg,
+        &RunOptions::default(),
+        &mut Vec::new(),
+    )
+    .unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn wide_csv_print() {
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &make_config(json!("csv_print")),
+        &RunOptions {
+            wide: true,
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    insta::assert_snapshot!(redact_generated_at(&String::from_utf8(out).unwrap()));
+}
+
+#[test]
+fn wide_csv_print_without_categories() {
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &make_config_watching(json!({}), json!("csv_print")),
+        &RunOptions {
+            wide: true,
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    insta::assert_snapshot!(redact_generated_at(&String::from_utf8(out).unwrap()));
+}
+
+/// Clonable `Write` handle onto a shared buffer, so the test can read what the
+/// subscriber wrote.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn debug_logging_emits_report_milestones() {
+    let buffer = SharedBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = subscriber("debug", move || writer.clone()).unwrap();
+    tracing::subscriber::with_default(subscriber, || {
+        run_to_string(&make_config(json!("polars_print")));
+    });
+
+    let logged = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let milestones: Vec<&str> = logged
+        .lines()
+        .filter(|line| line.starts_with("DEBUG") && line.contains(" crustynab::report: "))
+        .collect();
+    insta::assert_snapshot!(milestones.join("\n"));
+}
+
+#[test]
+fn budget_override_csv_print() {
+    let mut cfg = make_config(json!("csv_print"));
+    cfg.budget_overrides.insert("Groceries".into(), 20.0);
+    insta::assert_snapshot!(redact_generated_at(&run_to_string(&cfg)));
+}
+
+#[test]
+fn budget_override_visual_html_marks_planned_cell() {
+    let dir = tempfile::tempdir().unwrap();
+    let visual_path = dir.path().join("report.html");
+    let mut cfg = make_config(json!({"visual_output": visual_path}));
+    cfg.budget_overrides.insert("Groceries".into(), 20.0);
+    run_to_string(&cfg);
+    let html = std::fs::read_to_string(&visual_path).unwrap();
+    let marked: Vec<&str> = html
+        .lines()
+        .filter(|line| line.contains("class=\"override\""))
+        .map(str::trim)
+        .collect();
+    insta::assert_snapshot!(marked.join("\n"));
+}
+
+#[test]
+fn compare_last_week_csv_print() {
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &make_config(json!("csv_print")),
+        &RunOptions {
+            compare_last_week: true,
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    insta::assert_snapshot!(redact_generated_at(&String::from_utf8(out).unwrap()));
+}
+
+#[test]
+fn show_goal_progress_csv_print() {
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &make_config(json!("csv_print")),
+        &RunOptions {
+            show_goal_progress: true,
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    let output = String::from_utf8(out).unwrap();
+    let section = output
+        .split_once("goal_progress\n")
+        .map_or("", |(_, section)| section);
+    insta::assert_snapshot!(section);
+}
+
+/// A [`FileYnabClient`] whose budget list is unavailable, to show a run never asked
+/// for it.
+struct NoBudgetListClient(FileYnabClient);
+
+impl YnabApi for NoBudgetListClient {
+    fn get_budgets(&self) -> Result<Vec<BudgetSummary>> {
+        anyhow::bail!("get_budgets should not be called")
+    }
+
+    fn get_category_groups(&self, budget_id: &str) -> Result<Vec<CategoryGroup>> {
+        self.0.get_category_groups(budget_id)
+    }
+
+    fn get_month_category(
+        &self,
+        budget_id: &str,
+        month: NaiveDate,
+        category_id: &str,
+    ) -> Result<Category> {
+        self.0.get_month_category(budget_id, month, category_id)
+    }
+
+    fn get_transactions(&self, budget_id: &str, since_date: NaiveDate) -> Result<Vec<Transaction>> {
+        self.0.get_transactions(budget_id, since_date)
+    }
+}
+
+#[test]
+fn budget_id_option_skips_budget_lookup() {
+    let mut out = Vec::new();
+    run(
+        &NoBudgetListClient(FileYnabClient::new()),
+        &make_config(json!("polars_print")),
+        &RunOptions {
+            budget_id: Some("6a1f0e8c-3b52-4c1e-9a4e-1d2f3a4b5c6d".into()),
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn config_budget_id_must_match_budget_name() {
+    let mut cfg = make_config(json!("polars_print"));
+    cfg.budget_id = Some("f0e1d2c3-b4a5-4968-8776-655443322110".into());
+    let err = run(
+        &FileYnabClient::new(),
+        &cfg,
+        &RunOptions::default(),
+        &mut Vec::new(),
+    )
+    .unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn print_mode_links_companion_stylesheet() {
+    let dir = tempfile::tempdir().unwrap();
+    let visual_path = dir.path().join("report.html");
+    let mut cfg = make_config(json!({"visual_output": visual_path}));
+    cfg.print_mode = true;
+    run_to_string(&cfg);
+    let html = std::fs::read_to_string(&visual_path).unwrap();
+    let link = html
+        .lines()
+        .find(|line| line.contains("<link"))
+        .unwrap_or_default();
+    let stylesheet = std::fs::read_to_string(dir.path().join("report.print.css")).unwrap();
+    insta::assert_snapshot!(format!("{}\n{stylesheet}", link.trim()));
+}
+
+#[test]
+fn transaction_detail_pages_written_next_to_visual_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let visual_path = dir.path().join("report.html");
+    let mut cfg = make_config(json!({"visual_output": visual_path}));
+    cfg.transaction_detail_pages = true;
+    run_to_string(&cfg);
+    let mut pages: Vec<String> = std::fs::read_dir(dir.path().join("report_transactions"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    pages.sort();
+    let html = std::fs::read_to_string(&visual_path).unwrap();
+    let links: Vec<&str> = html
+        .split("data-detail-href=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .collect();
+    insta::assert_snapshot!(format!(
+        "pages:\n{}\nlinks:\n{}",
+        pages.join("\n"),
+        links.join("\n")
+    ));
+}
+
+#[test]
+fn currency_symbol_applies_to_visual_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let visual_path = dir.path().join("report.html");
+    let mut cfg = make_config(json!({"visual_output": visual_path}));
+    cfg.currency_symbol = Some("$".to_string());
+    run_to_string(&cfg);
+    let html = std::fs::read_to_string(&visual_path).unwrap();
+    let total_row: Vec<&str> = html
+        .lines()
+        .skip_while(|line| {
+            !line.contains(r#"<tr class="total" style="background-color: #b7b7b7;">"#)
+        })
+        .take_while(|line| !line.contains("</tr>"))
+        .collect();
+    insta::assert_snapshot!(total_row.join("\n"));
+}
+
+#[test]
+fn verbose_leaves_report_output_unchanged() {
+    let cfg = make_config(json!("csv_print"));
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &cfg,
+        &RunOptions {
+            verbose: true,
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(
+        redact_generated_at(&String::from_utf8(out).unwrap()),
+        redact_generated_at(&run_to_string(&cfg))
+    );
+}
+
+#[test]
+fn stage_log_line_shows_elapsed_milliseconds() {
+    insta::assert_snapshot!(StageLog::line(
+        std::time::Duration::from_micros(23_400),
+        "relevant_transactions",
+        "142 → 37 rows"
+    ));
+}
+
+#[test]
+fn csv_append_adds_a_section_per_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log.csv");
+    let cfg = make_config(json!({ "csv_append": path }));
+    run_to_string(&cfg);
+    run_to_string(&cfg);
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn export_transactions_writes_week_transactions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transactions.csv");
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &make_config(json!("polars_print")),
+        &RunOptions {
+            export_transactions: Some(path.clone()),
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn export_transactions_has_a_row_per_transaction_in_the_week() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transactions.csv");
+    let cfg = make_config(json!("polars_print"));
+    let mut out = Vec::new();
+    run(
+        &FileYnabClient::new(),
+        &cfg,
+        &RunOptions {
+            export_transactions: Some(path.clone()),
+            ..RunOptions::default()
+        },
+        &mut out,
+    )
+    .unwrap();
+
+    let week = crustynab::calendar_weeks::month_week_for_date(cfg.today().unwrap()).unwrap();
+    let transactions = FileYnabClient::new()
+        .get_transactions("", week.week_start)
+        .unwrap();
+    let in_week = crustynab::report::relevant_transactions(
+        crustynab::report::transactions_to_polars(&transactions).unwrap(),
+        week.week_start,
+        week.week_end,
+    )
+    .0
+    .collect()
+    .unwrap()
+    .height();
+    let exported = std::fs::read_to_string(&path).unwrap().lines().count() - 1;
+    assert_eq!(exported, in_week);
+}
+
+fn run_check(cfg: &Config) -> anyhow::Result<()> {
+    run(
+        &FileYnabClient::new(),
+        cfg,
+        &RunOptions {
+            check: true,
+            ..RunOptions::default()
+        },
+        &mut Vec::new(),
+    )
+}
+
+#[test]
+fn check_fails_when_a_category_is_over_the_threshold() {
+    let mut cfg = make_config(json!("polars_print"));
+    cfg.check_threshold_percent = Some(30.0);
+    let err = run_check(&cfg).unwrap_err();
+    let failed = err
+        .downcast_ref::<crustynab::alerts::BudgetCheckFailed>()
+        .map(|failed| {
+            let names: Vec<&str> = failed
+                .over_budget
+                .iter()
+                .map(|alert| alert.category_name.as_str())
+                .collect();
+            format!("{failed}: {}", names.join(", "))
+        });
+    insta::assert_snapshot!(failed.unwrap_or_default());
+}
+
+#[test]
+fn check_passes_when_every_category_is_within_budget() {
+    insta::assert_debug_snapshot!(run_check(&make_config(json!("polars_print"))).is_ok());
+}
+
+const BUDGET_ID: &str = "6a1f0e8c-3b52-4c1e-9a4e-1d2f3a4b5c6d";
+
+/// A config for `behavior` whose server knowledge cache, in `dir`, records the fixture
+/// categories as an earlier run saw them: Groceries under its old name "Food", plus
+/// the since-removed "Old Phone Plan" in Essentials and "Bus Pass" in the unwatched
+/// Transport group.
+fn config_after_categories_vanished(
+    dir: &Path,
+    behavior: crustynab::config::MissingCategoryBehavior,
+) -> Config {
+    let known = json!({
+        "9e3d1c2b-4a5f-4e6d-8c7b-1a2b3c4d5e6f": {"group": "Essentials", "name": "Food"},
+        "1f2e3d4c-5b6a-4798-8a9b-0c1d2e3f4a5b": {"group": "Essentials", "name": "Rent"},
+        "3b4c5d6e-7f80-4192-a324-b5c6d7e8f9a0": {"group": "Essentials", "name": "Gym"},
+        "5d6e7f80-91a2-43b4-8546-d7e8f9a0b1c2": {"group": "Fun", "name": "Books"},
+        "old-phone-plan": {"group": "Essentials", "name": "Old Phone Plan"},
+        "bus-pass": {"group": "Transport", "name": "Bus Pass"}
+    });
+    let cache = dir.join("knowledge.json");
+    std::fs::write(
+        &cache,
+        json!({BUDGET_ID: {"known_categories": known}}).to_string(),
+    )
+    .unwrap();
+    let mut cfg = make_config(json!("polars_print"));
+    cfg.server_knowledge_cache = Some(cache);
+    cfg.on_missing_category = behavior;
+    cfg
+}
+
+/// The run's error, if any, and the names of the categories it recorded.
+fn run_recording_categories(cfg: &Config) -> String {
+    let result = run(
+        &FileYnabClient::new(),
+        cfg,
+        &RunOptions::default(),
+        &mut Vec::new(),
+    );
+    let cache: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cfg.server_knowledge_cache.as_ref().unwrap()).unwrap(),
+    )
+    .unwrap();
+    let mut recorded: Vec<&str> = cache[BUDGET_ID]["known_categories"]
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|known| known["name"].as_str().unwrap())
+        .collect();
+    recorded.sort();
+    format!(
+        "error: {:?}\nrecorded: {recorded:?}",
+        result.err().map(|err| err.to_string())
+    )
+}
+
+#[test]
+fn on_missing_category_ignore_records_current_categories() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = config_after_categories_vanished(
+        dir.path(),
+        crustynab::config::MissingCategoryBehavior::Ignore,
+    );
+    insta::assert_snapshot!(run_recording_categories(&cfg));
+}
+
+#[test]
+fn on_missing_category_warn_still_reports() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = config_after_categories_vanished(
+        dir.path(),
+        crustynab::config::MissingCategoryBehavior::Warn,
+    );
+    insta::assert_snapshot!(run_recording_categories(&cfg));
+}
+
+#[test]
+fn on_missing_category_error_fails_once_and_refreshes_record() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = config_after_categories_vanished(
+        dir.path(),
+        crustynab::config::MissingCategoryBehavior::Error,
+    );
+    let first = run_recording_categories(&cfg);
+    let second = run_recording_categories(&cfg);
+    insta::assert_snapshot!(format!("{first}\n---\n{second}"));
+}
+
+#[test]
+fn on_missing_category_ignores_a_shrunken_watch_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cfg = config_after_categories_vanished(
+        dir.path(),
+        crustynab::config::MissingCategoryBehavior::Error,
+    );
+    run_recording_categories(&cfg);
+    cfg.category_group_watch_list.shift_remove("Fun");
+    insta::assert_snapshot!(run_recording_categories(&cfg));
+}
@@ -0,0 +1,120 @@
+use httpmock::prelude::*;
+use polars::prelude::*;
+
+use crustynab::slack_report::{post_slack_blocks, report_to_slack_blocks};
+
+fn make_report_df() -> DataFrame {
+    DataFrame::new(vec![
+        Column::new(
+            "category_group_name".into(),
+            &["Essentials", "Essentials", "Fun & Games"],
+        ),
+        Column::new("category_name".into(), &["Groceries", "Rent", "<Books>"]),
+        Column::new("budgeted".into(), &[50.0, 100.0, 10.0]),
+        Column::new("spent".into(), &[-18.5, -25.0, -4.0]),
+        Column::new("balance".into(), &[31.5, 75.0, 6.0]),
+    ])
+    .unwrap()
+}
+
+fn make_totals_df() -> DataFrame {
+    DataFrame::new(vec![
+        Column::new(
+            "category_group_name".into(),
+            &["Essentials", "Fun & Games", "Total"],
+        ),
+        Column::new("budgeted".into(), &[150.0, 10.0, 160.0]),
+        Column::new("spent".into(), &[-43.5, -4.0, -47.5]),
+        Column::new("balance".into(), &[106.5, 6.0, 112.5]),
+    ])
+    .unwrap()
+}
+
+fn many_categories_df(count: usize) -> DataFrame {
+    let names: Vec<String> = (1..=count).map(|i| format!("Category {i}")).collect();
+    DataFrame::new(vec![
+        Column::new("category_group_name".into(), vec!["Essentials"; count]),
+        Column::new("category_name".into(), names),
+        Column::new("budgeted".into(), vec![10.0; count]),
+        Column::new("spent".into(), vec![-1.0; count]),
+        Column::new("balance".into(), vec![9.0; count]),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn slack_blocks_payload() {
+    let payloads =
+        report_to_slack_blocks(&make_report_df(), &make_totals_df(), "Mar 10 - Mar 16").unwrap();
+    insta::assert_json_snapshot!(payloads);
+}
+
+#[test]
+fn slack_blocks_split_sections_at_ten_fields() {
+    let payloads =
+        report_to_slack_blocks(&many_categories_df(7), &make_totals_df(), "Mar 10 - Mar 16")
+            .unwrap();
+    let field_counts: Vec<usize> = payloads[0]["blocks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|block| block["fields"].as_array().map(Vec::len))
+        .collect();
+    insta::assert_snapshot!(format!("{field_counts:?}"));
+}
+
+#[test]
+fn slack_blocks_split_into_messages_of_at_most_fifty_blocks() {
+    let payloads = report_to_slack_blocks(
+        &many_categories_df(300),
+        &make_totals_df(),
+        "Mar 10 - Mar 16",
+    )
+    .unwrap();
+    let messages: Vec<(String, usize)> = payloads
+        .iter()
+        .map(|payload| {
+            (
+                payload["blocks"][0]["text"]["text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                payload["blocks"].as_array().map_or(0, Vec::len),
+            )
+        })
+        .collect();
+    insta::assert_debug_snapshot!(messages);
+}
+
+#[test]
+fn post_slack_blocks_sends_payload_to_webhook() {
+    let payload = report_to_slack_blocks(&make_report_df(), &make_totals_df(), "Mar 10 - Mar 16")
+        .unwrap()
+        .remove(0);
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/services/T000/B000/XXXX")
+            .header("content-type", "application/json")
+            .json_body(payload.clone());
+        then.status(200).body("ok");
+    });
+
+    post_slack_blocks(&server.url("/services/T000/B000/XXXX"), &payload).unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn post_slack_blocks_reports_webhook_errors() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST).path("/hook");
+        then.status(404).body("no_team");
+    });
+
+    let err =
+        post_slack_blocks(&server.url("/hook"), &serde_json::json!({"blocks": []})).unwrap_err();
+
+    insta::assert_snapshot!(format!("{err:#}"));
+}
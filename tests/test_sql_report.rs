@@ -0,0 +1,107 @@
+use chrono::NaiveDate;
+use polars::prelude::*;
+use rusqlite::Connection;
+
+use crustynab::sql_report::{INCOME_TABLE, REPORT_TABLE, TOTALS_TABLE, build_sql_report};
+
+fn make_report_df() -> DataFrame {
+    DataFrame::new(vec![
+        Column::new(
+            "category_group_name".into(),
+            &["Essentials", "Essentials", "Fun"],
+        ),
+        Column::new(
+            "category_name".into(),
+            &["Groceries", "Rent", "Children's Books"],
+        ),
+        Column::new("budgeted".into(), &[50.0, 100.0, 10.0]),
+        Column::new("spent".into(), &[-18.5, -25.0, -4.0]),
+        Column::new("balance".into(), &[31.5, 75.0, 6.0]),
+        Column::new("goal_cadence".into(), &["monthly", "annual", "annual"]),
+    ])
+    .unwrap()
+}
+
+fn make_totals_df() -> DataFrame {
+    DataFrame::new(vec![
+        Column::new(
+            "category_group_name".into(),
+            &["Essentials", "Fun", "Total"],
+        ),
+        Column::new("budgeted".into(), &[150.0, 10.0, 160.0]),
+        Column::new("spent".into(), &[-43.5, -4.0, -47.5]),
+        Column::new("balance".into(), &[106.5, 6.0, 112.5]),
+    ])
+    .unwrap()
+}
+
+fn make_income_df() -> DataFrame {
+    DataFrame::new(vec![
+        Column::new("category_group_name".into(), &["Essentials"]),
+        Column::new("category_name".into(), &["Groceries"]),
+        Column::new("income".into(), &[4.5]),
+    ])
+    .unwrap()
+}
+
+fn count_rows(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+        row.get(0)
+    })
+    .unwrap()
+}
+
+#[test]
+fn sql_report_script() {
+    let date = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let sql = build_sql_report(&make_report_df(), &make_totals_df(), None, date).unwrap();
+    insta::assert_snapshot!(sql);
+}
+
+#[test]
+fn sql_report_executes_in_sqlite() {
+    let conn = Connection::open_in_memory().unwrap();
+    let first = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let second = NaiveDate::from_ymd_opt(2024, 3, 17).unwrap();
+    for date in [first, first, second] {
+        let sql = build_sql_report(&make_report_df(), &make_totals_df(), None, date).unwrap();
+        conn.execute_batch(&sql).unwrap();
+    }
+
+    let apostrophe_name: String = conn
+        .query_row(
+            &format!(
+                "SELECT category_name FROM {REPORT_TABLE} WHERE category_group_name = 'Fun' LIMIT 1"
+            ),
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    insta::assert_snapshot!(format!(
+        "report_rows={} totals_rows={} fun_category={apostrophe_name}",
+        count_rows(&conn, REPORT_TABLE),
+        count_rows(&conn, TOTALS_TABLE)
+    ));
+}
+
+#[test]
+fn sql_report_includes_income_table() {
+    let conn = Connection::open_in_memory().unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let income = make_income_df();
+    let sql = build_sql_report(&make_report_df(), &make_totals_df(), Some(&income), date).unwrap();
+    conn.execute_batch(&sql).unwrap();
+    conn.execute_batch(&sql).unwrap();
+
+    let total_income: f64 = conn
+        .query_row(
+            &format!("SELECT SUM(income) FROM {INCOME_TABLE}"),
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    insta::assert_snapshot!(format!(
+        "income_rows={} total_income={total_income}",
+        count_rows(&conn, INCOME_TABLE)
+    ));
+}
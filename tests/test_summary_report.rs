@@ -0,0 +1,86 @@
+use polars::prelude::*;
+
+use crustynab::summary_report::{MAX_LINE_WIDTH, build_summary, write_summary};
+
+const WEEK_LABEL: &str = "Week 2 (Mar 10 - Mar 16)";
+
+fn make_report_table() -> LazyFrame {
+    DataFrame::new(vec![
+        Column::new(
+            "category_group_name".into(),
+            &["Essentials", "Essentials", "Essentials", "Fun"],
+        ),
+        Column::new(
+            "category_name".into(),
+            &["Groceries", "Rent", "Utilities", "Children's Books"],
+        ),
+        Column::new("budgeted".into(), &[50.0, 1500.0, 80.0, 10.0]),
+        Column::new("spent".into(), &[-18.5, -1250.0, 0.0, -4.0]),
+        Column::new("balance".into(), &[31.5, 250.0, 80.0, 6.0]),
+    ])
+    .unwrap()
+    .lazy()
+}
+
+#[test]
+fn summary_text() {
+    let summary = build_summary(&make_report_table(), WEEK_LABEL, None).unwrap();
+    insta::assert_snapshot!(summary);
+}
+
+#[test]
+fn summary_text_with_income() {
+    let income = DataFrame::new(vec![
+        Column::new("category_group_name".into(), &["Essentials", "Fun"]),
+        Column::new("category_name".into(), &["Groceries", "Children's Books"]),
+        Column::new("income".into(), &[4.5, 12.0]),
+    ])
+    .unwrap()
+    .lazy();
+    let summary = build_summary(&make_report_table(), WEEK_LABEL, Some(&income)).unwrap();
+    insta::assert_snapshot!(summary);
+}
+
+#[test]
+fn summary_round_trip_contains_every_category() {
+    let report_table = make_report_table();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("summary.txt");
+    write_summary(&path, &report_table, WEEK_LABEL, None).unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+
+    let df = report_table.collect().unwrap();
+    let spent = df.column("spent").unwrap().f64().unwrap();
+    let names = df.column("category_name").unwrap().str().unwrap();
+    let missing: Vec<&str> = names
+        .iter()
+        .zip(spent.iter())
+        .filter(|(_, spent)| *spent != Some(0.0))
+        .filter_map(|(name, _)| name)
+        .filter(|name| !written.contains(name))
+        .collect();
+    assert!(missing.is_empty(), "missing from summary: {missing:?}");
+}
+
+#[test]
+fn summary_lines_fit_width_with_long_names() {
+    let long_name = "A category name that goes on and on well past the width of a terminal";
+    let report_table = DataFrame::new(vec![
+        Column::new("category_group_name".into(), &[long_name]),
+        Column::new("category_name".into(), &[long_name]),
+        Column::new("budgeted".into(), &[1_000_000.0]),
+        Column::new("spent".into(), &[-987_654.32]),
+        Column::new("balance".into(), &[12_345.68]),
+    ])
+    .unwrap()
+    .lazy();
+    let summary = build_summary(&report_table, WEEK_LABEL, None).unwrap();
+    let too_long: Vec<&str> = summary
+        .lines()
+        .filter(|line| line.chars().count() > MAX_LINE_WIDTH)
+        .collect();
+    assert!(
+        too_long.is_empty(),
+        "lines over {MAX_LINE_WIDTH}: {too_long:?}"
+    );
+}
@@ -1,4 +1,10 @@
-use crustynab::visual_report::{CURRENCY, build_visual_report_html, darken_hex, format_currency};
+use crustynab::report::TransactionFrame;
+use crustynab::visual_report::{
+    CURRENCY, ColorScheme, CurrencyFormat, Sparklines, VisualSections,
+    build_transaction_detail_html, build_visual_report_html, darken_hex, format_currency,
+    format_currency_with, format_with_commas, lighten_hex, parse_currency, scheme_group_colors,
+    sparkline_svg, transaction_detail_file_name, write_visual_report_html,
+};
 use indexmap::IndexMap;
 use polars::prelude::*;
 
@@ -27,6 +33,76 @@ fn format_currency_large_with_commas() {
     insta::assert_snapshot!(format_currency(1234567.89, true));
 }
 
+#[test]
+fn format_currency_with_precision_zero_omits_decimal_separator() {
+    insta::assert_snapshot!(format_currency_with(
+        -1234.56,
+        true,
+        &CurrencyFormat::gbp(),
+        0
+    ));
+}
+
+#[test]
+fn format_currency_with_precision_four() {
+    insta::assert_snapshot!(format_currency_with(
+        1234.56789,
+        true,
+        &CurrencyFormat::gbp(),
+        4
+    ));
+}
+
+#[test]
+fn format_currency_with_custom_separators() {
+    let euro = CurrencyFormat {
+        symbol: "€".to_string(),
+        group_separator: ".".to_string(),
+        decimal_separator: ",".to_string(),
+        precision: 2,
+    };
+    insta::assert_snapshot!(format_currency_with(1234567.891, true, &euro, 3));
+}
+
+#[test]
+fn format_currency_with_hides_value_rounding_to_zero() {
+    insta::assert_snapshot!(format_currency_with(0.4, false, &CurrencyFormat::gbp(), 0));
+}
+
+#[test]
+fn parse_currency_reads_custom_separators() {
+    let euro = CurrencyFormat {
+        symbol: "€".to_string(),
+        group_separator: ".".to_string(),
+        decimal_separator: ",".to_string(),
+        precision: 2,
+    };
+    insta::assert_snapshot!(parse_currency("-€1.234.567,891", &euro).unwrap_or(f64::NAN));
+}
+
+#[test]
+fn parse_currency_rejects_non_numeric_text() {
+    let error = parse_currency("£twelve", &CurrencyFormat::gbp())
+        .err()
+        .map(|error| format!("{error:#}"));
+    insta::assert_snapshot!(error.unwrap_or_default());
+}
+
+#[test]
+fn format_with_commas_negative_uses_absolute_value() {
+    insta::assert_snapshot!(format_with_commas(-1234.5, 2));
+}
+
+#[test]
+fn format_with_commas_below_one() {
+    insta::assert_snapshot!(format_with_commas(0.456, 2));
+}
+
+#[test]
+fn format_with_commas_over_one_billion() {
+    insta::assert_snapshot!(format_with_commas(12_345_678_901.0, 0));
+}
+
 #[test]
 fn darken_hex_standard() {
     insta::assert_snapshot!(darken_hex("#dfe7f5", 0.85));
@@ -43,10 +119,35 @@ fn darken_hex_invalid_passthrough() {
 }
 
 #[test]
-fn darken_hex_short_passthrough() {
+fn darken_hex_expands_shorthand() {
     insta::assert_snapshot!(darken_hex("#fff", 0.85));
 }
 
+#[test]
+fn darken_hex_shorthand_matches_full_form() {
+    assert_eq!(darken_hex("#fff", 0.5), darken_hex("#ffffff", 0.5));
+}
+
+#[test]
+fn darken_hex_wrong_length_passthrough() {
+    insta::assert_snapshot!(darken_hex("#ffff", 0.85));
+}
+
+#[test]
+fn lighten_hex_standard() {
+    insta::assert_snapshot!(lighten_hex("#386ea1", 0.5));
+}
+
+#[test]
+fn lighten_hex_expands_shorthand() {
+    insta::assert_snapshot!(lighten_hex("#036", 0.25));
+}
+
+#[test]
+fn lighten_hex_invalid_passthrough() {
+    insta::assert_snapshot!(lighten_hex("not-a-color", 0.5));
+}
+
 fn make_report_lazyframe(rows: Vec<(&str, &str, f64, f64, f64, &str)>) -> LazyFrame {
     let cat_names: Vec<&str> = rows.iter().map(|r| r.0).collect();
     let group_names: Vec<&str> = rows.iter().map(|r| r.1).collect();
@@ -86,6 +187,7 @@ fn visual_report_basic() {
         "Week 11 (Mar 10 - Mar 16)",
         2024,
         true,
+        VisualSections::default(),
     )
     .unwrap();
 
@@ -102,7 +204,15 @@ fn visual_report_totals_include_hidden_balance() {
     let mut group_colors = IndexMap::new();
     group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
 
-    let html = build_visual_report_html(report, &group_colors, "Week 1", 2024, false).unwrap();
+    let html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 1",
+        2024,
+        false,
+        VisualSections::default(),
+    )
+    .unwrap();
 
     assert!(!html.contains("Savings"));
     assert!(html.contains("Total Essentials"));
@@ -126,8 +236,544 @@ fn visual_report_hides_remaining_when_no_spend() {
     let mut group_colors = IndexMap::new();
     group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
 
-    let html = build_visual_report_html(report, &group_colors, "Week 1", 2024, true).unwrap();
+    let html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 1",
+        2024,
+        true,
+        VisualSections::default(),
+    )
+    .unwrap();
 
     assert!(html.contains("Zero Spend"));
     insta::assert_snapshot!(html);
 }
+
+#[test]
+fn visual_report_renders_budget_trend_arrows() {
+    let mut df = make_report_lazyframe(vec![
+        ("Books", "Fun", 10.0, -4.0, 6.0, "annual"),
+        ("Games", "Fun", 20.0, -3.0, 17.0, "annual"),
+        ("Films", "Fun", 15.0, -2.0, 13.0, "annual"),
+    ])
+    .collect()
+    .unwrap();
+    df.with_column(Column::new("budget_change".into(), &[5.0, -2.5, 0.0]))
+        .unwrap();
+
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Fun".to_string(), "#f4dccb".to_string());
+
+    let html = build_visual_report_html(
+        df.lazy(),
+        &group_colors,
+        "Week 1",
+        2024,
+        true,
+        VisualSections::default(),
+    )
+    .unwrap();
+    insta::assert_snapshot!(html);
+}
+
+#[test]
+fn write_visual_report_html_to_buffer_matches_build() {
+    let rows = vec![
+        ("Groceries", "Essentials", 50.0, -18.5, 31.5, "monthly"),
+        ("Books", "Fun", 10.0, -4.0, 6.0, "annual"),
+    ];
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    group_colors.insert("Fun".to_string(), "#f4dccb".to_string());
+
+    let mut buffer: Vec<u8> = Vec::new();
+    write_visual_report_html(
+        &mut buffer,
+        make_report_lazyframe(rows.clone()),
+        &group_colors,
+        "Week 11 (Mar 10 - Mar 16)",
+        2024,
+        true,
+        VisualSections::default(),
+    )
+    .unwrap();
+    let written = String::from_utf8(buffer).unwrap();
+
+    let built = build_visual_report_html(
+        make_report_lazyframe(rows),
+        &group_colors,
+        "Week 11 (Mar 10 - Mar 16)",
+        2024,
+        true,
+        VisualSections::default(),
+    )
+    .unwrap();
+    assert_eq!(written, built);
+}
+
+#[test]
+fn visual_report_renders_income_section() {
+    let report = make_report_lazyframe(vec![(
+        "Groceries",
+        "Essentials",
+        50.0,
+        -18.5,
+        31.5,
+        "monthly",
+    )]);
+    let income = DataFrame::new(vec![
+        Column::new("category_group_name".into(), &["Essentials"]),
+        Column::new("category_name".into(), &["Groceries & Co"]),
+        Column::new("income".into(), &[4.5]),
+    ])
+    .unwrap()
+    .lazy();
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+
+    let html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 1",
+        2024,
+        true,
+        VisualSections {
+            income_table: Some(income),
+            ..VisualSections::default()
+        },
+    )
+    .unwrap();
+    let section_start = html.find("<h2>Income</h2>").unwrap();
+    let section_end = section_start + html[section_start..].find("</table>").unwrap();
+    insta::assert_snapshot!(&html[section_start..section_end]);
+}
+
+/// Minimal XML well-formedness check: tags nest and close in order, attribute values
+/// are quoted, and text contains no bare `<` or unknown `&` entity.
+fn check_well_formed_xml(xml: &str) -> Result<(), String> {
+    let mut open_tags: Vec<String> = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        check_xml_text(&rest[..start])?;
+        let end = rest[start..]
+            .find('>')
+            .ok_or_else(|| format!("unterminated tag at {:?}", &rest[start..]))?
+            + start;
+        let tag = &rest[start + 1..end];
+        if let Some(name) = tag.strip_prefix('/') {
+            match open_tags.pop() {
+                Some(open) if open == name.trim() => {}
+                other => return Err(format!("</{name}> closes {other:?}")),
+            }
+        } else {
+            let self_closing = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let (name, attributes) = tag.split_once(' ').unwrap_or((tag, ""));
+            if attributes.matches('"').count() % 2 != 0 {
+                return Err(format!("unbalanced quotes in <{tag}>"));
+            }
+            for attribute in attributes.split("\" ").filter(|a| !a.trim().is_empty()) {
+                if !attribute.contains("=\"") {
+                    return Err(format!("unquoted attribute {attribute:?} in <{name}>"));
+                }
+            }
+            if !self_closing {
+                open_tags.push(name.to_string());
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    check_xml_text(rest)?;
+    match open_tags.is_empty() {
+        true => Ok(()),
+        false => Err(format!("unclosed tags {open_tags:?}")),
+    }
+}
+
+fn check_xml_text(text: &str) -> Result<(), String> {
+    let entities = ["&amp;", "&lt;", "&gt;", "&quot;", "&#x27;", "&#39;"];
+    text.match_indices('&').try_for_each(|(i, _)| {
+        match entities.iter().any(|entity| text[i..].starts_with(entity)) {
+            true => Ok(()),
+            false => Err(format!("bare & in {text:?}")),
+        }
+    })
+}
+
+#[test]
+fn sparkline_svg_is_well_formed_xml() {
+    let svg = sparkline_svg(&[(9, -12.0), (10, 0.0), (11, 4.5), (12, -30.25)], "#1f3a5f");
+    insta::assert_snapshot!(format!("{:?}", check_well_formed_xml(&svg)));
+}
+
+#[test]
+fn sparkline_svg_scales_bars_to_largest_week() {
+    insta::assert_snapshot!(sparkline_svg(
+        &[(9, -10.0), (10, -20.0), (11, 0.0), (12, 5.0)],
+        "#1f3a5f"
+    ));
+}
+
+#[test]
+fn well_formed_check_rejects_mismatched_tags() {
+    insta::assert_snapshot!(format!(
+        "{:?}",
+        check_well_formed_xml("<svg><rect></svg></rect>")
+    ));
+}
+
+#[test]
+fn visual_report_renders_trend_column() {
+    let report = make_report_lazyframe(vec![
+        ("Groceries", "Essentials", 50.0, -18.5, 31.5, "monthly"),
+        ("Rent", "Essentials", 100.0, -25.0, 75.0, "annual"),
+    ]);
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    let mut sparklines = Sparklines::new();
+    sparklines.insert(
+        "Groceries".to_string(),
+        vec![(9, -10.0), (10, -12.0), (11, -18.5)],
+    );
+    let html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 11",
+        2024,
+        true,
+        VisualSections {
+            sparklines: Some(&sparklines),
+            ..VisualSections::default()
+        },
+    )
+    .unwrap();
+    let trend_lines: Vec<&str> = html
+        .lines()
+        .filter(|line| line.contains("Trend") || line.contains("trend-cell"))
+        .map(str::trim)
+        .collect();
+    insta::assert_snapshot!(trend_lines.join("\n"));
+}
+
+const ALL_SCHEMES: [ColorScheme; 4] = [
+    ColorScheme::Pastel,
+    ColorScheme::Earth,
+    ColorScheme::Ocean,
+    ColorScheme::HighContrast,
+];
+
+fn watch_list_of(count: usize) -> IndexMap<String, String> {
+    (0..count)
+        .map(|i| (format!("Group {i}"), "#000000".to_string()))
+        .collect()
+}
+
+#[test]
+fn color_scheme_palettes_are_distinct_rrggbb_colors() {
+    let report: Vec<String> = ALL_SCHEMES
+        .iter()
+        .map(|scheme| {
+            let palette = scheme.palette();
+            let valid = palette.iter().all(|color| {
+                color.len() == 7
+                    && color.starts_with('#')
+                    && color[1..].chars().all(|ch| ch.is_ascii_hexdigit())
+            });
+            let distinct = palette
+                .iter()
+                .collect::<std::collections::HashSet<_>>()
+                .len();
+            format!(
+                "{scheme:?}: {} colors, valid={valid}, distinct={distinct}",
+                palette.len()
+            )
+        })
+        .collect();
+    insta::assert_snapshot!(report.join("\n"));
+}
+
+#[test]
+fn scheme_group_colors_override_config_and_cycle() {
+    let palette_len = ColorScheme::Ocean.palette().len();
+    let colors = scheme_group_colors(&watch_list_of(palette_len + 2), ColorScheme::Ocean);
+    let lines: Vec<String> = colors
+        .iter()
+        .map(|(group, color)| format!("{group}: {color}"))
+        .collect();
+    insta::assert_snapshot!(lines.join("\n"));
+}
+
+#[test]
+fn color_scheme_parses_cli_names() {
+    let parsed: Vec<String> = ["pastel", "earth", "ocean", "high-contrast", "neon"]
+        .iter()
+        .map(|name| match name.parse::<ColorScheme>() {
+            Ok(scheme) => format!("{name}: {scheme:?}"),
+            Err(err) => format!("{name}: {err}"),
+        })
+        .collect();
+    insta::assert_snapshot!(parsed.join("\n"));
+}
+
+/// The document head of a one-category report styled with `extra_css`.
+fn head_with_extra_css(extra_css: &str) -> String {
+    let report = make_report_lazyframe(vec![(
+        "Groceries",
+        "Essentials",
+        50.0,
+        -18.5,
+        31.5,
+        "monthly",
+    )]);
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    let html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 11",
+        2024,
+        true,
+        VisualSections {
+            extra_css: Some(extra_css),
+            ..VisualSections::default()
+        },
+    )
+    .unwrap();
+    let head_end = html.find("</head>").unwrap();
+    let extra_start = html[..head_end].rfind("  <style>").unwrap();
+    html[extra_start..head_end].to_string()
+}
+
+#[test]
+fn visual_report_appends_extra_css() {
+    insta::assert_snapshot!(head_with_extra_css(
+        "h1 { font-family: \"Inter\", sans-serif; }\ntable > thead { color: #123456; }"
+    ));
+}
+
+#[test]
+fn visual_report_escapes_style_end_tag_in_extra_css() {
+    insta::assert_snapshot!(head_with_extra_css(
+        "h1 { color: red; }</style><script>alert(1)</script>"
+    ));
+}
+
+#[test]
+fn visual_report_has_print_media_query() {
+    let report = make_report_lazyframe(vec![(
+        "Groceries",
+        "Essentials",
+        50.0,
+        -18.5,
+        31.5,
+        "monthly",
+    )]);
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    let html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 11",
+        2024,
+        true,
+        VisualSections::default(),
+    )
+    .unwrap();
+    let print_block: Vec<&str> = html
+        .lines()
+        .skip_while(|line| line.trim() != "@media print {")
+        .take_while(|line| !line.contains("</style>"))
+        .collect();
+    insta::assert_snapshot!(print_block.join("\n"));
+}
+
+fn detail_transactions() -> TransactionFrame {
+    let dates = Column::new("date".into(), &[19797, 19795, 19797, 19796])
+        .cast(&DataType::Date)
+        .unwrap();
+    let df = DataFrame::new(vec![
+        dates,
+        Column::new("amount".into(), &[-12.5, -30.0, 4.25, -99.0]),
+        Column::new(
+            "payee_name".into(),
+            &[Some("Corner Shop"), Some("Fish & Co"), None, Some("Garage")],
+        ),
+        Column::new(
+            "category_name".into(),
+            &["Groceries", "Groceries", "Groceries", "Car"],
+        ),
+    ])
+    .unwrap();
+    TransactionFrame(df.lazy())
+}
+
+fn detail_body(html: &str) -> &str {
+    let start = html.find("<body>").unwrap();
+    let end = html.find("</body>").unwrap() + "</body>".len();
+    &html[start..end]
+}
+
+#[test]
+fn transaction_detail_html_body_is_well_formed() {
+    let html =
+        build_transaction_detail_html("Groceries", detail_transactions(), "Week 11").unwrap();
+    insta::assert_snapshot!(format!("{:?}", check_well_formed_xml(detail_body(&html))));
+}
+
+#[test]
+fn transaction_detail_html_lists_category_transactions_by_date() {
+    let html =
+        build_transaction_detail_html("Groceries", detail_transactions(), "Week 11").unwrap();
+    insta::assert_snapshot!(detail_body(&html));
+}
+
+#[test]
+fn transaction_detail_html_shares_report_styles() {
+    let report = make_report_lazyframe(vec![(
+        "Groceries",
+        "Essentials",
+        50.0,
+        -18.5,
+        31.5,
+        "monthly",
+    )]);
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    let report_html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 11",
+        2024,
+        true,
+        VisualSections::default(),
+    )
+    .unwrap();
+    let detail_html =
+        build_transaction_detail_html("Groceries", detail_transactions(), "Week 11").unwrap();
+    let styles = |html: &str| -> Vec<String> {
+        html.lines()
+            .skip_while(|line| line.trim() != ":root {")
+            .take_while(|line| !line.contains("@media print") && !line.contains("</style>"))
+            .map(str::to_string)
+            .collect()
+    };
+    assert_eq!(styles(&report_html), styles(&detail_html));
+}
+
+#[test]
+fn transaction_detail_file_name_escapes_non_alphanumeric_bytes() {
+    insta::assert_snapshot!(transaction_detail_file_name("Eating Out/Café-2"));
+}
+
+#[test]
+fn visual_report_rows_link_transaction_detail_pages() {
+    let report = make_report_lazyframe(vec![
+        ("Groceries", "Essentials", 50.0, -18.5, 31.5, "monthly"),
+        ("Fish & Chips", "Essentials", 20.0, -5.0, 15.0, "monthly"),
+    ]);
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    let html = build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 11",
+        2024,
+        true,
+        VisualSections {
+            transaction_detail_dir: Some("report_transactions"),
+            ..VisualSections::default()
+        },
+    )
+    .unwrap();
+    let rows: Vec<&str> = html
+        .lines()
+        .filter(|line| line.trim_start().starts_with("<tr class="))
+        .collect();
+    insta::assert_snapshot!(rows.join("\n"));
+}
+
+fn long_groups_html(max_rows_per_group: Option<usize>) -> String {
+    let report = make_report_lazyframe(vec![
+        ("Bakery", "Essentials", 20.0, -4.0, 16.0, "monthly"),
+        ("Butcher", "Essentials", 40.0, -35.0, 5.0, "monthly"),
+        ("Groceries", "Essentials", 50.0, -18.5, 31.5, "monthly"),
+        ("Pharmacy", "Essentials", 15.0, -2.5, 12.5, "monthly"),
+        ("Rent", "Essentials", 100.0, -100.0, 0.0, "annual"),
+        ("Books", "Fun", 10.0, -4.0, 6.0, "monthly"),
+        ("Cinema", "Fun", 12.0, -12.0, 0.0, "monthly"),
+        ("Games", "Fun", 20.0, -7.0, 13.0, "monthly"),
+    ]);
+    let mut group_colors = IndexMap::new();
+    group_colors.insert("Essentials".to_string(), "#dfe7f5".to_string());
+    group_colors.insert("Fun".to_string(), "#f4dccb".to_string());
+    build_visual_report_html(
+        report,
+        &group_colors,
+        "Week 11",
+        2024,
+        true,
+        VisualSections {
+            max_rows_per_group,
+            ..VisualSections::default()
+        },
+    )
+    .unwrap()
+}
+
+/// `(first cell, row class)` of each body row.
+fn body_row_labels(html: &str) -> Vec<(String, String)> {
+    let lines: Vec<&str> = html.lines().collect();
+    lines
+        .windows(2)
+        .filter_map(|pair| {
+            let class = pair[0]
+                .trim()
+                .strip_prefix("<tr class=\"")?
+                .split('"')
+                .next()?;
+            let label = pair[1].trim().strip_prefix("<td>")?.split("</td>").next()?;
+            Some((label.to_string(), class.to_string()))
+        })
+        .collect()
+}
+
+#[test]
+fn max_rows_per_group_renders_top_rows_and_one_more_row_per_group() {
+    let html = long_groups_html(Some(2));
+    let data_rows = body_row_labels(&html)
+        .into_iter()
+        .filter(|(_, class)| class != "total")
+        .map(|(label, class)| format!("{class}: {label}"))
+        .collect::<Vec<_>>();
+    insta::assert_snapshot!(data_rows.join("\n"));
+}
+
+#[test]
+fn max_rows_per_group_keeps_group_totals() {
+    let total_rows = |html: &str| -> String {
+        let lines: Vec<&str> = html.lines().collect();
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.contains(r#"<tr class="total""#))
+            .flat_map(|(i, _)| lines[i..i + 6].to_vec())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    assert_eq!(
+        total_rows(&long_groups_html(None)),
+        total_rows(&long_groups_html(Some(2)))
+    );
+}
+
+#[test]
+fn max_rows_per_group_more_row_sums_hidden_categories() {
+    let html = long_groups_html(Some(2));
+    let more_row: Vec<&str> = html
+        .lines()
+        .skip_while(|line| !line.contains(r#"<tr class="more""#))
+        .take_while(|line| !line.contains("</tr>"))
+        .collect();
+    insta::assert_snapshot!(more_row.join("\n"));
+}
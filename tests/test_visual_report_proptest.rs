@@ -1,9 +1,12 @@
-use crustynab::visual_report::{CURRENCY, darken_hex, format_currency};
+use crustynab::visual_report::{
+    CURRENCY, CurrencyFormat, darken_hex, format_currency, format_currency_with,
+    format_with_commas, lighten_hex, parse_currency,
+};
 use proptest::prelude::*;
 
 fn is_valid_hex_color(value: &str) -> bool {
     value.starts_with('#')
-        && value.len() == 7
+        && (value.len() == 7 || value.len() == 4)
         && value[1..].chars().all(|ch| ch.is_ascii_hexdigit())
 }
 
@@ -58,4 +61,81 @@ proptest! {
         prop_assume!(!is_valid_hex_color(&value));
         prop_assert_eq!(darken_hex(&value, 0.85), value);
     }
+
+    #[test]
+    fn prop_format_with_commas_comma_count(
+        value in -10_000_000_000.0f64..10_000_000_000.0f64,
+        decimal_places in 0_u32..=4,
+    ) {
+        let formatted = format_with_commas(value, decimal_places);
+        let integer_part = formatted.split('.').next().unwrap_or("");
+        let integer_digits = integer_part.chars().filter(char::is_ascii_digit).count();
+        let commas = integer_part.chars().filter(|&ch| ch == ',').count();
+
+        prop_assert!(!formatted.starts_with('-'));
+        prop_assert_eq!(commas, (integer_digits - 1) / 3);
+    }
+
+    #[test]
+    fn prop_shorthand_and_full_hex_darken_alike(
+        red in 0u8..16,
+        green in 0u8..16,
+        blue in 0u8..16,
+        factor in 0.0f64..=1.0f64,
+    ) {
+        let shorthand = format!("#{red:x}{green:x}{blue:x}");
+        let full = format!("#{red:x}{red:x}{green:x}{green:x}{blue:x}{blue:x}");
+        prop_assert_eq!(darken_hex(&shorthand, factor), darken_hex(&full, factor));
+        prop_assert_eq!(lighten_hex(&shorthand, factor), lighten_hex(&full, factor));
+    }
+
+    #[test]
+    fn prop_lighten_hex_never_darkens(
+        red in any::<u8>(),
+        green in any::<u8>(),
+        blue in any::<u8>(),
+        amount in 0.0f64..=1.0f64,
+    ) {
+        let color = format!("#{red:02x}{green:02x}{blue:02x}");
+        let lightened = lighten_hex(&color, amount);
+
+        prop_assert_eq!(lightened.len(), 7);
+        let channels: Vec<u8> = [1, 3, 5]
+            .iter()
+            .map(|&i| u8::from_str_radix(&lightened[i..i + 2], 16).expect("hex"))
+            .collect();
+        prop_assert!(channels[0] >= red);
+        prop_assert!(channels[1] >= green);
+        prop_assert!(channels[2] >= blue);
+    }
+
+    #[test]
+    fn prop_format_currency_matches_gbp_at_two_places(
+        value in -1_000_000.0f64..1_000_000.0f64,
+        show_zero in any::<bool>(),
+    ) {
+        prop_assert_eq!(
+            format_currency(value, show_zero),
+            format_currency_with(value, show_zero, &CurrencyFormat::gbp(), 2)
+        );
+    }
+
+    #[test]
+    fn prop_format_currency_with_precision_sets_decimal_digits(
+        value in -1_000_000.0f64..1_000_000.0f64,
+        precision in 0_u8..=4,
+    ) {
+        let formatted = format_currency_with(value, true, &CurrencyFormat::gbp(), precision);
+        let decimals = formatted.split_once('.').map_or(0, |(_, decimals)| decimals.len());
+        prop_assert_eq!(decimals, usize::from(precision));
+    }
+
+    #[test]
+    fn prop_format_currency_round_trips_through_parse(
+        value in (-1_000_000.0f64..1_000_000.0f64).prop_filter("non-zero", |value| *value != 0.0),
+    ) {
+        let parsed = parse_currency(&format_currency(value, true), &CurrencyFormat::gbp())
+            .expect("formatted amounts parse");
+        prop_assert!((parsed - value).abs() <= 0.01);
+    }
 }
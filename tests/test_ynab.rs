@@ -0,0 +1,81 @@
+use serde_json::json;
+
+use crustynab::ynab::{Category, GoalType};
+
+fn category_with(goal_percentage_complete: serde_json::Value) -> Category {
+    serde_json::from_value(json!({
+        "id": "c1",
+        "name": "Groceries",
+        "budgeted": 50000,
+        "balance": 31500,
+        "goal_percentage_complete": goal_percentage_complete
+    }))
+    .unwrap()
+}
+
+#[test]
+fn category_goal_percentage_complete_present() {
+    insta::assert_debug_snapshot!(category_with(json!(53)).goal_percentage_complete);
+}
+
+#[test]
+fn category_goal_percentage_complete_null() {
+    insta::assert_debug_snapshot!(category_with(json!(null)).goal_percentage_complete);
+}
+
+#[test]
+fn category_goal_percentage_complete_missing() {
+    let category: Category =
+        serde_json::from_value(json!({"id": "c1", "name": "Groceries"})).unwrap();
+    insta::assert_debug_snapshot!(category.goal_percentage_complete);
+}
+
+fn goal_type_of(goal_type: serde_json::Value) -> Option<GoalType> {
+    let category: Category = serde_json::from_value(json!({
+        "id": "c1",
+        "name": "Groceries",
+        "goal_type": goal_type
+    }))
+    .unwrap();
+    category.goal_type
+}
+
+#[test]
+fn category_goal_type_target_balance() {
+    insta::assert_debug_snapshot!(goal_type_of(json!("TB")));
+}
+
+#[test]
+fn category_goal_type_target_balance_by_date() {
+    insta::assert_debug_snapshot!(goal_type_of(json!("TBD")));
+}
+
+#[test]
+fn category_goal_type_monthly_funding() {
+    insta::assert_debug_snapshot!(goal_type_of(json!("MF")));
+}
+
+#[test]
+fn category_goal_type_plan_your_spending() {
+    insta::assert_debug_snapshot!(goal_type_of(json!("NEED")));
+}
+
+#[test]
+fn category_goal_type_debt() {
+    insta::assert_debug_snapshot!(goal_type_of(json!("DEBT")));
+}
+
+#[test]
+fn category_goal_type_unknown_code_falls_back() {
+    insta::assert_debug_snapshot!(goal_type_of(json!("SAVE")));
+}
+
+#[test]
+fn category_goal_type_null() {
+    insta::assert_debug_snapshot!(goal_type_of(json!(null)));
+}
+
+#[test]
+fn goal_type_code_round_trips_unknown() {
+    insta::assert_snapshot!(GoalType::Unknown("SAVE".to_string()).code());
+}
@@ -0,0 +1,589 @@
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use httpmock::prelude::*;
+use serde_json::json;
+use ynab_api::apis::configuration::Configuration;
+
+use crustynab::visual_report::format_currency_with;
+use crustynab::ynab::{
+    DEFAULT_RETRY_AFTER, HttpYnabClient, YnabApi, remaining_requests, retry_after,
+};
+
+const BUDGET_ID: &str = "6a1f0e8c-3b52-4c1e-9a4e-1d2f3a4b5c6d";
+const GROUP_ID: &str = "0c8f2a7e-5d41-4b3a-8e2f-7a6b5c4d3e2f";
+const CATEGORY_ID: &str = "9e3d1c2b-4a5f-4e6d-8c7b-1a2b3c4d5e6f";
+const ACCOUNT_ID: &str = "2b4d6f8a-1c3e-4a5b-9d7f-0e2c4a6b8d0f";
+
+fn category_json() -> serde_json::Value {
+    json!({
+        "id": CATEGORY_ID,
+        "category_group_id": GROUP_ID,
+        "category_group_name": "Essentials",
+        "name": "Groceries",
+        "hidden": false,
+        "budgeted": 50000,
+        "activity": -18500,
+        "balance": 31500,
+        "goal_cadence": 1,
+        "goal_target": 50000,
+        "deleted": false
+    })
+}
+
+fn configuration(server: &MockServer, client: reqwest::Client) -> Configuration {
+    let mut configuration = Configuration::new();
+    configuration.base_path = server.base_url();
+    configuration.bearer_access_token = Some("test-token".to_string());
+    configuration.client = client;
+    configuration
+}
+
+fn client(server: &MockServer) -> HttpYnabClient {
+    HttpYnabClient::with_base_url("test-token", &server.base_url()).unwrap()
+}
+
+#[test]
+fn get_budgets_from_mock_server() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/budgets")
+            .header("authorization", "Bearer test-token");
+        then.status(200).json_body(json!({
+            "data": {"budgets": [{
+                "id": BUDGET_ID,
+                "name": "Household",
+                "last_modified_on": "2024-03-14T09:30:00+00:00"
+            }]}
+        }));
+    });
+
+    let budgets = client(&server).get_budgets().unwrap();
+
+    mock.assert();
+    insta::assert_debug_snapshot!(budgets);
+}
+
+#[test]
+fn get_category_groups_from_mock_server() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/categories"))
+            .header("authorization", "Bearer test-token");
+        then.status(200).json_body(json!({
+            "data": {
+                "server_knowledge": 7,
+                "category_groups": [{
+                    "id": GROUP_ID,
+                    "name": "Essentials",
+                    "hidden": false,
+                    "deleted": false,
+                    "categories": [category_json()]
+                }]
+            }
+        }));
+    });
+
+    let groups = client(&server).get_category_groups(BUDGET_ID).unwrap();
+
+    mock.assert();
+    insta::assert_debug_snapshot!(groups);
+}
+
+#[test]
+fn get_month_category_from_mock_server() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!(
+                "/budgets/{BUDGET_ID}/months/2024-03-01/categories/{CATEGORY_ID}"
+            ))
+            .header("authorization", "Bearer test-token");
+        then.status(200)
+            .json_body(json!({"data": {"category": category_json()}}));
+    });
+
+    let month = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+    let category = client(&server)
+        .get_month_category(BUDGET_ID, month, CATEGORY_ID)
+        .unwrap();
+
+    mock.assert();
+    insta::assert_debug_snapshot!(category);
+}
+
+const RENT_CATEGORY_ID: &str = "4f6e8d0c-2b1a-4c3d-9e8f-7a6b5c4d3e2a";
+
+fn rent_category_json() -> serde_json::Value {
+    json!({
+        "id": RENT_CATEGORY_ID,
+        "category_group_id": GROUP_ID,
+        "category_group_name": "Essentials",
+        "name": "Rent",
+        "hidden": false,
+        "budgeted": 100000,
+        "activity": -25000,
+        "balance": 75000,
+        "goal_cadence": 13,
+        "goal_target": 1200000,
+        "deleted": false
+    })
+}
+
+/// Serves March 2024 both as a whole month and one category at a time, from the same
+/// category JSON.
+fn mock_month_endpoints(server: &MockServer) -> httpmock::Mock<'_> {
+    for category in [category_json(), rent_category_json()] {
+        let id = category["id"].as_str().unwrap().to_string();
+        server.mock(|when, then| {
+            when.method(GET).path(format!(
+                "/budgets/{BUDGET_ID}/months/2024-03-01/categories/{id}"
+            ));
+            then.status(200)
+                .json_body(json!({"data": {"category": category}}));
+        });
+    }
+    server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/months/2024-03-01"))
+            .header("authorization", "Bearer test-token");
+        then.status(200).json_body(json!({
+            "data": {"month": {
+                "month": "2024-03-01",
+                "income": 0,
+                "budgeted": 150000,
+                "activity": -43500,
+                "to_be_budgeted": 0,
+                "deleted": false,
+                "categories": [category_json(), rent_category_json()]
+            }}
+        }));
+    })
+}
+
+#[test]
+fn get_month_categories_batch_from_mock_server() {
+    let server = MockServer::start();
+    let month_mock = mock_month_endpoints(&server);
+
+    let month = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+    let categories = client(&server)
+        .get_month_categories_batch(BUDGET_ID, month)
+        .unwrap();
+
+    month_mock.assert();
+    insta::assert_debug_snapshot!(categories);
+}
+
+#[test]
+fn get_month_categories_batch_matches_individual_calls() {
+    let server = MockServer::start();
+    mock_month_endpoints(&server);
+    let client = client(&server);
+    let month = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+
+    let batch = client.get_month_categories_batch(BUDGET_ID, month).unwrap();
+    let individual: Vec<_> = [CATEGORY_ID, RENT_CATEGORY_ID]
+        .into_iter()
+        .map(|id| client.get_month_category(BUDGET_ID, month, id).unwrap())
+        .collect();
+
+    assert_eq!(format!("{batch:?}"), format!("{individual:?}"));
+}
+
+#[test]
+fn get_transactions_from_mock_server() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/transactions"))
+            .query_param("since_date", "2024-03-10")
+            .header("authorization", "Bearer test-token");
+        then.status(200).json_body(json!({
+            "data": {
+                "server_knowledge": 11,
+                "transactions": [{
+                    "id": "t1",
+                    "date": "2024-03-11",
+                    "amount": -30000,
+                    "cleared": "cleared",
+                    "approved": true,
+                    "account_id": ACCOUNT_ID,
+                    "account_name": "Current",
+                    "deleted": false,
+                    "payee_name": "Corner Shop",
+                    "category_name": "Split",
+                    "subtransactions": [
+                        {
+                            "id": "t1-a",
+                            "transaction_id": "t1",
+                            "amount": -18500,
+                            "category_name": "Groceries",
+                            "deleted": false
+                        },
+                        {
+                            "id": "t1-b",
+                            "transaction_id": "t1",
+                            "amount": -11500,
+                            "payee_name": "Newsagent",
+                            "category_name": "Books",
+                            "deleted": false
+                        }
+                    ]
+                }]
+            }
+        }));
+    });
+
+    let since = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let transactions = client(&server).get_transactions(BUDGET_ID, since).unwrap();
+
+    mock.assert();
+    insta::assert_debug_snapshot!(transactions);
+}
+
+fn budgets_body() -> serde_json::Value {
+    json!({"data": {"budgets": [{"id": BUDGET_ID, "name": "Household"}]}})
+}
+
+#[test]
+fn rate_limited_request_pauses_then_retries() {
+    let server = MockServer::start();
+    let mut limited = server.mock(|when, then| {
+        when.method(GET).path("/budgets");
+        then.status(429).header("Retry-After", "1");
+    });
+
+    let started = Instant::now();
+    let budgets = std::thread::scope(|scope| {
+        let request = scope.spawn(|| client(&server).get_budgets());
+        while limited.calls() == 0 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        limited.delete();
+        server.mock(|when, then| {
+            when.method(GET).path("/budgets");
+            then.status(200)
+                .header("X-Rate-Limit", "12/200")
+                .json_body(budgets_body());
+        });
+        request.join().unwrap()
+    })
+    .unwrap();
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    insta::assert_debug_snapshot!(budgets);
+}
+
+#[test]
+fn rate_limited_request_fails_when_pause_disabled() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/budgets");
+        then.status(429).header("Retry-After", "30");
+    });
+
+    let err = client(&server)
+        .with_rate_limit_pause(false)
+        .get_budgets()
+        .unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn request_log_appends_one_entry_per_call() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/budgets");
+        then.status(200).json_body(budgets_body());
+    });
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("requests.ndjson");
+
+    // separate clients show entries accumulate across runs rather than being truncated
+    for _ in 0..2 {
+        client(&server)
+            .with_log_file(&log_path)
+            .get_budgets()
+            .unwrap();
+    }
+
+    let entries: Vec<serde_json::Value> = std::fs::read_to_string(&log_path)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut entry: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(entry["request_time_ms"].is_u64());
+            entry["request_time_ms"] = json!("[elapsed]");
+            entry
+        })
+        .collect();
+    insta::assert_json_snapshot!(entries);
+}
+
+#[test]
+fn remaining_requests_parses_used_over_limit() {
+    let parsed: Vec<Option<u32>> = ["36/200", " 198 / 200 ", "250/200", "200", "x/200"]
+        .into_iter()
+        .map(remaining_requests)
+        .collect();
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn retry_after_defaults_when_missing_or_invalid() {
+    let waits: Vec<bool> = [None, Some("soon"), Some("")]
+        .into_iter()
+        .map(|header| retry_after(header) == DEFAULT_RETRY_AFTER)
+        .collect();
+    insta::assert_debug_snapshot!((retry_after(Some("7")), waits));
+}
+
+#[test]
+fn new_with_configuration_uses_the_given_client_and_base_path() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/budgets")
+            .header("authorization", "Bearer test-token");
+        then.status(200).json_body(json!({"data": {"budgets": []}}));
+    });
+    let client = HttpYnabClient::new_with_configuration(configuration(
+        &server,
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap(),
+    ))
+    .unwrap();
+
+    let budgets = client.get_budgets().unwrap();
+
+    mock.assert();
+    insta::assert_debug_snapshot!(budgets);
+}
+
+#[test]
+fn new_with_configuration_reports_client_timeouts() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/budgets");
+        then.status(200)
+            .delay(Duration::from_millis(500))
+            .json_body(json!({"data": {"budgets": []}}));
+    });
+    let client = HttpYnabClient::new_with_configuration(configuration(
+        &server,
+        reqwest::Client::builder()
+            .timeout(Duration::from_millis(1))
+            .build()
+            .unwrap(),
+    ))
+    .unwrap();
+
+    let error = client.get_budgets().unwrap_err();
+
+    assert!(format!("{error:#}").contains("timeout"), "{error:#}");
+}
+
+#[test]
+fn get_budget_currency_format_for_usd_budget() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/settings"))
+            .header("authorization", "Bearer test-token");
+        then.status(200).json_body(json!({
+            "data": {"settings": {
+                "date_format": {"format": "MM/DD/YYYY"},
+                "currency_format": {
+                    "iso_code": "USD",
+                    "example_format": "123,456.78",
+                    "decimal_digits": 2,
+                    "decimal_separator": ".",
+                    "symbol_first": true,
+                    "group_separator": ",",
+                    "currency_symbol": "$",
+                    "display_symbol": true
+                }
+            }}
+        }));
+    });
+
+    let format = client(&server)
+        .get_budget_currency_format(BUDGET_ID)
+        .unwrap();
+
+    mock.assert();
+    insta::assert_snapshot!(format_currency_with(1234.56, true, &format, 2));
+}
+
+fn transaction_json(id: &str, date: &str, amount: i64, deleted: bool) -> serde_json::Value {
+    json!({
+        "id": id,
+        "date": date,
+        "amount": amount,
+        "cleared": "cleared",
+        "approved": true,
+        "account_id": ACCOUNT_ID,
+        "account_name": "Current",
+        "deleted": deleted,
+        "payee_name": "Corner Shop",
+        "category_name": "Groceries",
+        "subtransactions": []
+    })
+}
+
+/// Serves the full transaction list from 2024-03-10 without `last_knowledge_of_server`
+/// and `delta` when it is 10, returning the two mocks.
+fn mock_transaction_sync(
+    server: &MockServer,
+    delta: Vec<serde_json::Value>,
+) -> (httpmock::Mock<'_>, httpmock::Mock<'_>) {
+    let full = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/transactions"))
+            .query_param("since_date", "2024-03-10")
+            .query_param_missing("last_knowledge_of_server");
+        then.status(200).json_body(json!({
+            "data": {"server_knowledge": 10, "transactions": [
+                transaction_json("t1", "2024-03-10", -1000, false),
+                transaction_json("t2", "2024-03-11", -2000, false),
+                transaction_json("t3", "2024-03-12", -3000, false),
+            ]}
+        }));
+    });
+    let incremental = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/transactions"))
+            .query_param("since_date", "2024-03-10")
+            .query_param("last_knowledge_of_server", "10");
+        then.status(200).json_body(json!({
+            "data": {"server_knowledge": 11, "transactions": delta}
+        }));
+    });
+    (full, incremental)
+}
+
+fn transaction_amounts(transactions: &[crustynab::ynab::Transaction]) -> String {
+    transactions
+        .iter()
+        .map(|txn| format!("{} {} {}", txn.id, txn.date, txn.amount))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn server_knowledge_cache_merges_transaction_delta() {
+    let server = MockServer::start();
+    let delta = vec![
+        transaction_json("t2", "2024-03-11", -2500, false),
+        transaction_json("t3", "2024-03-12", -3000, true),
+    ];
+    let (full, incremental) = mock_transaction_sync(&server, delta);
+    let dir = tempfile::tempdir().unwrap();
+    let since = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let cached_client =
+        || client(&server).with_server_knowledge_cache(dir.path().join("knowledge.json"));
+
+    let first = cached_client().get_transactions(BUDGET_ID, since).unwrap();
+    let second = cached_client().get_transactions(BUDGET_ID, since).unwrap();
+
+    full.assert();
+    incremental.assert();
+    insta::assert_snapshot!(format!(
+        "first run:\n{}\nsecond run:\n{}",
+        transaction_amounts(&first),
+        transaction_amounts(&second)
+    ));
+}
+
+#[test]
+fn server_knowledge_cache_refetches_after_a_truncated_cache_file() {
+    let server = MockServer::start();
+    let (full, incremental) = mock_transaction_sync(&server, vec![]);
+    let dir = tempfile::tempdir().unwrap();
+    let cache_path = dir.path().join("knowledge.json");
+    std::fs::write(&cache_path, r#"{"6a1f0e8c": {"transactions": {"server_kn"#).unwrap();
+    let since = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    let cached_client = || client(&server).with_server_knowledge_cache(&cache_path);
+
+    let first = cached_client().get_transactions(BUDGET_ID, since).unwrap();
+    cached_client().get_transactions(BUDGET_ID, since).unwrap();
+
+    full.assert();
+    incremental.assert();
+    insta::assert_snapshot!(transaction_amounts(&first));
+}
+
+#[test]
+fn server_knowledge_cache_serves_later_since_date_from_snapshot() {
+    let server = MockServer::start();
+    let (full, incremental) = mock_transaction_sync(&server, vec![]);
+    let dir = tempfile::tempdir().unwrap();
+    let cache_client =
+        client(&server).with_server_knowledge_cache(dir.path().join("knowledge.json"));
+
+    cache_client
+        .get_transactions(BUDGET_ID, NaiveDate::from_ymd_opt(2024, 3, 10).unwrap())
+        .unwrap();
+    let later = cache_client
+        .get_transactions(BUDGET_ID, NaiveDate::from_ymd_opt(2024, 3, 11).unwrap())
+        .unwrap();
+
+    full.assert();
+    incremental.assert();
+    insta::assert_snapshot!(transaction_amounts(&later));
+}
+
+#[test]
+fn server_knowledge_cache_merges_category_delta() {
+    let server = MockServer::start();
+    let group = |categories: Vec<serde_json::Value>| {
+        json!({
+            "id": GROUP_ID,
+            "name": "Essentials",
+            "hidden": false,
+            "deleted": false,
+            "categories": categories
+        })
+    };
+    let full = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/categories"))
+            .query_param_missing("last_knowledge_of_server");
+        then.status(200).json_body(json!({
+            "data": {
+                "server_knowledge": 7,
+                "category_groups": [group(vec![category_json(), rent_category_json()])]
+            }
+        }));
+    });
+    let mut rent = rent_category_json();
+    rent["budgeted"] = json!(120000);
+    let incremental = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("/budgets/{BUDGET_ID}/categories"))
+            .query_param("last_knowledge_of_server", "7");
+        then.status(200).json_body(json!({
+            "data": {"server_knowledge": 8, "category_groups": [group(vec![rent])]}
+        }));
+    });
+    let dir = tempfile::tempdir().unwrap();
+    let cached_client =
+        || client(&server).with_server_knowledge_cache(dir.path().join("knowledge.json"));
+
+    cached_client().get_category_groups(BUDGET_ID).unwrap();
+    let groups = cached_client().get_category_groups(BUDGET_ID).unwrap();
+
+    full.assert();
+    incremental.assert();
+    let budgeted: Vec<String> = groups
+        .iter()
+        .flat_map(|group| &group.categories)
+        .map(|category| format!("{} {}", category.name, category.budgeted))
+        .collect();
+    insta::assert_snapshot!(budgeted.join("\n"));
+}
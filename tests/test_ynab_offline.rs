@@ -0,0 +1,232 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde_json::json;
+
+use crustynab::ynab::{OfflineYnabClient, YnabApi};
+
+const BUDGET_ID: &str = "6a1f0e8c-3b52-4c1e-9a4e-1d2f3a4b5c6d";
+
+fn client() -> OfflineYnabClient {
+    OfflineYnabClient::from_path(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/budget_export.json"),
+    )
+    .unwrap()
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn offline_get_budgets() {
+    insta::assert_debug_snapshot!(client().get_budgets().unwrap());
+}
+
+#[test]
+fn offline_get_category_groups_attaches_categories() {
+    insta::assert_debug_snapshot!(client().get_category_groups(BUDGET_ID).unwrap());
+}
+
+#[test]
+fn offline_get_month_category_reads_that_month() {
+    let category = client()
+        .get_month_category(BUDGET_ID, date(2024, 2, 15), "cat-groceries")
+        .unwrap();
+    insta::assert_debug_snapshot!(category);
+}
+
+#[test]
+fn offline_get_transactions_resolves_names_and_splits() {
+    let transactions = client()
+        .get_transactions(BUDGET_ID, date(2024, 3, 10))
+        .unwrap();
+    insta::assert_debug_snapshot!(transactions);
+}
+
+#[test]
+fn offline_rejects_other_budget_ids() {
+    let err = client()
+        .get_category_groups("some-other-budget")
+        .unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn offline_reports_missing_month() {
+    let err = client()
+        .get_month_category(BUDGET_ID, date(2023, 12, 1), "cat-groceries")
+        .unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn offline_reports_missing_keys() {
+    let client = OfflineYnabClient::new(json!({
+        "data": {"budget": {"id": BUDGET_ID, "name": "Household", "category_groups": []}}
+    }));
+    let err = client.get_category_groups(BUDGET_ID).unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn offline_reports_missing_budget_name() {
+    let client = OfflineYnabClient::new(json!({"id": BUDGET_ID}));
+    let err = client.get_budgets().unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn offline_reports_missing_transaction_amount() {
+    let client = OfflineYnabClient::new(json!({
+        "id": BUDGET_ID,
+        "name": "Household",
+        "payees": [],
+        "categories": [],
+        "subtransactions": [],
+        "transactions": [{"id": "txn-1", "date": "2024-03-12"}]
+    }));
+    let err = client
+        .get_transactions(BUDGET_ID, date(2024, 3, 10))
+        .unwrap_err();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn offline_currency_format_reads_export_settings() {
+    let client = OfflineYnabClient::new(json!({
+        "id": BUDGET_ID,
+        "name": "Household",
+        "currency_format": {
+            "iso_code": "EUR",
+            "example_format": "123.456,78",
+            "decimal_digits": 2,
+            "decimal_separator": ",",
+            "symbol_first": true,
+            "group_separator": ".",
+            "currency_symbol": "€",
+            "display_symbol": true
+        }
+    }));
+    insta::assert_debug_snapshot!(client.get_budget_currency_format(BUDGET_ID).unwrap());
+}
+
+#[test]
+fn offline_currency_format_takes_decimal_digits_as_precision() {
+    let client = OfflineYnabClient::new(json!({
+        "id": BUDGET_ID,
+        "name": "Household",
+        "currency_format": {
+            "iso_code": "JPY",
+            "example_format": "123,456",
+            "decimal_digits": 0,
+            "decimal_separator": ".",
+            "symbol_first": true,
+            "group_separator": ",",
+            "currency_symbol": "¥",
+            "display_symbol": true
+        }
+    }));
+    insta::assert_debug_snapshot!(client.get_budget_currency_format(BUDGET_ID).unwrap());
+}
+
+#[test]
+fn offline_currency_format_defaults_without_export_settings() {
+    insta::assert_debug_snapshot!(client().get_budget_currency_format(BUDGET_ID).unwrap());
+}
+
+#[test]
+fn offline_month_categories_batch_matches_individual_calls() {
+    let client = client();
+    let month = date(2024, 3, 1);
+    let batch = client.get_month_categories_batch(BUDGET_ID, month).unwrap();
+    let individual: Vec<_> = batch
+        .iter()
+        .map(|category| {
+            client
+                .get_month_category(BUDGET_ID, month, &category.id)
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(format!("{batch:?}"), format!("{individual:?}"));
+}
+
+#[test]
+fn offline_month_categories_batch_reads_whole_month() {
+    let names: Vec<String> = client()
+        .get_month_categories_batch(BUDGET_ID, date(2024, 3, 20))
+        .unwrap()
+        .into_iter()
+        .map(|category| category.name)
+        .collect();
+    insta::assert_snapshot!(format!("{names:?}"));
+}
+
+/// A single-entry zip archive holding `contents` uncompressed under `name`.
+fn stored_zip(name: &str, contents: &[u8]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    writer.start_file(name, options).unwrap();
+    std::io::Write::write_all(&mut writer, contents).unwrap();
+    writer.finish().unwrap().into_inner()
+}
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn offline_from_zip_reads_nested_deflated_budget_file() {
+    let zipped = OfflineYnabClient::from_zip(&fixture("budget_export.zip")).unwrap();
+    assert_eq!(
+        format!("{:?}", zipped.get_category_groups(BUDGET_ID).unwrap()),
+        format!("{:?}", client().get_category_groups(BUDGET_ID).unwrap())
+    );
+}
+
+#[test]
+fn offline_from_zip_reads_stored_budget_file() {
+    let export = std::fs::read(fixture("budget_export.json")).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("stored.zip");
+    std::fs::write(&path, stored_zip("Budget.yfull", &export)).unwrap();
+    let zipped = OfflineYnabClient::from_zip(&path).unwrap();
+    assert_eq!(
+        format!("{:?}", zipped.get_budgets().unwrap()),
+        format!("{:?}", client().get_budgets().unwrap())
+    );
+}
+
+#[test]
+fn offline_from_path_detects_zip_by_magic() {
+    let export = std::fs::read(fixture("budget_export.json")).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("export.bin");
+    std::fs::write(&path, stored_zip("a/Budget.yfull", &export)).unwrap();
+    let from_path = OfflineYnabClient::from_path(&path).unwrap();
+    assert_eq!(
+        format!("{:?}", from_path.get_budgets().unwrap()),
+        format!("{:?}", client().get_budgets().unwrap())
+    );
+}
+
+#[test]
+fn offline_from_json_reads_plain_export() {
+    let from_json = OfflineYnabClient::from_json(&fixture("budget_export.json")).unwrap();
+    assert_eq!(
+        format!("{:?}", from_json.get_budgets().unwrap()),
+        format!("{:?}", client().get_budgets().unwrap())
+    );
+}
+
+#[test]
+fn offline_from_zip_without_budget_file_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("other.zip");
+    std::fs::write(&path, stored_zip("Budget.ydiff", b"{}")).unwrap();
+    let err = OfflineYnabClient::from_zip(&path).err().unwrap();
+    insta::assert_snapshot!(format!("{:#}", err).replace(&format!("{path:?}"), "PATH"));
+}
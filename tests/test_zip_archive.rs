@@ -0,0 +1,62 @@
+use crustynab::zip_archive::{MAX_ENTRY_SIZE, read_entry};
+
+// zlib raw deflate of b"abcabcabcabc hello hello" with Z_FIXED
+const FIXED_BLOCK: [u8; 15] = [
+    75, 76, 74, 78, 132, 33, 133, 140, 212, 156, 156, 124, 8, 9, 0,
+];
+const FIXED_BLOCK_CRC32: u32 = 0x5200_2f38;
+
+/// A single-entry archive holding `FIXED_BLOCK` deflated under `Budget.yfull`, with
+/// `declared` as its uncompressed size.
+fn deflated_archive(declared: u32) -> Vec<u8> {
+    let name = b"Budget.yfull";
+    let common = [
+        &[20, 0, 0, 0, 8, 0, 0, 0, 0, 0][..],
+        &FIXED_BLOCK_CRC32.to_le_bytes(),
+        &(FIXED_BLOCK.len() as u32).to_le_bytes(),
+        &declared.to_le_bytes(),
+        &(name.len() as u16).to_le_bytes(),
+        &[0, 0],
+    ]
+    .concat();
+    let local = [b"PK\x03\x04", &common[..], name, &FIXED_BLOCK].concat();
+    let central = [b"PK\x01\x02", &[20, 0][..], &common, &[0; 14], name].concat();
+    let end = [
+        b"PK\x05\x06",
+        &[0, 0, 0, 0, 1, 0, 1, 0][..],
+        &(central.len() as u32).to_le_bytes(),
+        &(local.len() as u32).to_le_bytes(),
+        &[0, 0],
+    ]
+    .concat();
+    [local, central, end].concat()
+}
+
+#[test]
+fn read_entry_inflates_a_deflated_entry() {
+    let contents = read_entry(&deflated_archive(24), |_| true).unwrap();
+    assert_eq!(contents, b"abcabcabcabc hello hello");
+}
+
+#[test]
+fn read_entry_rejects_entries_declared_over_the_size_limit() {
+    let err = read_entry(&deflated_archive(MAX_ENTRY_SIZE as u32 + 1), |_| true)
+        .err()
+        .unwrap();
+    insta::assert_snapshot!(err.to_string());
+}
+
+#[test]
+fn read_entry_rejects_entries_inflating_past_their_declared_size() {
+    let err = read_entry(&deflated_archive(10), |_| true).err().unwrap();
+    insta::assert_snapshot!(format!("{err:#}"));
+}
+
+#[test]
+fn read_entry_rejects_a_truncated_archive() {
+    let archive = deflated_archive(24);
+    let err = read_entry(&archive[..archive.len() - 30], |_| true)
+        .err()
+        .unwrap();
+    insta::assert_snapshot!(format!("{err:#}"));
+}